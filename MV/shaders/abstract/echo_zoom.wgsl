@@ -0,0 +1,26 @@
+@fragment
+fn fs_main(@builtin(position) coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let aspect = uniforms.resolution.x / uniforms.resolution.y;
+    let uv     = coord.xy / uniforms.resolution;
+    let p      = (uv - 0.5) * vec2<f32>(aspect, 1.0);
+
+    // Feed the previous frame back slightly zoomed in and rotated, so older
+    // frames spiral outwards and fade. Bass twists harder, beats zoom faster.
+    let angle   = 0.01 + uniforms.bass_energy * 0.03;
+    let zoom    = 0.97 - uniforms.beat_intensity * 0.03;
+    let rot     = mat2x2<f32>(cos(angle), -sin(angle), sin(angle), cos(angle));
+    let prev_uv = (rot * p) * zoom / vec2<f32>(aspect, 1.0) + 0.5;
+    let prev    = textureSample(prev_frame, prev_sampler, prev_uv).rgb * 0.94;
+
+    // Fresh spectrum ring in the centre, one bin per angle.
+    let radius    = length(p);
+    let angle_n   = atan2(p.y, p.x) / 6.2831853 + 0.5;
+    let valid_len = arrayLength(&data) / 2u;
+    let idx       = min(u32(angle_n * f32(valid_len)), valid_len - 1u);
+    let magnitude = max(data[idx] * uniforms.intensity, 0.0);
+    let ring_r    = 0.12 + magnitude * 0.15;
+    let ring      = smoothstep(0.012, 0.0, abs(radius - ring_r));
+    let col       = hsv_to_rgb(angle_n + uniforms.time * 0.1, 0.8, 1.0) * ring * uniforms.color.rgb;
+
+    return vec4<f32>(max(prev, col), 1.0);
+}
@@ -0,0 +1,65 @@
+// Buffer A: Gray-Scott reaction-diffusion. r = 1 - u, g = v, so the zeroed
+// start state is the stable "all u" rest state. The spectrum seeds v.
+@fragment
+fn fs_buffer_a(@builtin(position) coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let size = vec2<i32>(textureDimensions(buffer_a));
+    let p    = vec2<i32>(coord.xy);
+
+    var lap    = vec2<f32>(0.0);
+    var centre = vec2<f32>(0.0);
+    for (var dy = -1; dy <= 1; dy++) {
+        for (var dx = -1; dx <= 1; dx++) {
+            let q = (p + vec2<i32>(dx, dy) + size) % size;
+            let s = textureLoad(buffer_a, q, 0).rg;
+            let uv = vec2<f32>(1.0 - s.r, s.g);
+            var w = 0.05;
+            if dx == 0 || dy == 0 { w = 0.2; }
+            if dx == 0 && dy == 0 { w = -1.0; centre = uv; }
+            lap += uv * w;
+        }
+    }
+
+    let feed = 0.037 + uniforms.bass_energy * 0.02;
+    let kill = 0.06;
+    let reaction = centre.x * centre.y * centre.y;
+    var u = centre.x + (1.0 * lap.x - reaction + feed * (1.0 - centre.x));
+    var v = centre.y + (0.5 * lap.y + reaction - (kill + feed) * centre.y);
+
+    // Seed v along a spectrum ring; beats throw in a random blot.
+    let res       = uniforms.resolution;
+    let aspect    = res.x / res.y;
+    let pn        = (coord.xy / res - 0.5) * vec2<f32>(aspect, 1.0);
+    let angle_n   = atan2(pn.y, pn.x) / 6.2831853 + 0.5;
+    let valid_len = arrayLength(&data) / 2u;
+    let idx       = min(u32(angle_n * f32(valid_len)), valid_len - 1u);
+    let magnitude = max(data[idx] * uniforms.intensity, 0.0);
+    let ring      = smoothstep(0.006, 0.0, abs(length(pn) - 0.25)) * step(0.3, magnitude);
+    let blot_at   = vec2<f32>(rand2(vec2<f32>(floor(uniforms.time * 4.0), 1.0)), rand2(vec2<f32>(floor(uniforms.time * 4.0), 2.0))) - 0.5;
+    let blot      = smoothstep(0.03, 0.0, length(pn - blot_at * vec2<f32>(aspect, 1.0))) * step(0.5, uniforms.beat_intensity);
+    v = max(v, max(ring, blot) * 0.5);
+
+    u = clamp(u, 0.0, 1.0);
+    v = clamp(v, 0.0, 1.0);
+    return vec4<f32>(1.0 - u, v, 0.0, 1.0);
+}
+
+// Buffer B: slowly decaying glow of the reaction fronts.
+@fragment
+fn fs_buffer_b(@builtin(position) coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let p     = vec2<i32>(coord.xy);
+    let v     = textureLoad(buffer_a, p, 0).g;
+    let glow  = textureLoad(buffer_b, p, 0).r * 0.96;
+    let front = smoothstep(0.15, 0.35, v);
+    return vec4<f32>(max(glow, front), 0.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let uv   = coord.xy / uniforms.resolution;
+    let v    = textureSample(buffer_a, buffer_sampler, uv).g;
+    let glow = textureSample(buffer_b, buffer_sampler, uv).r;
+
+    let body = hsv_to_rgb(v * 1.5 + uniforms.time * 0.03, 0.75, smoothstep(0.1, 0.4, v));
+    let halo = hsv_to_rgb(0.6 + uniforms.time * 0.03, 0.6, 1.0) * glow * 0.35;
+    return vec4<f32>((body + halo) * uniforms.color.rgb * (1.0 + uniforms.beat_intensity * 0.5), 1.0);
+}
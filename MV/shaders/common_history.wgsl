@@ -1,22 +1,5 @@
 // common_history.wgsl — common.wgsl plus the waveform-history binding.
 
-struct Uniforms {
-    color: vec4<f32>,
-    intensity: f32,
-    padding1: f32,
-    resolution: vec2<f32>,
-    mode: u32,
-    padding3a: u32,
-    padding3b: u32,
-    padding3c: u32,
-    padding2: vec3<u32>,
-    time: f32,
-    bass_energy: f32,
-    smoothing_factor: f32,
-    gain: f32,
-    beat_intensity: f32,
-};
-
 @group(0) @binding(0) var<uniform> uniforms: Uniforms;
 @group(0) @binding(1) var<storage, read> data: array<f32>;
 @group(0) @binding(2) var<storage, read> history: array<f32>;
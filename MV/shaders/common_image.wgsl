@@ -0,0 +1,61 @@
+// common_image.wgsl — common.wgsl plus a user-assigned image.
+//
+// `user_image` is the picture assigned to this shader in the shader browser
+// (or dropped on the window), mipmapped and in sRGB; until one is assigned it
+// holds the MV logo. `image_info.size` is its size in pixels.
+
+struct ImageInfo {
+    size: vec2<f32>,
+    padding: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(0) @binding(1) var<storage, read> data: array<f32>;
+@group(1) @binding(0) var user_image: texture_2d<f32>;
+@group(1) @binding(1) var image_sampler: sampler;
+@group(1) @binding(2) var<uniform> image_info: ImageInfo;
+
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32) -> @builtin(position) vec4<f32> {
+    var pos = array<vec2<f32>, 3>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>( 3.0, -1.0),
+        vec2<f32>(-1.0,  3.0),
+    );
+    return vec4<f32>(pos[idx], 0.0, 1.0);
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> vec3<f32> {
+    let hh = fract(h) * 6.0;
+    let i  = floor(hh);
+    let f  = hh - i;
+    let p  = v * (1.0 - s);
+    let q  = v * (1.0 - s * f);
+    let t  = v * (1.0 - s * (1.0 - f));
+    let ii = u32(i) % 6u;
+    if ii == 0u { return vec3<f32>(v, t, p); }
+    if ii == 1u { return vec3<f32>(q, v, p); }
+    if ii == 2u { return vec3<f32>(p, v, t); }
+    if ii == 3u { return vec3<f32>(p, q, v); }
+    if ii == 4u { return vec3<f32>(t, p, v); }
+    return vec3<f32>(v, p, q);
+}
+
+fn rand2(seed: vec2<f32>) -> f32 {
+    return fract(sin(dot(seed, vec2<f32>(127.1, 311.7))) * 43758.5453);
+}
+
+// Image UV for aspect-corrected screen coordinates `p` (centred at 0, y down,
+// -0.5..0.5 from top to bottom): the image fitted inside a square of side `scale`.
+fn image_uv(p: vec2<f32>, scale: f32) -> vec2<f32> {
+    let aspect = image_info.size.x / max(image_info.size.y, 1.0);
+    let half = select(vec2<f32>(0.5, 0.5 / aspect), vec2<f32>(0.5 * aspect, 0.5), aspect < 1.0) * scale;
+    return p / (2.0 * half) + 0.5;
+}
+
+// The image at `uv`, transparent outside 0..1.
+fn sample_image(uv: vec2<f32>) -> vec4<f32> {
+    let c = textureSample(user_image, image_sampler, uv);
+    let inside = all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0));
+    return select(vec4<f32>(0.0), c, inside);
+}
@@ -0,0 +1,40 @@
+// common_mesh.wgsl — definitions for mesh plugins that rasterise real geometry.
+//
+// The plugin's base shape arrives as `VertexInput` and is drawn once per
+// instance; `vs_main` places each instance (usually from the spectrum) and
+// projects it with the shared orbit `camera`. A depth buffer is attached.
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(0) @binding(1) var<storage, read> data: array<f32>;
+
+struct Camera {
+    view_proj: mat4x4<f32>,
+    eye: vec4<f32>,
+};
+
+@group(1) @binding(0) var<uniform> camera: Camera;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> vec3<f32> {
+    let hh = fract(h) * 6.0;
+    let i  = floor(hh);
+    let f  = hh - i;
+    let p  = v * (1.0 - s);
+    let q  = v * (1.0 - s * f);
+    let t  = v * (1.0 - s * (1.0 - f));
+    let ii = u32(i) % 6u;
+    if ii == 0u { return vec3<f32>(v, t, p); }
+    if ii == 1u { return vec3<f32>(q, v, p); }
+    if ii == 2u { return vec3<f32>(p, v, t); }
+    if ii == 3u { return vec3<f32>(p, q, v); }
+    if ii == 4u { return vec3<f32>(t, p, v); }
+    return vec3<f32>(v, p, q);
+}
+
+fn rand2(seed: vec2<f32>) -> f32 {
+    return fract(sin(dot(seed, vec2<f32>(127.1, 311.7))) * 43758.5453);
+}
@@ -0,0 +1,44 @@
+// common_multibuffer.wgsl — common.wgsl plus persistent Buffer A/B/C textures.
+//
+// A multi-buffer shader defines `fs_buffer_a` (and optionally `fs_buffer_b`,
+// `fs_buffer_c`), run in that order each frame, each writing its own buffer.
+// Every pass, including the final `fs_main`, sees the newest contents of all
+// buffers: this frame's output for passes that already ran, last frame's
+// for the pass itself and those after it. Buffers start out zeroed.
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(0) @binding(1) var<storage, read> data: array<f32>;
+@group(1) @binding(0) var buffer_a: texture_2d<f32>;
+@group(1) @binding(1) var buffer_b: texture_2d<f32>;
+@group(1) @binding(2) var buffer_c: texture_2d<f32>;
+@group(1) @binding(3) var buffer_sampler: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32) -> @builtin(position) vec4<f32> {
+    var pos = array<vec2<f32>, 3>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>( 3.0, -1.0),
+        vec2<f32>(-1.0,  3.0),
+    );
+    return vec4<f32>(pos[idx], 0.0, 1.0);
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> vec3<f32> {
+    let hh = fract(h) * 6.0;
+    let i  = floor(hh);
+    let f  = hh - i;
+    let p  = v * (1.0 - s);
+    let q  = v * (1.0 - s * f);
+    let t  = v * (1.0 - s * (1.0 - f));
+    let ii = u32(i) % 6u;
+    if ii == 0u { return vec3<f32>(v, t, p); }
+    if ii == 1u { return vec3<f32>(q, v, p); }
+    if ii == 2u { return vec3<f32>(p, v, t); }
+    if ii == 3u { return vec3<f32>(p, q, v); }
+    if ii == 4u { return vec3<f32>(t, p, v); }
+    return vec3<f32>(v, p, q);
+}
+
+fn rand2(seed: vec2<f32>) -> f32 {
+    return fract(sin(dot(seed, vec2<f32>(127.1, 311.7))) * 43758.5453);
+}
@@ -0,0 +1,79 @@
+// Boids flocking. Each step reads `boids` and writes `next`, then
+// `cs_copy` publishes the result for the render pass and the next frame.
+
+struct Boid {
+    pos: vec2<f32>,
+    vel: vec2<f32>,
+};
+
+@group(1) @binding(0) var<storage, read_write> boids: array<Boid>;
+@group(1) @binding(1) var<storage, read_write> next: array<Boid>;
+
+const NEIGHBOUR_RADIUS: f32 = 0.08;
+const SEPARATION_RADIUS: f32 = 0.025;
+
+@compute @workgroup_size(64)
+fn cs_step(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    let n = arrayLength(&boids);
+    if i >= n { return; }
+
+    var me = boids[i];
+    // Buffers start zeroed: scatter boids the first time they are seen.
+    if all(me.vel == vec2<f32>(0.0)) {
+        let seed = vec2<f32>(f32(i), 7.0);
+        me.pos = vec2<f32>(rand2(seed), rand2(seed + 1.0)) * 2.0 - 1.0;
+        me.vel = normalize(vec2<f32>(rand2(seed + 2.0), rand2(seed + 3.0)) - 0.5 + 1e-4) * 0.005;
+    }
+
+    var centre   = vec2<f32>(0.0);
+    var heading  = vec2<f32>(0.0);
+    var separate = vec2<f32>(0.0);
+    var count    = 0.0;
+    for (var j = 0u; j < n; j++) {
+        if j == i { continue; }
+        let other = boids[j];
+        let d = other.pos - me.pos;
+        let dist = length(d);
+        if dist < NEIGHBOUR_RADIUS {
+            centre  += other.pos;
+            heading += other.vel;
+            count   += 1.0;
+            if dist < SEPARATION_RADIUS && dist > 0.0 {
+                separate -= d / (dist * dist);
+            }
+        }
+    }
+
+    // Low bands tighten the flock, high bands make it skittish.
+    let valid_len = arrayLength(&data) / 2u;
+    let low  = max(data[2u] * uniforms.intensity, 0.0);
+    let high = max(data[valid_len / 2u] * uniforms.intensity, 0.0);
+
+    var vel = me.vel;
+    if count > 0.0 {
+        vel += (centre / count - me.pos) * (0.002 + low * 0.004);
+        vel += (heading / count - me.vel) * 0.05;
+    }
+    vel += separate * 0.00002 * (1.0 + high * 4.0);
+
+    // Beats push everyone away from the centre.
+    vel += normalize(me.pos + 1e-4) * uniforms.beat_intensity * 0.004;
+
+    let max_speed = 0.006 + uniforms.bass_energy * 0.012;
+    let speed = length(vel);
+    if speed > max_speed { vel *= max_speed / speed; }
+    if speed < 0.002 { vel *= 0.002 / max(speed, 1e-5); }
+
+    var pos = me.pos + vel;
+    // Wrap around the screen edges.
+    pos = fract((pos + 1.0) * 0.5) * 2.0 - 1.0;
+
+    next[i] = Boid(pos, vel);
+}
+
+@compute @workgroup_size(64)
+fn cs_copy(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= arrayLength(&boids) { return; }
+    boids[id.x] = next[id.x];
+}
@@ -0,0 +1,41 @@
+// Draws each boid as a small triangle pointing along its velocity.
+
+struct Boid {
+    pos: vec2<f32>,
+    vel: vec2<f32>,
+};
+
+@group(1) @binding(0) var<storage, read> boids: array<Boid>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vi: u32, @builtin(instance_index) ii: u32) -> VertexOutput {
+    let boid = boids[ii];
+    let dir  = normalize(boid.vel + vec2<f32>(1e-6, 0.0));
+    let side = vec2<f32>(-dir.y, dir.x);
+
+    var shape = array<vec2<f32>, 3>(
+        vec2<f32>( 1.0,  0.0),
+        vec2<f32>(-0.6,  0.5),
+        vec2<f32>(-0.6, -0.5),
+    );
+    let size   = 0.012 + uniforms.beat_intensity * 0.006;
+    let local  = shape[vi] * size;
+    let aspect = uniforms.resolution.x / uniforms.resolution.y;
+    let pos    = boid.pos + (dir * local.x + side * local.y) / vec2<f32>(aspect, 1.0);
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(pos, 0.0, 1.0);
+    let hue = atan2(dir.y, dir.x) / 6.2831853 + uniforms.time * 0.05;
+    out.color = hsv_to_rgb(hue, 0.6, 1.0) * uniforms.color.rgb;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color * 0.8, 1.0);
+}
@@ -2,25 +2,23 @@ struct Particle {
     position: vec2<f32>,
     velocity: vec2<f32>,
     lifetime: f32,
-    padding: vec3<f32>,
+    max_lifetime: f32,
+    padding: vec2<f32>,
     color: vec4<f32>,
 };
 
-struct Uniforms {
-    color: vec4<f32>,
-    intensity: f32,
-    padding1: f32,
-    resolution: vec2<f32>,
-    mode: u32,
-    padding3a: u32,
-    padding3b: u32,
-    padding3c: u32,
-    padding2: vec3<u32>,
-    time: f32,
-    bass_energy: f32,
-    smoothing_factor: f32,
-    gain: f32,
-    beat_intensity: f32,
+struct Params {
+    count: u32,
+    emitter: u32,      // 0 floor, 1 centre, 2 ring, 3 screen
+    color_mode: u32,   // 0 scheme, 1 rainbow, 2 spectrum
+    burst: u32,
+    lifetime: f32,
+    gravity: f32,
+    dt: f32,
+    burst_fraction: f32,
+    emit_pos: vec2<f32>,  // click position in clip space
+    emit: u32,            // 1 on the frame of a click
+    emit_fraction: f32,
 };
 
 @group(0) @binding(0)
@@ -32,40 +30,103 @@ var<storage, read> fft_data: array<f32>;
 @group(0) @binding(2)
 var<uniform> uniforms: Uniforms;
 
+@group(0) @binding(3)
+var<uniform> params: Params;
+
+const TAU: f32 = 6.2831853;
+
+// PCG hash mapped to 0..1; `stream` picks independent values per particle.
+fn rand(idx: u32, stream: u32) -> f32 {
+    var state = idx * 747796405u + stream * 2891336453u + u32(uniforms.time * 1000.0);
+    state = state * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return f32((word >> 22u) ^ word) / 4294967295.0;
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> vec3<f32> {
+    let k = vec3<f32>(1.0, 2.0 / 3.0, 1.0 / 3.0);
+    let p = abs(fract(vec3<f32>(h) + k) * 6.0 - 3.0);
+    return v * mix(vec3<f32>(1.0), clamp(p - 1.0, vec3<f32>(0.0), vec3<f32>(1.0)), s);
+}
+
+fn spawn_color(idx: u32, along: f32) -> vec3<f32> {
+    switch params.color_mode {
+        case 1u: {
+            return hsv_to_rgb(fract(rand(idx, 4u) * 0.3 + uniforms.time * 0.05), 0.8, 1.0);
+        }
+        case 2u: {
+            let half_len = arrayLength(&fft_data) / 2u;
+            let bin = min(u32(along * f32(half_len)), half_len - 1u);
+            let energy = clamp(abs(fft_data[bin]) * uniforms.intensity, 0.0, 1.0);
+            return hsv_to_rgb(along * 0.8, 0.9, 0.4 + energy * 0.6);
+        }
+        default: {
+            return uniforms.color.rgb;
+        }
+    }
+}
+
+fn spawn(idx: u32, boost: f32) -> Particle {
+    var p: Particle;
+    let r0 = rand(idx, 0u);
+    let angle = rand(idx, 1u) * TAU;
+    let aspect = uniforms.resolution.x / max(uniforms.resolution.y, 1.0);
+    let dir = vec2<f32>(cos(angle) / aspect, sin(angle));
+    let speed = 0.4 + uniforms.bass_energy * 1.5 + boost;
+
+    // `along` runs 0..1 across the emitter and selects the spectrum colour.
+    var along = r0;
+    switch params.emitter {
+        case 1u: {
+            p.position = vec2<f32>(0.0, 0.0);
+            p.velocity = dir * speed * 0.6;
+            along = angle / TAU;
+        }
+        case 2u: {
+            p.position = dir * 0.5;
+            p.velocity = dir * speed * 0.4;
+            along = angle / TAU;
+        }
+        case 3u: {
+            p.position = vec2<f32>(r0 * 2.0 - 1.0, rand(idx, 2u) * 2.0 - 1.0);
+            p.velocity = dir * (0.05 + boost * 0.5);
+        }
+        default: {
+            p.position = vec2<f32>(r0 * 2.0 - 1.0, -1.0);
+            p.velocity = vec2<f32>((rand(idx, 2u) - 0.5) * 0.3, speed);
+        }
+    }
+
+    p.max_lifetime = params.lifetime * (0.75 + 0.5 * rand(idx, 3u));
+    p.lifetime = p.max_lifetime;
+    p.color = vec4<f32>(spawn_color(idx, along), 1.0);
+    return p;
+}
+
+// A particle thrown out of a click at `at`.
+fn spawn_click(idx: u32, at: vec2<f32>) -> Particle {
+    var p = spawn(idx, 0.0);
+    let angle = rand(idx, 6u) * TAU;
+    let aspect = uniforms.resolution.x / max(uniforms.resolution.y, 1.0);
+    p.position = at;
+    p.velocity = vec2<f32>(cos(angle) / aspect, sin(angle)) * (0.3 + rand(idx, 7u) * 1.2);
+    return p;
+}
+
 @compute @workgroup_size(64)
 fn main(@builtin(global_invocation_id) id: vec3<u32>) {
     let idx = id.x;
-    if (idx >= arrayLength(&particles)) {
+    if (idx >= params.count) {
         return;
     }
 
     var p = particles[idx];
 
-    let len = arrayLength(&fft_data);
-    var scale: f32;
-    var num_bins: u32;
-    if (uniforms.mode == 0u) { // spectrum
-        scale = f32(len) / 2.0;
-        num_bins = 10u;
-    } else {
-        scale = 1.0;
-        num_bins = len / 10u; // fewer bins for waveform
-    }
-
-    var bass = 0.0;
-    for (var i = 0u; i < num_bins; i = i + 1u) {
-        if (i < len) {
-            bass += abs(fft_data[i]);
-        }
-    }
-    bass /= f32(num_bins) * scale;
-    bass *= uniforms.intensity * 5.0; // amplify
-
-    p.velocity.y += bass * 0.5;
-    p.velocity.y -= 0.01; // gravity
-    p.velocity *= 0.98; // damping
-    p.position += p.velocity;
-    p.lifetime -= 0.01;
+    let dt = params.dt;
+    p.velocity.y -= params.gravity * dt;
+    p.velocity *= exp(-1.2 * dt); // damping
+    p.position += p.velocity * dt;
+    p.lifetime -= dt;
 
     // Bounce off bottom
     if (p.position.y < -1.0) {
@@ -81,12 +142,16 @@ fn main(@builtin(global_invocation_id) id: vec3<u32>) {
         p.velocity.x = -p.velocity.x * 0.8;
     }
 
-    if (p.lifetime <= 0.0 || p.position.y < -1.0) {
-        p.position = vec2<f32>((f32(idx) / f32(arrayLength(&particles)) - 0.5) * 2.0, -1.0);
-        p.velocity = vec2<f32>(0.0, bass * 2.0);
-        p.lifetime = 2.0 + bass;
-        p.color = uniforms.color;
+    if (params.emit != 0u && rand(idx, 8u) < params.emit_fraction) {
+        p = spawn_click(idx, params.emit_pos);
+    } else if (params.burst != 0u && rand(idx, 5u) < params.burst_fraction) {
+        p = spawn(idx, 1.0 + uniforms.beat_intensity);
+    } else if (p.lifetime <= 0.0) {
+        p = spawn(idx, 0.0);
     }
 
+    // Fade out over the last half of the particle's life.
+    p.color.a = clamp(2.0 * p.lifetime / max(p.max_lifetime, 0.001), 0.0, 1.0);
+
     particles[idx] = p;
 }
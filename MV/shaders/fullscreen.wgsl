@@ -0,0 +1,11 @@
+// fullscreen.wgsl — vertex stage for plugins whose fragment stage is not WGSL.
+
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32) -> @builtin(position) vec4<f32> {
+    var pos = array<vec2<f32>, 3>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>( 3.0, -1.0),
+        vec2<f32>(-1.0,  3.0),
+    );
+    return vec4<f32>(pos[idx], 0.0, 1.0);
+}
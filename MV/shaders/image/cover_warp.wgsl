@@ -0,0 +1,31 @@
+// cover_warp.wgsl — the assigned image filling the screen, rippled by the spectrum.
+
+@fragment
+fn fs_main(@builtin(position) coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let aspect = uniforms.resolution.x / uniforms.resolution.y;
+    let p      = (coord.xy / uniforms.resolution - 0.5) * vec2<f32>(aspect, 1.0);
+    let radius = length(p);
+
+    // Each radius ripples with its own frequency band
+    let valid_len = arrayLength(&data) / 2u;
+    let norm_r    = clamp(radius / (0.5 * aspect), 0.0, 1.0);
+    let freq_idx  = min(u32(norm_r * f32(valid_len) * 0.5), valid_len - 1u);
+    let magnitude = max(data[freq_idx] * uniforms.intensity, 0.0);
+    let wave      = sin(radius * 40.0 - uniforms.time * 4.0) * magnitude * 0.02;
+    let dir       = select(vec2<f32>(0.0), p / radius, radius > 0.0001);
+
+    // Cover the screen: scale the fitted image up to the longer screen side
+    let img_aspect = image_info.size.x / max(image_info.size.y, 1.0);
+    let fit        = select(max(aspect / img_aspect, 1.0), max(aspect, img_aspect), img_aspect >= 1.0);
+    let cover      = fit * (1.0 + uniforms.bass_energy * 0.05);
+    let q          = p + dir * wave;
+
+    // Beat-driven chromatic split
+    let split = dir * uniforms.beat_intensity * 0.015;
+    let r = sample_image(image_uv(q + split, cover)).r;
+    let g = sample_image(image_uv(q, cover)).g;
+    let b = sample_image(image_uv(q - split, cover)).b;
+
+    let col = vec3<f32>(r, g, b) * mix(vec3<f32>(1.0), uniforms.color.rgb, 0.3) * (0.85 + uniforms.bass_energy * 0.3);
+    return vec4<f32>(col, 1.0);
+}
@@ -0,0 +1,28 @@
+// logo_pulse.wgsl — the assigned image pulsing with the bass, ringed by the spectrum.
+
+@fragment
+fn fs_main(@builtin(position) coord: vec4<f32>) -> @location(0) vec4<f32> {
+    let aspect = uniforms.resolution.x / uniforms.resolution.y;
+    let p      = (coord.xy / uniforms.resolution - 0.5) * vec2<f32>(aspect, 1.0);
+    let radius = length(p);
+    let angle  = atan2(p.y, p.x);
+
+    // Image grows with the bass and kicks on each beat
+    let scale = 0.5 * (1.0 + uniforms.bass_energy * 0.25 * uniforms.intensity + uniforms.beat_intensity * 0.12);
+    let img   = sample_image(image_uv(p, scale));
+
+    // Spectrum ring around the image, mirrored left/right
+    let valid_len = arrayLength(&data) / 2u;
+    let t         = abs(angle) / 3.14159265;
+    let freq_idx  = min(u32(t * f32(valid_len) * 0.5), valid_len - 1u);
+    let magnitude = max(data[freq_idx] * uniforms.intensity, 0.0);
+    let ring_r    = scale * 0.62;
+    let ring      = smoothstep(0.012, 0.0, abs(radius - ring_r - magnitude * 0.12) - magnitude * 0.02);
+    let ring_col  = hsv_to_rgb(t * 0.6 + uniforms.time * 0.05, 0.8, 1.0) * uniforms.color.rgb * ring;
+
+    // Glow behind the image that flares on beats
+    let glow = exp(-radius * 4.0) * (0.15 + uniforms.beat_intensity * 0.5) * uniforms.color.rgb;
+
+    let col = mix(glow + ring_col, img.rgb * (1.0 + uniforms.beat_intensity * 0.4), img.a);
+    return vec4<f32>(col, 1.0);
+}
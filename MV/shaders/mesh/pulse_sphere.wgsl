@@ -0,0 +1,39 @@
+// A sphere whose surface is pushed out along its normals by the spectrum,
+// low frequencies at the poles and highs at the equator.
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) level: f32,
+};
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    let latitude  = 1.0 - abs(in.position.y);
+    let valid_len = arrayLength(&data) / 2u;
+    let idx       = min(u32(latitude * f32(valid_len) * 0.5), valid_len - 1u);
+    let level     = clamp(data[idx] * uniforms.intensity, 0.0, 1.0);
+
+    let ripple = sin(atan2(in.position.z, in.position.x) * 8.0 + uniforms.time * 2.0) * 0.05;
+    let radius = 1.2 + level * 0.8 + uniforms.beat_intensity * 0.25 + ripple * level;
+    let world  = in.position * radius;
+
+    var out: VertexOutput;
+    out.position = camera.view_proj * vec4<f32>(world, 1.0);
+    out.world    = world;
+    out.normal   = in.normal;
+    out.level    = level;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let n       = normalize(in.normal);
+    let view    = normalize(camera.eye.xyz - in.world);
+    let light   = normalize(vec3<f32>(0.5, 0.8, 0.4));
+    let diffuse = max(dot(n, light), 0.0);
+    let rim     = pow(1.0 - max(dot(n, view), 0.0), 3.0);
+    let base    = hsv_to_rgb(0.6 - in.level * 0.5 + uniforms.time * 0.03, 0.75, 1.0) * uniforms.color.rgb;
+    return vec4<f32>(base * (0.15 + diffuse * 0.7) + base * rim * (1.0 + in.level * 2.0), 1.0);
+}
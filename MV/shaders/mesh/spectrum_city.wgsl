@@ -0,0 +1,47 @@
+// A grid of towers, one frequency bin per ring around the centre, whose
+// heights follow the spectrum. Rasterised cubes instead of ray-marching.
+
+const GRID: u32 = 32u;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) hue: f32,
+    @location(3) level: f32,
+};
+
+@vertex
+fn vs_main(in: VertexInput, @builtin(instance_index) ii: u32) -> VertexOutput {
+    let cell   = vec2<f32>(f32(ii % GRID), f32(ii / GRID)) - f32(GRID - 1u) * 0.5;
+    let radius = length(cell) / (f32(GRID) * 0.5 * 1.4143);
+
+    let valid_len = arrayLength(&data) / 2u;
+    let idx       = min(u32(radius * radius * f32(valid_len)), valid_len - 1u);
+    let level     = clamp(data[idx] * uniforms.intensity, 0.0, 1.0);
+    let height    = 0.05 + level * 2.5 + uniforms.beat_intensity * 0.2 * (1.0 - radius);
+
+    let spacing = 0.16;
+    let scale   = vec3<f32>(spacing * 0.8, height, spacing * 0.8);
+    let offset  = vec3<f32>(cell.x * spacing, height * 0.5, cell.y * spacing);
+    let world   = in.position * scale + offset;
+
+    var out: VertexOutput;
+    out.position = camera.view_proj * vec4<f32>(world, 1.0);
+    out.world    = world;
+    out.normal   = in.normal;
+    out.hue      = radius * 0.8 + uniforms.time * 0.02;
+    out.level    = level;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let light   = normalize(vec3<f32>(0.4, 1.0, 0.3));
+    let diffuse = max(dot(normalize(in.normal), light), 0.0) * 0.7 + 0.3;
+    let base    = hsv_to_rgb(in.hue, 0.7, 1.0) * uniforms.color.rgb;
+    // Tower tops glow with their level so bloom can pick them up.
+    let glow    = select(0.0, in.level * 2.0, in.normal.y > 0.5);
+    let fog     = exp(-0.08 * length(in.world - camera.eye.xyz));
+    return vec4<f32>(base * (diffuse + glow) * fog, 1.0);
+}
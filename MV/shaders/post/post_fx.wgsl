@@ -0,0 +1,178 @@
+// post_fx.wgsl — fullscreen post-processing passes applied to the HDR scene target.
+//
+// Every pass shares one bind group layout:
+//   binding 1 = the pass input, binding 2 = an auxiliary input
+//   (previous trail frame for fs_trails, blurred bloom for fs_composite).
+
+struct PostUniforms {
+    time: f32,
+    beat_intensity: f32,
+    bass_energy: f32,
+    flags: u32,
+    bloom_threshold: f32,
+    bloom_intensity: f32,
+    trails_decay: f32,
+    chromatic_amount: f32,
+    grain_amount: f32,
+    vignette_strength: f32,
+    opacity: f32,
+    padding: f32,
+};
+
+// NOTE: must match the FX_* constants in graphics/postprocess.rs
+const FX_BLOOM:     u32 = 1u;
+const FX_CHROMATIC: u32 = 4u;
+const FX_GRAIN:     u32 = 8u;
+const FX_VIGNETTE:  u32 = 16u;
+const FX_TRANSPARENT:   u32 = 32u;
+const FX_PREMULTIPLIED: u32 = 64u;
+
+@group(0) @binding(0) var<uniform> post: PostUniforms;
+@group(0) @binding(1) var src_tex: texture_2d<f32>;
+@group(0) @binding(2) var aux_tex: texture_2d<f32>;
+@group(0) @binding(3) var linear_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32) -> VertexOutput {
+    var pos = array<vec2<f32>, 3>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>( 3.0, -1.0),
+        vec2<f32>(-1.0,  3.0),
+    );
+    var out: VertexOutput;
+    out.position = vec4<f32>(pos[idx], 0.0, 1.0);
+    out.uv = vec2<f32>(pos[idx].x * 0.5 + 0.5, 0.5 - pos[idx].y * 0.5);
+    return out;
+}
+
+fn luminance(c: vec3<f32>) -> f32 {
+    return dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+fn hash(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+}
+
+// ── Trails ──────────────────────────────────────────────────────────────────
+
+@fragment
+fn fs_trails(in: VertexOutput) -> @location(0) vec4<f32> {
+    let current  = textureSample(src_tex, linear_sampler, in.uv);
+    let previous = textureSample(aux_tex, linear_sampler, in.uv);
+    return vec4<f32>(max(current.rgb, previous.rgb * post.trails_decay), current.a);
+}
+
+// ── Bloom ───────────────────────────────────────────────────────────────────
+
+@fragment
+fn fs_bright(in: VertexOutput) -> @location(0) vec4<f32> {
+    let col  = textureSample(src_tex, linear_sampler, in.uv).rgb;
+    let knee = smoothstep(post.bloom_threshold, post.bloom_threshold + 0.25, luminance(col));
+    return vec4<f32>(col * knee, 1.0);
+}
+
+// 9-tap separable Gaussian (sigma ≈ 2 texels).
+fn blur(uv: vec2<f32>, dir: vec2<f32>) -> vec4<f32> {
+    let texel = dir / vec2<f32>(textureDimensions(src_tex));
+    var weights = array<f32, 5>(0.2270270, 0.1945946, 0.1216216, 0.0540540, 0.0162162);
+    var col = textureSample(src_tex, linear_sampler, uv).rgb * weights[0];
+    for (var i = 1; i < 5; i++) {
+        let offset = texel * f32(i);
+        col += textureSample(src_tex, linear_sampler, uv + offset).rgb * weights[i];
+        col += textureSample(src_tex, linear_sampler, uv - offset).rgb * weights[i];
+    }
+    return vec4<f32>(col, 1.0);
+}
+
+@fragment
+fn fs_blur_h(in: VertexOutput) -> @location(0) vec4<f32> {
+    return blur(in.uv, vec2<f32>(1.0, 0.0));
+}
+
+@fragment
+fn fs_blur_v(in: VertexOutput) -> @location(0) vec4<f32> {
+    return blur(in.uv, vec2<f32>(0.0, 1.0));
+}
+
+// ── Composite ───────────────────────────────────────────────────────────────
+
+// Catmull-Rom filter from nine bilinear taps, for upscaling a scene rendered
+// below the surface resolution. At 1:1 it reproduces the texels exactly.
+fn sample_catmull_rom(uv: vec2<f32>) -> vec4<f32> {
+    let size = vec2<f32>(textureDimensions(src_tex));
+    let pos = uv * size;
+    let center = floor(pos - 0.5) + 0.5;
+    let f = pos - center;
+
+    let w0 = f * (-0.5 + f * (1.0 - 0.5 * f));
+    let w1 = 1.0 + f * f * (-2.5 + 1.5 * f);
+    let w2 = f * (0.5 + f * (2.0 - 1.5 * f));
+    let w3 = f * f * (-0.5 + 0.5 * f);
+
+    // Merge the two middle taps into one bilinear fetch per axis.
+    let w12 = w1 + w2;
+    let t0 = (center - 1.0) / size;
+    let t3 = (center + 2.0) / size;
+    let t12 = (center + w2 / w12) / size;
+
+    var col = vec4<f32>(0.0);
+    col += textureSample(src_tex, linear_sampler, vec2<f32>(t0.x,  t0.y))  * w0.x  * w0.y;
+    col += textureSample(src_tex, linear_sampler, vec2<f32>(t12.x, t0.y))  * w12.x * w0.y;
+    col += textureSample(src_tex, linear_sampler, vec2<f32>(t3.x,  t0.y))  * w3.x  * w0.y;
+    col += textureSample(src_tex, linear_sampler, vec2<f32>(t0.x,  t12.y)) * w0.x  * w12.y;
+    col += textureSample(src_tex, linear_sampler, vec2<f32>(t12.x, t12.y)) * w12.x * w12.y;
+    col += textureSample(src_tex, linear_sampler, vec2<f32>(t3.x,  t12.y)) * w3.x  * w12.y;
+    col += textureSample(src_tex, linear_sampler, vec2<f32>(t0.x,  t3.y))  * w0.x  * w3.y;
+    col += textureSample(src_tex, linear_sampler, vec2<f32>(t12.x, t3.y))  * w12.x * w3.y;
+    col += textureSample(src_tex, linear_sampler, vec2<f32>(t3.x,  t3.y))  * w3.x  * w3.y;
+    return col;
+}
+
+@fragment
+fn fs_composite(in: VertexOutput) -> @location(0) vec4<f32> {
+    let base = sample_catmull_rom(in.uv);
+    var col  = base.rgb;
+
+    // Chromatic aberration: split R and B radially, stronger towards the edges.
+    let offset = (in.uv - 0.5) * post.chromatic_amount * 2.0;
+    let r = textureSample(src_tex, linear_sampler, in.uv + offset).r;
+    let b = textureSample(src_tex, linear_sampler, in.uv - offset).b;
+    if (post.flags & FX_CHROMATIC) != 0u {
+        col = vec3<f32>(r, col.g, b);
+    }
+
+    let bloom = textureSample(aux_tex, linear_sampler, in.uv).rgb;
+    if (post.flags & FX_BLOOM) != 0u {
+        col += bloom * post.bloom_intensity;
+    }
+
+    if (post.flags & FX_VIGNETTE) != 0u {
+        let d = distance(in.uv, vec2<f32>(0.5));
+        col *= 1.0 - smoothstep(0.3, 0.85, d) * post.vignette_strength;
+    }
+
+    if (post.flags & FX_GRAIN) != 0u {
+        let n = hash(in.position.xy + fract(post.time) * 1000.0) - 0.5;
+        col += vec3<f32>(n * post.grain_amount);
+    }
+
+    col = max(col, vec3<f32>(0.0));
+    if (post.flags & FX_TRANSPARENT) == 0u {
+        return vec4<f32>(col, 1.0);
+    }
+
+    // See-through window: dark pixels let the desktop through, bright ones
+    // cover it, all scaled by the window opacity.
+    col = min(col, vec3<f32>(1.0));
+    let coverage = clamp(max(col.r, max(col.g, col.b)) * 4.0, 0.0, 1.0);
+    let alpha = coverage * post.opacity;
+    if (post.flags & FX_PREMULTIPLIED) != 0u {
+        return vec4<f32>(col * post.opacity, alpha);
+    }
+    return vec4<f32>(col / max(coverage, 1e-4), alpha);
+}
@@ -0,0 +1,16 @@
+// Audio-reactive plasma written against the Shadertoy API.
+void mainImage(out vec4 fragColor, in vec2 fragCoord) {
+    vec2 uv = (fragCoord - 0.5 * iResolution.xy) / iResolution.y;
+
+    float bass = texture(iChannel0, vec2(0.02, 0.25)).x;
+    float wave = texture(iChannel0, vec2(fragCoord.x / iResolution.x, 0.75)).x;
+
+    float t = iTime * 0.5;
+    float v = sin(uv.x * 6.0 + t)
+            + sin(uv.y * 7.0 - t * 1.3)
+            + sin(length(uv) * (10.0 + bass * 20.0) - t * 2.0);
+    vec3 col = 0.5 + 0.5 * cos(v + vec3(0.0, 2.1, 4.2) + bass * 3.0);
+
+    float line = smoothstep(0.01, 0.0, abs(uv.y - (wave - 0.5) * 0.6));
+    fragColor = vec4(col * (0.4 + bass) + line, 1.0);
+}
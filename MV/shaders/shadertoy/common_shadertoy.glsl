@@ -0,0 +1,19 @@
+#version 450
+// common_shadertoy.glsl — Shadertoy-compatible inputs prepended to imported GLSL.
+//
+// iChannel0 is the audio texture: 512×2 texels, row 0 (y ≈ 0.25) holds the
+// spectrum and row 1 (y ≈ 0.75) the waveform centred on 0.5.
+// NOTE: the block layout must match ShadertoyUniforms in common/types.rs
+
+layout(set = 1, binding = 0) uniform ShadertoyUniforms {
+    vec3  iResolution;
+    float iTime;
+    vec4  iMouse;
+    float iTimeDelta;
+    int   iFrame;
+};
+layout(set = 1, binding = 1) uniform texture2D st_channel0;
+layout(set = 1, binding = 2) uniform sampler st_sampler;
+#define iChannel0 sampler2D(st_channel0, st_sampler)
+
+layout(location = 0) out vec4 st_frag_color;
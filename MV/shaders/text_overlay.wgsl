@@ -0,0 +1,39 @@
+// text_overlay.wgsl — one line of the text overlay, drawn as a textured quad
+// over the composite.
+//
+// The texture holds glyph coverage. A soft drop shadow keeps the text readable
+// over bright visuals; the output is premultiplied.
+
+struct TextUniforms {
+    rect: vec4<f32>,     // clip space: left, top, right, bottom
+    color: vec4<f32>,    // rgb, a = opacity × fade
+    shadow: vec2<f32>,   // shadow offset in texture UV
+    padding: vec2<f32>,
+};
+
+@group(0) @binding(0) var<uniform> text: TextUniforms;
+@group(0) @binding(1) var glyphs: texture_2d<f32>;
+@group(0) @binding(2) var glyph_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// Triangle strip: 0 = top-left, 1 = top-right, 2 = bottom-left, 3 = bottom-right.
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32) -> VertexOutput {
+    let corner = vec2<f32>(f32(idx & 1u), f32(idx >> 1u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(mix(text.rect.xy, text.rect.zw, corner), 0.0, 1.0);
+    out.uv = corner;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(glyphs, glyph_sampler, in.uv).r;
+    let shadow   = textureSample(glyphs, glyph_sampler, in.uv - text.shadow).r * 0.6;
+    let alpha    = coverage + shadow * (1.0 - coverage);
+    return vec4<f32>(text.color.rgb * coverage, alpha) * text.color.a;
+}
@@ -0,0 +1,43 @@
+// uniforms.wgsl — the per-frame uniforms, prepended to every shader that
+// reads them (before common*.wgsl).
+//
+// The layout must match `VisUniforms` and `AudioFeatures` in
+// src/common/types.rs; a test compares the two field by field. Fields are
+// only ever added, so shaders keep compiling as the block grows.
+
+// Bumped whenever fields are added to `AudioFeatures`.
+const AUDIO_FEATURES_VERSION: u32 = 1u;
+
+// Per-frame audio features. Levels are before gain, with a full-scale sine
+// reading 1.0.
+struct AudioFeatures {
+    version: u32,       // AUDIO_FEATURES_VERSION of the running MV
+    low: f32,           // amplitude below 500 Hz
+    mid: f32,           // amplitude from 500 Hz to 4 kHz
+    high: f32,          // amplitude above 4 kHz
+    rms: f32,
+    peak: f32,          // largest absolute sample
+    loudness: f32,      // RMS level from -60 dBFS (0) to 0 dBFS (1)
+    centroid: f32,      // spectral centroid as a fraction of Nyquist
+    flatness: f32,      // 0 = tonal, 1 = noise-like; 0 for silence
+    beat_phase: f32,    // 0 on a beat, rising to 1 at the next expected one
+    tempo: f32,         // estimated beats per minute, 0 until known
+    padding: f32,
+};
+
+struct Uniforms {
+    color: vec4<f32>,
+    resolution: vec2<f32>,
+    intensity: f32,
+    time: f32,
+    mode: u32,
+    bass_energy: f32,
+    smoothing_factor: f32,
+    gain: f32,
+    beat_intensity: f32,
+    mouse_buttons: u32,       // held buttons: 1 = left, 2 = right, 4 = middle
+    mouse: vec2<f32>,         // pointer position in pixels, origin top left
+    mouse_drag: vec2<f32>,    // offset from where the held buttons were pressed
+    mouse_scroll: vec2<f32>,  // scroll accumulated since start, in lines
+    features: AudioFeatures,
+};
@@ -0,0 +1,130 @@
+//! `--analyze`: per-frame features of an audio file as CSV or JSON Lines
+
+use super::{AnalysisSettings, Analyzer, BANDS};
+use crate::common::error::{AppError, AppResult};
+use crate::config::constants::SAMPLE_SIZE;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Output format of the feature export.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    JsonLines,
+}
+
+impl ExportFormat {
+    pub fn parse(name: &str) -> AppResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv"            => Ok(Self::Csv),
+            "jsonl" | "json" => Ok(Self::JsonLines),
+            _ => Err(AppError::Config(format!("Unknown format '{}' (expected csv or jsonl)", name))),
+        }
+    }
+
+    /// Format implied by the extension of `path`, CSV unless it is `.json`/`.jsonl`.
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("jsonl") || ext.eq_ignore_ascii_case("json") => Self::JsonLines,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// Analyse the WAV file `input` at `fps` frames per second and write one row
+/// per frame to `output`, or stdout for `None`.
+///
+/// Frame `i` analyses the `SAMPLE_SIZE` samples ending at `i / fps` seconds,
+/// so a frame rate matching the display gives the beats the live view shows.
+pub fn analyze_file(
+    input: &Path,
+    output: Option<&Path>,
+    format: ExportFormat,
+    fps: f32,
+    settings: AnalysisSettings,
+) -> AppResult<()> {
+    if fps.is_nan() || fps <= 0.0 {
+        return Err(AppError::Config(format!("--fps must be positive, got {}", fps)));
+    }
+    let (samples, sample_rate) = read_mono(input)?;
+    let settings = AnalysisSettings { sample_rate, spectrum: true, ..settings };
+
+    let mut writer: BufWriter<Box<dyn Write>> = BufWriter::new(match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(std::io::stdout().lock()),
+    });
+    if format == ExportFormat::Csv {
+        let bands: Vec<_> = BANDS.iter().map(|(name, _, _)| format!("band_{}", name)).collect();
+        writeln!(writer, "frame,time,rms,bass_energy,beat_intensity,beat,centroid_hz,{},peak,flatness,beat_phase,tempo_bpm", bands.join(","))?;
+    }
+
+    let mut analyzer = Analyzer::new();
+    let mut window = vec![0.0f32; SAMPLE_SIZE];
+    let duration = samples.len() as f64 / sample_rate as f64;
+    let frames = (duration * fps as f64).ceil() as u64;
+    for i in 0..frames {
+        let time = i as f64 / fps as f64;
+        let end = ((time * sample_rate as f64) as usize).min(samples.len());
+        let start = end.saturating_sub(SAMPLE_SIZE);
+        // Zero-pad the first frames, which reach back before the start.
+        let pad = SAMPLE_SIZE - (end - start);
+        window[..pad].fill(0.0);
+        window[pad..].copy_from_slice(&samples[start..end]);
+
+        let frame = analyzer.process(&window, &AnalysisSettings { time: time as f32, ..settings });
+        match format {
+            ExportFormat::Csv => {
+                let bands: Vec<_> = frame.bands.iter().map(|b| b.to_string()).collect();
+                writeln!(
+                    writer, "{},{:.6},{},{},{},{},{},{},{},{},{},{}",
+                    i, time, frame.rms, frame.bass_energy, frame.beat_intensity,
+                    frame.beat as u8, frame.centroid, bands.join(","),
+                    frame.peak, frame.flatness, frame.beat_phase, frame.tempo,
+                )?;
+            }
+            ExportFormat::JsonLines => {
+                let mut row = serde_json::json!({
+                    "frame": i,
+                    "time": time,
+                    "rms": frame.rms,
+                    "bass_energy": frame.bass_energy,
+                    "beat_intensity": frame.beat_intensity,
+                    "beat": frame.beat,
+                    "centroid_hz": frame.centroid,
+                    "peak": frame.peak,
+                    "flatness": frame.flatness,
+                    "beat_phase": frame.beat_phase,
+                    "tempo_bpm": frame.tempo,
+                });
+                for ((name, _, _), value) in BANDS.iter().zip(frame.bands) {
+                    row[format!("band_{}", name)] = value.into();
+                }
+                writeln!(writer, "{}", row)?;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Samples of a WAV file mixed down to mono, with its sample rate.
+fn read_mono(path: &Path) -> AppResult<(Vec<f32>, u32)> {
+    let reader = hound::WavReader::open(path)
+        .map_err(|e| AppError::Audio(format!("Cannot read {} as WAV: {}", path.display(), e)))?;
+    let spec = reader.spec();
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.into_samples::<f32>().collect::<Result<_, _>>(),
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader.into_samples::<i32>().map(|s| s.map(|s| s as f32 * scale)).collect()
+        }
+    }
+    .map_err(|e| AppError::Audio(format!("Failed to decode {}: {}", path.display(), e)))?;
+
+    let channels = spec.channels.max(1) as usize;
+    let mono = interleaved.chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+    Ok((mono, spec.sample_rate))
+}
@@ -0,0 +1,527 @@
+//! GPU-free audio analysis: spectrum, bass energy, beat detection, waveform
+//! history and per-frame features
+//!
+//! `GpuResources::update` runs an `Analyzer` and uploads the frame it
+//! returns; `--analyze` runs one over a file.
+
+pub mod export;
+
+use crate::config::constants::*;
+use rustfft::num_complex::Complex;
+use serde::{Deserialize, Serialize};
+
+/// Frequency bands reported in `AnalysisFrame::bands`: name, low and high edge in Hz.
+pub const BANDS: [(&str, f32, f32); 6] = [
+    ("bass",       20.0,    250.0),
+    ("low_mid",    250.0,   500.0),
+    ("mid",        500.0,   2000.0),
+    ("high_mid",   2000.0,  4000.0),
+    ("presence",   4000.0,  6000.0),
+    ("brilliance", 6000.0,  20000.0),
+];
+
+/// Sample rate assumed for live capture when computing band features.
+pub const NOMINAL_SAMPLE_RATE: u32 = 44100;
+
+/// Parameters of one analysis step.
+#[derive(Debug, Clone, Copy)]
+pub struct AnalysisSettings {
+    /// Spectrum smoothing factor, clamped to 0.01–0.3.
+    pub smoothing: f32,
+    /// Input gain, clamped to 0.5–5.
+    pub gain: f32,
+    pub intensity: f32,
+    /// Bass energy ratio over the rolling mean that counts as a beat.
+    pub beat_threshold: f32,
+    /// Spectrum visualizations read the smoothed spectrum and take bass
+    /// energy from it; the others read the waveform and take it from the RMS.
+    pub spectrum: bool,
+    /// Sample rate of the input, for band energies and the spectral centroid.
+    pub sample_rate: u32,
+    /// Time of this frame in seconds, for the beat clock.
+    pub time: f32,
+}
+
+impl Default for AnalysisSettings {
+    fn default() -> Self {
+        Self {
+            smoothing: 0.1,
+            gain: 1.5,
+            intensity: DEFAULT_INTENSITY,
+            beat_threshold: BEAT_THRESHOLD_MED,
+            spectrum: true,
+            sample_rate: NOMINAL_SAMPLE_RATE,
+            time: 0.0,
+        }
+    }
+}
+
+/// Result of one analysis step.
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisFrame {
+    /// What a visualization reads from its data buffer: the smoothed
+    /// spectrum or the gain-scaled waveform.
+    pub data: Vec<f32>,
+    pub bass_energy: f32,
+    /// Peaks on a beat and decays each frame.
+    pub beat_intensity: f32,
+    /// A beat was detected this frame.
+    pub beat: bool,
+    /// The waveform history advanced this frame.
+    pub history_updated: bool,
+    /// RMS of the input samples, before gain.
+    pub rms: f32,
+    /// Amplitude in each of `BANDS` (square root of the summed bin power).
+    pub bands: [f32; BANDS.len()],
+    /// Magnitude-weighted mean frequency in Hz; 0 for silence.
+    pub centroid: f32,
+    /// Largest absolute input sample, before gain.
+    pub peak: f32,
+    /// Geometric over arithmetic mean of the bin power: near 1 for noise,
+    /// near 0 for a pure tone; 0 for silence.
+    pub flatness: f32,
+    /// Fraction of the beat period since the last beat, 0–1; 0 while the
+    /// tempo is unknown.
+    pub beat_phase: f32,
+    /// Estimated tempo in beats per minute; 0 while unknown.
+    pub tempo: f32,
+}
+
+/// Analysis state carried from frame to frame, saved at the start of a
+/// session recording so replay starts from the same point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisSnapshot {
+    smoothed_fft: Vec<f32>,
+    bass_energy: f32,
+    waveform_history: Vec<f32>,
+    history_frame_counter: u32,
+    energy_history: Vec<f32>,
+    beat_intensity: f32,
+    last_beat: Option<f32>,
+    beat_period: f32,
+}
+
+/// Turns frames of samples into `AnalysisFrame`s.
+pub struct Analyzer {
+    smoothed_fft: Vec<f32>,
+    bass_energy: f32,
+    waveform_history: Vec<f32>,
+    history_frame_counter: u32,
+    /// Rolling energy history for beat detection (newest at index 0).
+    energy_history: Vec<f32>,
+    beat_intensity: f32,
+    /// Time of the last beat the beat clock counted.
+    last_beat: Option<f32>,
+    /// Smoothed seconds between beats; 0 while unknown.
+    beat_period: f32,
+    /// Reused every FFT call to avoid per-frame heap allocation.
+    fft_complex_buf: Vec<Complex<f32>>,
+    /// Plans are memoized internally, so reusing the planner avoids
+    /// redundant algorithm selection on every call.
+    fft_planner: rustfft::FftPlanner<f32>,
+}
+
+impl Analyzer {
+    pub fn new() -> Self {
+        Self {
+            smoothed_fft: vec![0.0; SAMPLE_SIZE / 2],
+            bass_energy: 0.0,
+            waveform_history: vec![0.0; WAVEFORM_HISTORY_SIZE * SAMPLE_SIZE],
+            history_frame_counter: 0,
+            energy_history: vec![0.0; BEAT_HISTORY_SIZE],
+            beat_intensity: 0.0,
+            last_beat: None,
+            beat_period: 0.0,
+            fft_complex_buf: vec![Complex::new(0.0, 0.0); SAMPLE_SIZE],
+            fft_planner: rustfft::FftPlanner::new(),
+        }
+    }
+
+    /// Analyse one frame of mono `samples` (normally `SAMPLE_SIZE` long).
+    pub fn process(&mut self, samples: &[f32], settings: &AnalysisSettings) -> AnalysisFrame {
+        let smoothing = settings.smoothing.clamp(0.01, 0.3);
+        let gain = settings.gain.clamp(0.5, 5.0);
+
+        // Amplitude per bin: a full-scale sine reads 1.0 in its bin.
+        let mut magnitudes = self.compute_fft(samples);
+        let len = magnitudes.len() as f32;
+        for m in &mut magnitudes {
+            *m /= len;
+        }
+
+        let data = if settings.spectrum {
+            for (s, &m) in self.smoothed_fft.iter_mut().zip(magnitudes.iter()) {
+                let m = (m * 50.0 * settings.intensity * gain).min(1.0);
+                *s = *s * (1.0 - smoothing) + m * smoothing;
+            }
+            let bass_bins = BASS_BIN_COUNT.min(self.smoothed_fft.len());
+            let raw_bass = self.smoothed_fft[..bass_bins].iter().sum::<f32>() / bass_bins as f32;
+            self.bass_energy = (raw_bass * 10.0).min(1.0);
+            self.smoothed_fft.clone()
+        } else {
+            let waveform: Vec<f32> = samples.iter().map(|s| s * gain).collect();
+            self.bass_energy = (rms(&waveform) * 5.0).min(1.0);
+            waveform
+        };
+
+        // Beat detection: bass energy against its rolling mean.
+        self.energy_history.copy_within(0..BEAT_HISTORY_SIZE - 1, 1);
+        self.energy_history[0] = self.bass_energy;
+        let avg_energy = self.energy_history.iter().sum::<f32>() / BEAT_HISTORY_SIZE as f32;
+        let beat = avg_energy > 0.001 && self.bass_energy > settings.beat_threshold * avg_energy;
+        if beat {
+            // Peak intensity proportional to the excess energy.
+            self.beat_intensity = (self.bass_energy / (avg_energy * settings.beat_threshold)).min(1.0);
+        } else {
+            // Decay toward zero so the pulse fades over several frames.
+            self.beat_intensity *= BEAT_DECAY;
+            if self.beat_intensity < 0.01 {
+                self.beat_intensity = 0.0;
+            }
+        }
+        let (beat_phase, tempo) = self.beat_clock(beat, settings.time);
+
+        // Waveform history, always from the raw signal: slot 0 is the newest.
+        self.history_frame_counter += 1;
+        let history_updated = self.history_frame_counter >= HISTORY_UPDATE_INTERVAL;
+        if history_updated {
+            self.history_frame_counter = 0;
+            let ss = SAMPLE_SIZE;
+            self.waveform_history.copy_within(0..(WAVEFORM_HISTORY_SIZE - 1) * ss, ss);
+            let len = ss.min(samples.len());
+            for (dst, &src) in self.waveform_history[..len].iter_mut().zip(samples.iter()) {
+                *dst = src * gain;
+            }
+            self.waveform_history[len..ss].fill(0.0);
+        }
+
+        let bin_hz = settings.sample_rate as f32 / samples.len().max(1) as f32;
+        AnalysisFrame {
+            data,
+            bass_energy: self.bass_energy,
+            beat_intensity: self.beat_intensity,
+            beat,
+            history_updated,
+            rms: rms(samples),
+            bands: band_energies(&magnitudes, bin_hz),
+            centroid: spectral_centroid(&magnitudes, bin_hz),
+            peak: samples.iter().fold(0.0, |peak: f32, s| peak.max(s.abs())),
+            flatness: spectral_flatness(&magnitudes),
+            beat_phase,
+            tempo,
+        }
+    }
+
+    /// Follow the tempo from the beats and return the beat phase and tempo
+    /// at `time`.
+    fn beat_clock(&mut self, beat: bool, time: f32) -> (f32, f32) {
+        let min_period = 60.0 / TEMPO_MAX_BPM;
+        let max_period = 60.0 / TEMPO_MIN_BPM;
+        let since = self.last_beat.map(|last| time - last);
+        if since.is_some_and(|since| since < 0.0) {
+            // The clock went back, e.g. a replay restarted.
+            self.last_beat = None;
+            self.beat_period = 0.0;
+        }
+        match since {
+            Some(since) if beat && since >= min_period => {
+                if since <= max_period {
+                    self.beat_period = if self.beat_period > 0.0 {
+                        self.beat_period + (since - self.beat_period) * TEMPO_SMOOTHING
+                    } else {
+                        since
+                    };
+                }
+                self.last_beat = Some(time);
+            }
+            None if beat => self.last_beat = Some(time),
+            _ => {}
+        }
+
+        let Some(last) = self.last_beat else { return (0.0, 0.0) };
+        let since = time - last;
+        if self.beat_period <= 0.0 {
+            return (0.0, 0.0);
+        }
+        if since > self.beat_period * TEMPO_TIMEOUT_BEATS {
+            self.beat_period = 0.0;
+            return (0.0, 0.0);
+        }
+        ((since / self.beat_period).fract(), 60.0 / self.beat_period)
+    }
+
+    pub fn bass_energy(&self) -> f32 {
+        self.bass_energy
+    }
+
+    pub fn beat_intensity(&self) -> f32 {
+        self.beat_intensity
+    }
+
+    /// Smoothed spectrum; only advances for spectrum visualizations.
+    pub fn spectrum(&self) -> &[f32] {
+        &self.smoothed_fft
+    }
+
+    /// `WAVEFORM_HISTORY_SIZE` gain-scaled waveforms, newest first.
+    pub fn waveform_history(&self) -> &[f32] {
+        &self.waveform_history
+    }
+
+    pub fn snapshot(&self) -> AnalysisSnapshot {
+        AnalysisSnapshot {
+            smoothed_fft: self.smoothed_fft.clone(),
+            bass_energy: self.bass_energy,
+            waveform_history: self.waveform_history.clone(),
+            history_frame_counter: self.history_frame_counter,
+            energy_history: self.energy_history.clone(),
+            beat_intensity: self.beat_intensity,
+            last_beat: self.last_beat,
+            beat_period: self.beat_period,
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &AnalysisSnapshot) {
+        self.smoothed_fft.clone_from(&snapshot.smoothed_fft);
+        self.bass_energy = snapshot.bass_energy;
+        self.waveform_history.clone_from(&snapshot.waveform_history);
+        self.history_frame_counter = snapshot.history_frame_counter;
+        self.energy_history.clone_from(&snapshot.energy_history);
+        self.beat_intensity = snapshot.beat_intensity;
+        self.last_beat = snapshot.last_beat;
+        self.beat_period = snapshot.beat_period;
+    }
+
+    /// Magnitude spectrum of `samples` (first half of the bins).
+    fn compute_fft(&mut self, samples: &[f32]) -> Vec<f32> {
+        let n = samples.len();
+        // Resize the reusable buffer only when the input length changes (rare).
+        if self.fft_complex_buf.len() != n {
+            self.fft_complex_buf.resize(n, Complex::new(0.0, 0.0));
+        }
+        for (dst, &src) in self.fft_complex_buf.iter_mut().zip(samples.iter()) {
+            *dst = Complex::new(src, 0.0);
+        }
+        let fft = self.fft_planner.plan_fft_forward(n);
+        fft.process(&mut self.fft_complex_buf);
+        self.fft_complex_buf[0..n / 2].iter().map(|c| c.norm()).collect()
+    }
+}
+
+impl Default for Analyzer {
+    fn default() -> Self { Self::new() }
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|x| x * x).sum::<f32>() / samples.len().max(1) as f32).sqrt()
+}
+
+fn band_energies(magnitudes: &[f32], bin_hz: f32) -> [f32; BANDS.len()] {
+    let mut bands = [0.0; BANDS.len()];
+    for (k, &m) in magnitudes.iter().enumerate() {
+        let freq = k as f32 * bin_hz;
+        if let Some(i) = BANDS.iter().position(|&(_, low, high)| freq >= low && freq < high) {
+            bands[i] += m * m;
+        }
+    }
+    bands.map(f32::sqrt)
+}
+
+fn spectral_centroid(magnitudes: &[f32], bin_hz: f32) -> f32 {
+    // Skip the DC bin: an offset is not a frequency.
+    let total: f32 = magnitudes.iter().skip(1).sum();
+    if total <= f32::EPSILON {
+        return 0.0;
+    }
+    let weighted: f32 = magnitudes.iter().enumerate().skip(1).map(|(k, &m)| k as f32 * bin_hz * m).sum();
+    weighted / total
+}
+
+fn spectral_flatness(magnitudes: &[f32]) -> f32 {
+    let power: Vec<f32> = magnitudes.iter().skip(1).map(|m| m * m).collect();
+    let mean = power.iter().sum::<f32>() / power.len().max(1) as f32;
+    if mean <= f32::EPSILON * f32::EPSILON {
+        return 0.0;
+    }
+    // Floor each bin so one silent bin does not zero the geometric mean.
+    let log_mean = power.iter().map(|p| (p + mean * 1e-6).ln()).sum::<f32>() / power.len() as f32;
+    (log_mean.exp() / mean).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bin 12 of a `SAMPLE_SIZE` FFT at the nominal rate, about 1034 Hz.
+    const SINE_BIN: usize = 12;
+
+    fn sine(bin: usize, amplitude: f32) -> Vec<f32> {
+        (0..SAMPLE_SIZE)
+            .map(|i| amplitude * (std::f32::consts::TAU * bin as f32 * i as f32 / SAMPLE_SIZE as f32).sin())
+            .collect()
+    }
+
+    fn bin_hz() -> f32 {
+        NOMINAL_SAMPLE_RATE as f32 / SAMPLE_SIZE as f32
+    }
+
+    #[test]
+    fn silence_has_no_features() {
+        let mut analyzer = Analyzer::new();
+        let frame = analyzer.process(&[0.0; SAMPLE_SIZE], &AnalysisSettings::default());
+        assert_eq!(frame.rms, 0.0);
+        assert_eq!(frame.centroid, 0.0);
+        assert_eq!(frame.bass_energy, 0.0);
+        assert!(!frame.beat);
+        assert!(frame.bands.iter().all(|&b| b == 0.0));
+        assert_eq!(frame.data.len(), SAMPLE_SIZE / 2);
+    }
+
+    #[test]
+    fn sine_lands_in_its_band() {
+        let mut analyzer = Analyzer::new();
+        let frame = analyzer.process(&sine(SINE_BIN, 0.5), &AnalysisSettings::default());
+        let freq = SINE_BIN as f32 * bin_hz();
+        assert!((frame.rms - 0.5 / 2f32.sqrt()).abs() < 1e-3, "rms {}", frame.rms);
+        assert!((frame.centroid - freq).abs() < 1.0, "centroid {} vs {}", frame.centroid, freq);
+
+        let mid = BANDS.iter().position(|&(name, _, _)| name == "mid").unwrap();
+        assert!((frame.bands[mid] - 0.5).abs() < 1e-3, "mid band {}", frame.bands[mid]);
+        for (i, &band) in frame.bands.iter().enumerate().filter(|&(i, _)| i != mid) {
+            assert!(band < 1e-3, "band {} is {}", BANDS[i].0, band);
+        }
+    }
+
+    #[test]
+    fn spectrum_is_smoothed_towards_the_input() {
+        let settings = AnalysisSettings { smoothing: 0.2, gain: 1.0, ..AnalysisSettings::default() };
+        let mut analyzer = Analyzer::new();
+        let samples = sine(SINE_BIN, 0.01);
+        // 0.01 amplitude × 50 × intensity 1 × gain 1 = 0.5 in the sine's bin.
+        let first = analyzer.process(&samples, &settings).data[SINE_BIN];
+        assert!((first - 0.1).abs() < 1e-3, "first frame {}", first);
+        for _ in 0..100 {
+            analyzer.process(&samples, &settings);
+        }
+        assert!((analyzer.spectrum()[SINE_BIN] - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn burst_after_silence_is_a_beat_that_decays() {
+        // Waveform mode: bass energy follows the RMS without smoothing.
+        let settings = AnalysisSettings { spectrum: false, ..AnalysisSettings::default() };
+        let mut analyzer = Analyzer::new();
+        for _ in 0..BEAT_HISTORY_SIZE {
+            assert!(!analyzer.process(&[0.0; SAMPLE_SIZE], &settings).beat);
+        }
+
+        let burst = analyzer.process(&sine(1, 0.5), &settings);
+        assert!(burst.beat);
+        assert_eq!(burst.beat_intensity, 1.0);
+
+        let after = analyzer.process(&[0.0; SAMPLE_SIZE], &settings);
+        assert!(!after.beat);
+        assert!((after.beat_intensity - BEAT_DECAY).abs() < 1e-6);
+    }
+
+    #[test]
+    fn steady_signal_is_not_a_beat() {
+        let settings = AnalysisSettings::default();
+        let mut analyzer = Analyzer::new();
+        let samples = sine(1, 0.05);
+        let beats = (0..BEAT_HISTORY_SIZE * 3)
+            .map(|_| analyzer.process(&samples, &settings))
+            .skip(BEAT_HISTORY_SIZE)
+            .filter(|f| f.beat)
+            .count();
+        assert_eq!(beats, 0);
+    }
+
+    #[test]
+    fn waveform_mode_passes_the_scaled_signal() {
+        let settings = AnalysisSettings { spectrum: false, gain: 2.0, ..AnalysisSettings::default() };
+        let mut analyzer = Analyzer::new();
+        let samples = sine(SINE_BIN, 0.25);
+        let frame = analyzer.process(&samples, &settings);
+        assert_eq!(frame.data, samples.iter().map(|s| s * 2.0).collect::<Vec<_>>());
+        assert!((frame.bass_energy - (0.5 / 2f32.sqrt() * 5.0).min(1.0)).abs() < 1e-3);
+        assert!(analyzer.spectrum().iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn history_advances_every_interval() {
+        let settings = AnalysisSettings { gain: 1.0, ..AnalysisSettings::default() };
+        let mut analyzer = Analyzer::new();
+        for frame in 1..=HISTORY_UPDATE_INTERVAL * 2 {
+            let samples = vec![frame as f32 * 0.01; SAMPLE_SIZE];
+            let updated = analyzer.process(&samples, &settings).history_updated;
+            assert_eq!(updated, frame % HISTORY_UPDATE_INTERVAL == 0, "frame {}", frame);
+        }
+        let history = analyzer.waveform_history();
+        let newest = HISTORY_UPDATE_INTERVAL as f32 * 2.0 * 0.01;
+        let previous = HISTORY_UPDATE_INTERVAL as f32 * 0.01;
+        assert_eq!(history[0], newest);
+        assert_eq!(history[SAMPLE_SIZE], previous);
+        assert_eq!(history[2 * SAMPLE_SIZE], 0.0);
+    }
+
+    #[test]
+    fn sine_is_tonal_and_noise_is_flat() {
+        let mut analyzer = Analyzer::new();
+        let tone = analyzer.process(&sine(SINE_BIN, 0.5), &AnalysisSettings::default());
+        assert!(tone.flatness < 0.01, "sine flatness {}", tone.flatness);
+        assert!((tone.peak - 0.5).abs() < 1e-3, "peak {}", tone.peak);
+
+        // White-ish noise from a linear congruential generator.
+        let mut seed = 1u32;
+        let noise: Vec<f32> = (0..SAMPLE_SIZE).map(|_| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f32 / (1 << 24) as f32 - 0.5
+        }).collect();
+        let frame = analyzer.process(&noise, &AnalysisSettings::default());
+        assert!(frame.flatness > 0.4, "noise flatness {}", frame.flatness);
+    }
+
+    #[test]
+    fn beat_clock_follows_regular_beats() {
+        // A burst every 30 frames at 60 fps: 120 BPM.
+        let mut settings = AnalysisSettings { spectrum: false, ..AnalysisSettings::default() };
+        let mut analyzer = Analyzer::new();
+        let mut last = AnalysisFrame::default();
+        for frame in 0..300 {
+            settings.time = frame as f32 / 60.0;
+            let samples = if frame % 30 == 0 { sine(1, 0.5) } else { vec![0.0; SAMPLE_SIZE] };
+            last = analyzer.process(&samples, &settings);
+            if frame == 285 {
+                assert!((last.tempo - 120.0).abs() < 1.0, "tempo {}", last.tempo);
+                assert!((last.beat_phase - 0.5).abs() < 0.02, "phase {}", last.beat_phase);
+            }
+        }
+        assert!(last.tempo > 0.0);
+
+        // Without beats the tempo is forgotten after a few periods.
+        for frame in 300..300 + 30 * 5 {
+            settings.time = frame as f32 / 60.0;
+            last = analyzer.process(&[0.0; SAMPLE_SIZE], &settings);
+        }
+        assert_eq!((last.tempo, last.beat_phase), (0.0, 0.0));
+    }
+
+    #[test]
+    fn restored_snapshot_continues_identically() {
+        let settings = AnalysisSettings::default();
+        let mut analyzer = Analyzer::new();
+        for bin in 1..20 {
+            analyzer.process(&sine(bin, 0.1), &settings);
+        }
+        let snapshot = analyzer.snapshot();
+        let expected = analyzer.process(&sine(3, 0.4), &settings);
+
+        let mut restored = Analyzer::new();
+        restored.restore(&snapshot);
+        let frame = restored.process(&sine(3, 0.4), &settings);
+        assert_eq!(frame.data, expected.data);
+        assert_eq!(frame.beat_intensity, expected.beat_intensity);
+        assert_eq!(restored.waveform_history(), analyzer.waveform_history());
+    }
+}
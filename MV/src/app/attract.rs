@@ -0,0 +1,108 @@
+//! Attract mode: visualizations driven by synthetic audio while the input
+//! stays silent and nobody uses MV, handing back as soon as either changes
+
+use super::App;
+use crate::config::constants::{ATTRACT_FADE_SECONDS, SAMPLE_SIZE};
+use crate::config::settings::SyntheticSignal;
+use crate::input::synthetic::synthesize;
+
+pub struct AttractState {
+    pub active: bool,
+    /// Share of synthetic audio in the analysed signal, 0–1; fades out
+    /// after attract mode ends.
+    level: f32,
+    /// Seconds since attract mode started; the synthetic signal's clock.
+    clock: f64,
+    /// Clock time of the next visualization change.
+    next_switch: f64,
+    /// Visualization shown before attract mode, restored after it.
+    previous_plugin: Option<usize>,
+    /// Reused for the synthetic samples of each frame.
+    buffer: Vec<f32>,
+}
+
+impl AttractState {
+    pub fn new() -> Self {
+        Self { active: false, level: 0.0, clock: 0.0, next_switch: 0.0, previous_plugin: None, buffer: vec![0.0; SAMPLE_SIZE] }
+    }
+
+    /// Attract mode is on or its audio is still fading out.
+    pub fn running(&self) -> bool {
+        self.active || self.level > 0.0
+    }
+
+    /// Add this frame's synthetic `signal` to `samples` at the current level.
+    pub fn mix(&mut self, signal: SyntheticSignal, samples: &mut [f32]) {
+        if self.level <= 0.0 {
+            return;
+        }
+        self.buffer.resize(samples.len(), 0.0);
+        synthesize(signal, self.clock, &mut self.buffer);
+        for (sample, synthetic) in samples.iter_mut().zip(&self.buffer) {
+            *sample += synthetic * self.level;
+        }
+    }
+}
+
+impl App {
+    /// Start attract mode after the configured time without sound or user
+    /// input, end it as soon as either returns or a replay starts, and
+    /// change its visualization on schedule.
+    pub(crate) fn update_attract(&mut self, dt: f32) {
+        let settings = &self.state.settings.attract;
+        let wanted = settings.enabled
+            && self.state.replay.is_none()
+            && self.state.power.quiet_seconds() >= settings.after_seconds;
+        let attract = &mut self.state.attract;
+        if !attract.active && wanted {
+            attract.active = true;
+            attract.previous_plugin = Some(self.state.current_plugin_index);
+            if attract.level == 0.0 {
+                attract.clock = 0.0;
+            }
+            attract.next_switch = attract.clock;
+        } else if attract.active && !wanted {
+            attract.active = false;
+            if let Some(index) = attract.previous_plugin.take() {
+                self.select_visualization(index);
+            }
+        }
+
+        let attract = &mut self.state.attract;
+        let step = dt / ATTRACT_FADE_SECONDS;
+        attract.level = if attract.active { (attract.level + step).min(1.0) } else { (attract.level - step).max(0.0) };
+        if !attract.running() {
+            return;
+        }
+        attract.clock += dt as f64;
+        if attract.active && attract.clock >= attract.next_switch {
+            attract.next_switch = attract.clock + self.state.settings.attract.switch_seconds as f64;
+            if let Some(index) = self.attract_pick() {
+                self.select_visualization(index);
+            }
+        }
+    }
+
+    /// A random visualization from the attract set other than the current
+    /// one; the set falls back to every enabled visualization when none of
+    /// its entries are available.
+    fn attract_pick(&mut self) -> Option<usize> {
+        if self.state.enabled_plugin_cache.is_empty() {
+            self.rebuild_plugin_cache();
+        }
+        let plugins = &self.state.gpu.as_ref()?.plugins;
+        let chosen = &self.state.settings.attract.shaders;
+        let enabled = &self.state.enabled_plugin_cache;
+        let mut candidates: Vec<usize> = enabled.iter().copied()
+            .filter(|&i| plugins.get(i).is_some_and(|p| chosen.contains(&p.name)))
+            .collect();
+        if candidates.is_empty() {
+            candidates = enabled.clone();
+        }
+        if candidates.len() > 1 {
+            candidates.retain(|&i| i != self.state.current_plugin_index);
+        }
+        let roll = self.state.shuffle_rng.next_f32();
+        candidates.get((roll * candidates.len() as f32) as usize).copied()
+    }
+}
@@ -0,0 +1,160 @@
+//! Audio device monitoring: failure detection, fallback and reconnection
+
+use super::*;
+use crate::input::network::NetworkAddr;
+use crate::input::sources::{AudioHost, AudioSource};
+use std::sync::mpsc::{self, TryRecvError};
+
+impl App {
+    /// Re-enumerate the sources of the current host.
+    pub(crate) fn refresh_devices(&mut self) {
+        let sources = self.state.host.sources();
+        self.set_devices(sources);
+    }
+
+    /// Take the result of a source scan, adding the network input if set.
+    fn set_devices(&mut self, sources: AppResult<Vec<AudioSource>>) {
+        match sources {
+            Ok(devices) => {
+                self.state.devices = devices;
+                self.state.host_error = None;
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                self.state.devices.clear();
+                self.state.host_error = Some(e.to_string());
+            }
+        }
+        if let Some(url) = &self.state.settings.network_input {
+            match NetworkAddr::parse(url) {
+                Ok(addr) => self.state.devices.push(AudioSource::network(addr)),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        self.state.last_device_scan = Instant::now();
+    }
+
+    /// Capture from the host called `name`, keeping the current one if it
+    /// is unavailable.
+    pub(crate) fn switch_host(&mut self, name: &str) {
+        let host = match AudioHost::open(Some(name)) {
+            Ok(host) => host,
+            Err(e) => {
+                self.state.host_error = Some(e.to_string());
+                return;
+            }
+        };
+        self.state.audio = None;
+        self.state.audio_fallback = false;
+        self.state.device_scan = None;
+        self.state.host = host;
+        self.state.settings.audio_host = Some(self.state.host.name().to_string());
+        self.save_settings();
+        self.refresh_devices();
+        self.start_audio();
+    }
+
+    /// Open the device saved in settings, falling back if it is unavailable.
+    pub(crate) fn start_audio(&mut self) {
+        if self.state.settings.selected_device.is_none() {
+            self.state.show_device_selection = true;
+            return;
+        }
+        if !self.connect_preferred() {
+            eprintln!("Preferred audio device unavailable, falling back");
+            self.fall_back();
+        }
+    }
+
+    /// Check the stream for failures and, while falling back, periodically
+    /// rescan for the preferred device.
+    pub(crate) fn monitor_audio(&mut self) {
+        if let Some(name) = self.state.pending_host.take() {
+            self.switch_host(&name);
+        }
+        if std::mem::take(&mut self.state.refresh_devices_requested) {
+            self.refresh_devices();
+        }
+
+        if let Some(reason) = self.state.audio.as_mut().and_then(|a| a.failure()) {
+            eprintln!("Audio input lost ({}), falling back", reason);
+            // Drop the dead stream before opening another one.
+            self.state.audio = None;
+            self.refresh_devices();
+            self.fall_back();
+            return;
+        }
+
+        if self.state.audio_fallback {
+            self.rescan_in_background();
+        } else {
+            self.state.device_scan = None;
+        }
+    }
+
+    /// Rescan every `AUDIO_RESCAN_SECONDS` and reconnect once the preferred
+    /// device is back. Enumerating and probing devices can take hundreds of
+    /// milliseconds, so the scan runs on a worker thread with its own host.
+    fn rescan_in_background(&mut self) {
+        if let Some(scan) = &self.state.device_scan {
+            match scan.try_recv() {
+                Ok(sources) => {
+                    self.state.device_scan = None;
+                    self.set_devices(sources);
+                    if self.connect_preferred() {
+                        eprintln!("Preferred audio device is back");
+                    }
+                }
+                Err(TryRecvError::Empty)        => {}
+                Err(TryRecvError::Disconnected) => self.state.device_scan = None,
+            }
+            return;
+        }
+        if self.state.last_device_scan.elapsed().as_secs_f32() < AUDIO_RESCAN_SECONDS {
+            return;
+        }
+        let host = self.state.host.name();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(AudioHost::open(Some(host)).and_then(|host| host.sources()));
+        });
+        self.state.device_scan = Some(rx);
+        self.state.last_device_scan = Instant::now();
+    }
+
+    /// Open the device named in settings; `false` if it is missing or fails.
+    fn connect_preferred(&mut self) -> bool {
+        let Some(name) = self.state.settings.selected_device.as_ref() else { return false };
+        let Some(source) = self.state.devices.iter().find(|d| &d.name == name) else {
+            return false;
+        };
+        match AudioHandler::new(source) {
+            Ok(handler) => {
+                self.state.audio = Some(handler);
+                self.state.audio_fallback = false;
+                true
+            }
+            Err(e) => {
+                eprintln!("Failed to open {}: {:?}", name, e);
+                false
+            }
+        }
+    }
+
+    /// Capture from the host's default input, or silence if there is none,
+    /// until the preferred device returns.
+    fn fall_back(&mut self) {
+        self.state.audio = None;
+        self.state.audio_fallback = true;
+
+        let preferred = self.state.settings.selected_device.as_deref();
+        let Some(source) = self.state.host.default_source() else { return };
+        if Some(source.name.as_str()) == preferred {
+            return;
+        }
+        match AudioHandler::new(&source) {
+            Ok(handler) => self.state.audio = Some(handler),
+            Err(e) => eprintln!("Failed to open default input, using silence: {:?}", e),
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use super::*;
+use winit::keyboard::{Key, NamedKey};
 
 impl App {
     pub fn forward_to_egui(&mut self, event: &WindowEvent) {
         match event {
             WindowEvent::CursorMoved { position, .. } => {
                 let pos = egui::pos2(position.x as f32, position.y as f32);
-                self.state.egui_pointer_pos = pos;
                 self.state.egui_raw_input.events.push(egui::Event::PointerMoved(pos));
             }
             WindowEvent::MouseInput { state, button, .. } => {
@@ -15,8 +15,9 @@ impl App {
                     winit::event::MouseButton::Middle => egui::PointerButton::Middle,
                     _ => return,
                 };
+                let [x, y] = self.state.pointer.cursor();
                 self.state.egui_raw_input.events.push(egui::Event::PointerButton {
-                    pos: self.state.egui_pointer_pos,
+                    pos: egui::pos2(x, y),
                     button: egui_button,
                     pressed: matches!(state, ElementState::Pressed),
                     modifiers: egui::Modifiers::default(),
@@ -29,6 +30,32 @@ impl App {
                 };
                 self.state.egui_raw_input.events.push(egui::Event::Scroll(scroll));
             }
+            WindowEvent::KeyboardInput { event, .. } => {
+                let modifiers = egui_modifiers(self.state.current_modifiers);
+                let pressed = event.state == ElementState::Pressed;
+                let events = &mut self.state.egui_raw_input.events;
+                self.state.egui_raw_input.modifiers = modifiers;
+                // Copy, cut and paste go through MV's own clipboard.
+                let clipboard = match &event.logical_key {
+                    Key::Character(c) if pressed && modifiers.command => match c.to_lowercase().as_str() {
+                        "c" => Some(egui::Event::Copy),
+                        "x" => Some(egui::Event::Cut),
+                        "v" => Some(egui::Event::Paste(self.state.clipboard.clone())),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if let Some(clipboard) = clipboard {
+                    events.push(clipboard);
+                    return;
+                }
+                if let Some(key) = egui_key(&event.logical_key) {
+                    events.push(egui::Event::Key { key, physical_key: None, pressed, repeat: event.repeat, modifiers });
+                }
+                if let Some(text) = event.text.as_ref().filter(|t| pressed && !modifiers.command && !t.chars().any(char::is_control)) {
+                    events.push(egui::Event::Text(text.to_string()));
+                }
+            }
             WindowEvent::Resized(size) => {
                 self.state.egui_raw_input.screen_rect = Some(egui::Rect::from_min_size(
                     egui::Pos2::ZERO,
@@ -39,6 +66,13 @@ impl App {
         }
     }
 
+    /// Follow the pointer for the shaders, particles and camera; input over
+    /// egui panels is left to them.
+    pub fn track_pointer(&mut self, event: &WindowEvent) {
+        let egui_has_pointer = self.state.egui_ctx.is_pointer_over_area() || self.state.egui_ctx.wants_pointer_input();
+        self.state.pointer.handle(event, egui_has_pointer);
+    }
+
     pub fn handle_key_press(&mut self, physical_key: PhysicalKey) {
         let key = match physical_key {
             PhysicalKey::Code(k) => k,
@@ -66,31 +100,15 @@ impl App {
                 self.state.window_mode = self.state.window_mode.next();
                 #[cfg(debug_assertions)]
                 eprintln!("Window mode: {}", self.state.window_mode.label());
-                if let Some(window) = self.state.window.as_ref().map(Arc::clone) {
-                    #[cfg(target_os = "windows")]
-                    {
-                        self.apply_transparency(&window);
-                        self.set_topmost(&window, self.state.window_mode.needs_topmost());
-                    }
-                    #[cfg(not(target_os = "windows"))]
-                    {
-                        let _ = window.set_transparent(self.state.window_mode.needs_layered());
-                    }
-                }
+                self.apply_window_mode();
             }
             ShortcutAction::CycleBeatSensitivity => {
                 self.state.settings.beat_sensitivity = self.state.settings.beat_sensitivity.next();
                 #[cfg(debug_assertions)]
                 eprintln!("Beat sensitivity: {}", self.state.settings.beat_sensitivity.label());
             }
-            ShortcutAction::DecreaseOpacity => {
-                #[cfg(target_os = "windows")]
-                self.adjust_transparency_level(false);
-            }
-            ShortcutAction::IncreaseOpacity => {
-                #[cfg(target_os = "windows")]
-                self.adjust_transparency_level(true);
-            }
+            ShortcutAction::DecreaseOpacity => self.adjust_transparency_level(false),
+            ShortcutAction::IncreaseOpacity => self.adjust_transparency_level(true),
             ShortcutAction::IncreaseIntensity => {
                 self.state.uniforms.intensity = (self.state.uniforms.intensity + INTENSITY_STEP).min(10.0);
             }
@@ -114,6 +132,17 @@ impl App {
             ShortcutAction::ToggleShaderBrowser => {
                 self.state.show_shader_browser = !self.state.show_shader_browser;
             }
+            ShortcutAction::TogglePerfHud => {
+                self.state.settings.performance.show_hud = !self.state.settings.performance.show_hud;
+            }
+            ShortcutAction::ToggleRecording => self.toggle_recording(),
+            ShortcutAction::ToggleShaderEditor => {
+                if self.state.show_shader_editor {
+                    self.state.show_shader_editor = false;
+                } else {
+                    self.open_shader_editor();
+                }
+            }
             // These are handled directly in window_event with access to event_loop
             ShortcutAction::ToggleInfo | ShortcutAction::ToggleSettings | ShortcutAction::Exit => {}
         }
@@ -121,3 +150,39 @@ impl App {
 }
 
 
+
+fn egui_modifiers(state: winit::keyboard::ModifiersState) -> egui::Modifiers {
+    egui::Modifiers {
+        alt: state.alt_key(),
+        ctrl: state.control_key(),
+        shift: state.shift_key(),
+        mac_cmd: cfg!(target_os = "macos") && state.super_key(),
+        command: if cfg!(target_os = "macos") { state.super_key() } else { state.control_key() },
+    }
+}
+
+/// The egui key for `key`, for the keys text editing and egui shortcuts use.
+fn egui_key(key: &Key) -> Option<egui::Key> {
+    match key {
+        Key::Named(named) => Some(match named {
+            NamedKey::ArrowDown  => egui::Key::ArrowDown,
+            NamedKey::ArrowLeft  => egui::Key::ArrowLeft,
+            NamedKey::ArrowRight => egui::Key::ArrowRight,
+            NamedKey::ArrowUp    => egui::Key::ArrowUp,
+            NamedKey::Escape     => egui::Key::Escape,
+            NamedKey::Tab        => egui::Key::Tab,
+            NamedKey::Backspace  => egui::Key::Backspace,
+            NamedKey::Enter      => egui::Key::Enter,
+            NamedKey::Space      => egui::Key::Space,
+            NamedKey::Insert     => egui::Key::Insert,
+            NamedKey::Delete     => egui::Key::Delete,
+            NamedKey::Home       => egui::Key::Home,
+            NamedKey::End        => egui::Key::End,
+            NamedKey::PageUp     => egui::Key::PageUp,
+            NamedKey::PageDown   => egui::Key::PageDown,
+            _ => return None,
+        }),
+        Key::Character(c) => egui::Key::from_name(&c.to_uppercase()),
+        _ => None,
+    }
+}
@@ -1,15 +1,24 @@
 //! Main application logic and event handling
 
+pub mod attract;
+pub mod audio_monitor;
 pub mod lifecycle;
+pub mod now_playing;
 pub mod event_handler;
+pub mod perf;
+pub mod power;
+pub mod session;
+pub mod shader_editor;
+pub mod shuffle;
 pub mod state;
+pub mod text_overlay;
+pub mod window_mode;
 
 use crate::input::audio::AudioHandler;
 use crate::config::constants::*;
 use crate::common::error::AppResult;
 use crate::graphics::GpuResources;
-use crate::config::settings::{AppSettings, BeatSensitivity, ColorScheme};
-use cpal::traits::DeviceTrait;
+use crate::config::settings::AppSettings;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use winit::application::ApplicationHandler;
@@ -17,19 +26,6 @@ use winit::event::{ElementState, KeyEvent, MouseScrollDelta, WindowEvent};
 use winit::event_loop::ActiveEventLoop;
 use winit::keyboard::{KeyCode, PhysicalKey};
 use winit::window::{Fullscreen, Icon, Window};
-#[cfg(target_os = "windows")]
-use raw_window_handle::HasWindowHandle;
-#[cfg(target_os = "windows")]
-use windows::Win32::Foundation::{COLORREF, HWND};
-#[cfg(target_os = "windows")]
-use windows::Win32::UI::WindowsAndMessaging::{
-    SetLayeredWindowAttributes, SetWindowPos, LAYERED_WINDOW_ATTRIBUTES_FLAGS,
-    HWND_NOTOPMOST, HWND_TOPMOST, SWP_NOMOVE, SWP_NOSIZE,
-};
-#[cfg(target_os = "windows")]
-use windows::Win32::UI::WindowsAndMessaging::{
-    GetWindowLongPtrW, SetWindowLongPtrW, GWL_EXSTYLE, WS_EX_LAYERED, WS_EX_TRANSPARENT,
-};
 
 pub use state::{AppState, WindowMode};
 
@@ -38,10 +34,13 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(devices: Vec<cpal::Device>, settings: AppSettings) -> Self {
-        Self {
-            state: AppState::new(devices, settings),
-        }
+    pub fn new(host: crate::input::sources::AudioHost, settings: AppSettings) -> Self {
+        let mut app = Self {
+            state: AppState::new(host, settings),
+        };
+        app.refresh_devices();
+        app.start_audio();
+        app
     }
 }
 
@@ -49,7 +48,10 @@ impl ApplicationHandler for App {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         let window_attributes = Window::default_attributes()
             .with_title(WINDOW_TITLE)
-            .with_inner_size(winit::dpi::PhysicalSize::new(DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT));
+            .with_inner_size(winit::dpi::PhysicalSize::new(DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT))
+            // X11 picks an alpha-capable visual only at creation; Normal mode
+            // still presents opaquely.
+            .with_transparent(true);
         let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
 
         // Set the icon after window creation
@@ -112,12 +114,26 @@ impl ApplicationHandler for App {
             return;
         }
 
+        // Touch drives the same path as the mouse.
+        if let WindowEvent::Touch(touch) = &event {
+            for event in self.state.pointer.touch_as_mouse(touch) {
+                self.window_event(event_loop, window_id, event);
+            }
+            return;
+        }
+
+        // Keys typed into an egui text field are not shortcuts.
+        let typing = self.state.egui_ctx.wants_keyboard_input();
         self.forward_to_egui(&event);
+        self.track_pointer(&event);
+        self.state.power.handle(&event);
 
         match event {
+            WindowEvent::KeyboardInput { .. } if typing => {}
             WindowEvent::Resized(new_size) => self.resize(new_size),
             WindowEvent::ModifiersChanged(modifiers) => self.state.current_modifiers = modifiers.state(),
             WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::DroppedFile(path) => self.drop_image(path),
             WindowEvent::KeyboardInput {
                 event: KeyEvent { physical_key: PhysicalKey::Code(KeyCode::Escape), state: ElementState::Pressed, .. },
                 ..
@@ -165,8 +181,17 @@ impl ApplicationHandler for App {
                 self.handle_key_press(physical_key);
             }
             WindowEvent::RedrawRequested => {
+                let started = Instant::now();
+                self.state.frame_stats.begin_frame(started);
                 self.update();
-                if let Err(e) = self.render() {
+                let result = self.render();
+                let waited = self.state.gpu.as_ref().map(|g| g.surface_wait).unwrap_or_default();
+                let gpu_ms = self.state.gpu.as_ref()
+                    .and_then(|g| g.gpu_timer.as_ref())
+                    .and_then(|t| t.last)
+                    .map(|t| t.total_ms());
+                self.state.frame_stats.end_frame(started, waited, gpu_ms, self.state.settings.performance.frame_budget_ms);
+                if let Err(e) = result {
                     eprintln!("Render error: {:?}", e);
                     match e {
                         crate::common::error::AppError::Surface(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
@@ -181,9 +206,14 @@ impl ApplicationHandler for App {
         }
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        if let Some(window) = &self.state.window {
-            window.request_redraw();
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.schedule_frame(event_loop);
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        self.stop_recording();
+        if let Err(e) = self.state.play_history.save() {
+            eprintln!("Failed to save play statistics: {}", e);
         }
     }
 }
@@ -193,9 +223,7 @@ impl ApplicationHandler for App {
 // ──────────────────────────────────────────────────────────────────────────────
 
 fn plugin_group(name: &str) -> &'static str {
-    crate::visualization::shader_info(name)
-        .map(|info| info.category.label())
-        .unwrap_or("✨ Abstract")
+    crate::visualization::shader_category(name).label()
 }
 
 pub(crate) fn build_plugin_groups(names: &[String]) -> Vec<(String, Vec<String>)> {
@@ -0,0 +1,40 @@
+//! Now-playing metadata: track title for the overlay, track-change switches
+
+use super::App;
+use crate::input::now_playing::{NowPlayingEvent, NowPlayingWatcher, PlaybackState};
+
+impl App {
+    /// Start or stop the watcher with the setting and apply its events.
+    pub(crate) fn update_now_playing(&mut self) {
+        let enabled = self.state.settings.now_playing.enabled;
+        if enabled != self.state.now_playing_watcher.is_some() {
+            self.state.now_playing_watcher = enabled.then(NowPlayingWatcher::start);
+            self.state.now_playing = None;
+            self.state.now_playing_note = None;
+        }
+        let events = self.state.now_playing_watcher.as_ref().map(NowPlayingWatcher::poll).unwrap_or_default();
+
+        for event in events {
+            match event {
+                NowPlayingEvent::Updated(track) => self.state.now_playing = track,
+                NowPlayingEvent::TrackChanged(track) => {
+                    #[cfg(debug_assertions)]
+                    eprintln!("Track changed: {}", track.display_title());
+                    if self.state.settings.now_playing.switch_on_track_change && self.state.replay.is_none() {
+                        self.auto_switch();
+                    }
+                }
+                NowPlayingEvent::Unavailable(reason) => {
+                    self.state.now_playing = None;
+                    self.state.now_playing_note = Some(reason);
+                }
+            }
+        }
+
+        // A stopped player has no title worth showing.
+        self.state.track_title = self.state.now_playing.as_ref()
+            .filter(|track| track.state != PlaybackState::Stopped)
+            .map(|track| track.display_title())
+            .filter(|title| !title.is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+//! Frame-time statistics for the performance HUD and quality policy
+
+use crate::config::constants::{
+    FRAME_STATS_WINDOW, MIN_RENDER_SCALE, OVER_BUDGET_SECONDS, RENDER_SCALE_RECOVER_SECONDS, RENDER_SCALE_STEP,
+};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Rolling window of frame timings.
+pub struct FrameStats {
+    /// Wall time between consecutive frames in milliseconds, newest last.
+    intervals: VecDeque<f32>,
+    /// CPU plus GPU time of each frame in milliseconds, newest last. Unlike
+    /// the intervals this leaves out frame pacing, so the budget is judged on it.
+    work: VecDeque<f32>,
+    /// CPU time of the last frame (update, UI and command recording), excluding
+    /// the wait for a swapchain image.
+    pub cpu_ms: f32,
+    last_frame: Option<Instant>,
+    over_budget_since: Option<Instant>,
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        Self {
+            intervals: VecDeque::with_capacity(FRAME_STATS_WINDOW),
+            work: VecDeque::with_capacity(FRAME_STATS_WINDOW),
+            cpu_ms: 0.0,
+            last_frame: None,
+            over_budget_since: None,
+        }
+    }
+
+    /// Record the start of a frame at `now`.
+    pub fn begin_frame(&mut self, now: Instant) {
+        if let Some(last) = self.last_frame {
+            push_sample(&mut self.intervals, ms(now - last));
+        }
+        self.last_frame = Some(now);
+    }
+
+    /// Record the CPU time of the frame begun at `started`, minus `waited`
+    /// blocking on the surface, and its work time with the latest `gpu_ms`
+    /// where the GPU timer has one.
+    pub fn end_frame(&mut self, started: Instant, waited: Duration, gpu_ms: Option<f32>, budget_ms: f32) {
+        self.cpu_ms = ms(started.elapsed().saturating_sub(waited));
+        push_sample(&mut self.work, self.cpu_ms + gpu_ms.unwrap_or(0.0));
+
+        if self.work_percentile(50.0) > budget_ms {
+            self.over_budget_since.get_or_insert_with(Instant::now);
+        } else {
+            self.over_budget_since = None;
+        }
+    }
+
+    /// Frame interval at percentile `p` (0–100) of the window, in milliseconds.
+    pub fn percentile(&self, p: f32) -> f32 {
+        percentile(&self.intervals, p)
+    }
+
+    /// Work time at percentile `p` (0–100) of the window, in milliseconds.
+    pub fn work_percentile(&self, p: f32) -> f32 {
+        percentile(&self.work, p)
+    }
+
+    pub fn fps(&self) -> f32 {
+        let total: f32 = self.intervals.iter().sum();
+        if total > 0.0 { self.intervals.len() as f32 * 1000.0 / total } else { 0.0 }
+    }
+
+    /// Frame intervals in milliseconds, oldest first.
+    pub fn intervals(&self) -> impl ExactSizeIterator<Item = f32> + '_ {
+        self.intervals.iter().copied()
+    }
+
+    /// Whether the window holds `FRAME_STATS_WINDOW` work samples.
+    pub fn is_full(&self) -> bool {
+        self.work.len() == FRAME_STATS_WINDOW
+    }
+
+    /// Start a fresh window, e.g. after the render cost changed.
+    pub fn restart(&mut self) {
+        self.intervals.clear();
+        self.work.clear();
+        self.over_budget_since = None;
+    }
+
+    /// Whether the median work time has stayed over budget for
+    /// `OVER_BUDGET_SECONDS`.
+    pub fn over_budget(&self) -> bool {
+        self.over_budget_since
+            .is_some_and(|since| since.elapsed().as_secs_f32() >= OVER_BUDGET_SECONDS)
+    }
+}
+
+impl Default for FrameStats {
+    fn default() -> Self { Self::new() }
+}
+
+/// Render scale multiplier chosen by the `LowerResolution` quality policy.
+///
+/// Steps down while frames are over budget and back up once the 95th
+/// percentile of work time has fit the budget for a while; each step waits for the stats
+/// window to refill at the new resolution.
+pub struct DynamicScale {
+    pub factor: f32,
+    last_change: Instant,
+}
+
+impl DynamicScale {
+    pub fn new() -> Self {
+        Self { factor: 1.0, last_change: Instant::now() }
+    }
+
+    /// Step the factor from `stats`, restarting them after a step so the
+    /// next decision only sees frames rendered at the new resolution.
+    pub fn update(&mut self, stats: &mut FrameStats, budget_ms: f32) {
+        if !stats.is_full() {
+            return;
+        }
+        let since_change = self.last_change.elapsed().as_secs_f32();
+        if stats.over_budget() && self.factor > MIN_RENDER_SCALE {
+            self.factor = (self.factor - RENDER_SCALE_STEP).max(MIN_RENDER_SCALE);
+        } else if since_change >= RENDER_SCALE_RECOVER_SECONDS
+            && self.factor < 1.0
+            && stats.work_percentile(95.0) < budget_ms
+        {
+            self.factor = (self.factor + RENDER_SCALE_STEP).min(1.0);
+        } else {
+            return;
+        }
+        self.last_change = Instant::now();
+        stats.restart();
+    }
+
+    pub fn reset(&mut self) {
+        self.factor = 1.0;
+    }
+}
+
+impl Default for DynamicScale {
+    fn default() -> Self { Self::new() }
+}
+
+fn push_sample(window: &mut VecDeque<f32>, value: f32) {
+    if window.len() == FRAME_STATS_WINDOW {
+        window.pop_front();
+    }
+    window.push_back(value);
+}
+
+fn percentile(window: &VecDeque<f32>, p: f32) -> f32 {
+    if window.is_empty() {
+        return 0.0;
+    }
+    let mut sorted: Vec<f32> = window.iter().copied().collect();
+    sorted.sort_by(f32::total_cmp);
+    let rank = (p / 100.0 * (sorted.len() - 1) as f32).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+fn ms(d: Duration) -> f32 {
+    d.as_secs_f32() * 1000.0
+}
@@ -0,0 +1,127 @@
+//! Frame pacing and power saving: the frame-rate cap, pausing while the
+//! window is hidden and idling at a low frame rate during silence
+
+use super::App;
+use crate::config::constants::{IDLE_WAKE_POLL_MS, SILENCE_RMS};
+use std::time::{Duration, Instant};
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, ControlFlow};
+
+/// What the render loop is doing to save power.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerMode {
+    /// Rendering at the capped or present-mode frame rate.
+    Active,
+    /// Rendering at the idle frame rate until sound or input returns.
+    Idle,
+    /// Not rendering while the window is hidden.
+    Paused,
+}
+
+impl PowerMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Active => "Active",
+            Self::Idle   => "Idle",
+            Self::Paused => "Paused",
+        }
+    }
+}
+
+pub struct PowerState {
+    pub mode: PowerMode,
+    /// When the input went silent; `None` while there is sound.
+    silent_since: Option<Instant>,
+    last_input: Instant,
+    /// The window reported itself fully covered.
+    occluded: bool,
+    /// Earliest time for the next frame under a frame-rate limit.
+    next_frame: Instant,
+}
+
+impl PowerState {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self { mode: PowerMode::Active, silent_since: None, last_input: now, occluded: false, next_frame: now }
+    }
+
+    /// Seconds without sound or input so far.
+    pub fn quiet_seconds(&self) -> f32 {
+        self.silent_since.map_or(0.0, |since| since.max(self.last_input).elapsed().as_secs_f32())
+    }
+
+    /// Follow occlusion and note user input, which ends idling.
+    pub fn handle(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::Occluded(occluded) => self.occluded = *occluded,
+            WindowEvent::KeyboardInput { .. }
+            | WindowEvent::MouseInput { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::CursorMoved { .. } => self.last_input = Instant::now(),
+            _ => {}
+        }
+    }
+}
+
+impl App {
+    /// Request the next frame now or schedule a wake-up for it, following
+    /// the power settings. Called whenever the event loop runs dry.
+    pub(crate) fn schedule_frame(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(window) = self.state.window.clone() else { return };
+        let power = self.state.settings.power.clone();
+        let size = window.inner_size();
+        let hidden = self.state.power.occluded
+            || window.is_minimized() == Some(true)
+            || size.width == 0
+            || size.height == 0;
+        if power.pause_when_hidden && hidden && self.state.replay.is_none() {
+            // Showing or restoring the window sends events that wake the loop.
+            self.state.power.mode = PowerMode::Paused;
+            event_loop.set_control_flow(ControlFlow::Wait);
+            return;
+        }
+
+        self.track_silence();
+        let now = Instant::now();
+        let idle = power.idle_when_silent
+            && self.state.replay.is_none()
+            && !self.state.attract.running()
+            && self.state.power.quiet_seconds() >= power.idle_after_seconds;
+        let fps = if idle { power.idle_fps } else { power.max_fps };
+        let mode = if idle { PowerMode::Idle } else { PowerMode::Active };
+        if mode != self.state.power.mode {
+            // Entering or leaving idle takes effect at once.
+            self.state.power.next_frame = now;
+            self.state.power.mode = mode;
+        }
+
+        if fps == 0 {
+            window.request_redraw();
+            event_loop.set_control_flow(ControlFlow::Wait);
+            return;
+        }
+        let interval = Duration::from_secs_f32(1.0 / fps as f32);
+        let power = &mut self.state.power;
+        if now >= power.next_frame {
+            window.request_redraw();
+            power.next_frame = (power.next_frame + interval).max(now);
+        }
+        // While idle, look at the input often enough to wake as soon as it sounds.
+        let wake = if idle { power.next_frame.min(now + Duration::from_millis(IDLE_WAKE_POLL_MS)) } else { power.next_frame };
+        event_loop.set_control_flow(ControlFlow::WaitUntil(wake));
+    }
+
+    /// Update when the input went silent from the newest captured samples.
+    fn track_silence(&mut self) {
+        let level = self.state.audio.as_ref().map_or(0.0, |audio| {
+            let samples = audio.buffer.lock().unwrap();
+            (samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32).sqrt()
+        });
+        let power = &mut self.state.power;
+        match (level < SILENCE_RMS, power.silent_since) {
+            (true, None)     => power.silent_since = Some(Instant::now()),
+            (false, Some(_)) => power.silent_since = None,
+            _ => {}
+        }
+    }
+}
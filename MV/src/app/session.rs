@@ -0,0 +1,144 @@
+//! Session recording (F6, `--record`) and replay (`--replay`)
+
+use super::*;
+use crate::input::session::{RecordedFrame, SessionEvent, SessionPlayer, SessionRecorder};
+use std::path::PathBuf;
+
+/// A session being replayed in place of live audio.
+pub struct Replay {
+    player: SessionPlayer,
+    /// Settings to return to when the replay ends.
+    live_settings: AppSettings,
+    started: bool,
+    size_warned: bool,
+}
+
+impl Replay {
+    /// Frames replayed so far.
+    pub fn frames(&self) -> u64 {
+        self.player.frames
+    }
+}
+
+impl App {
+    /// Start or stop recording to a timestamped file in the working directory.
+    pub(crate) fn toggle_recording(&mut self) {
+        if self.state.recorder.is_some() {
+            self.stop_recording();
+        } else {
+            let stamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            self.state.pending_record = Some(PathBuf::from(format!("session-{}.mvsession", stamp)));
+        }
+    }
+
+    pub(crate) fn stop_recording(&mut self) {
+        if let Some(recorder) = self.state.recorder.take() {
+            let frames = recorder.frames;
+            match recorder.finish() {
+                Ok(path) => eprintln!("Recorded {} frames to {}", frames, path.display()),
+                Err(e) => eprintln!("Failed to finish recording: {}", e),
+            }
+        }
+    }
+
+    /// Write the settings to disk, except during a replay: the settings in
+    /// use then are the recorded ones and must not replace the user's.
+    pub(crate) fn save_settings(&self) {
+        if self.state.replay.is_none() {
+            self.state.settings.save().ok();
+        }
+    }
+
+    pub(crate) fn start_replay(&mut self, player: SessionPlayer) {
+        self.state.replay = Some(Replay {
+            player,
+            live_settings: self.state.settings.clone(),
+            started: false,
+            size_warned: false,
+        });
+    }
+
+    /// Open a pending recording once the analysis state can be captured.
+    pub(crate) fn begin_pending_recording(&mut self) {
+        let Some(gpu) = &self.state.gpu else { return };
+        let Some(path) = self.state.pending_record.take() else { return };
+        match SessionRecorder::create(&path, &gpu.analyzer.snapshot()) {
+            Ok(recorder) => {
+                eprintln!("Recording session to {}", path.display());
+                self.state.recorder = Some(recorder);
+            }
+            Err(e) => eprintln!("Failed to start recording: {}", e),
+        }
+    }
+
+    /// Apply recorded settings up to the next frame and return that frame,
+    /// selecting its visualization. Ends the replay at the end of the file.
+    pub(crate) fn next_replay_frame(&mut self) -> Option<RecordedFrame> {
+        let replay = self.state.replay.as_mut()?;
+        let gpu = self.state.gpu.as_mut()?;
+        if !replay.started {
+            gpu.restore_analysis(&replay.player.analysis);
+            replay.started = true;
+        }
+
+        loop {
+            let event = self.state.replay.as_mut()?.player.next_event();
+            match event {
+                Ok(Some(SessionEvent::Settings(settings))) => {
+                    self.state.settings = *settings;
+                    self.rebuild_plugin_cache();
+                }
+                Ok(Some(SessionEvent::Frame(frame))) => {
+                    let gpu = self.state.gpu.as_ref()?;
+                    if let Some(index) = gpu.plugins.iter().position(|p| p.name == frame.plugin) {
+                        self.state.current_plugin_index = index;
+                    }
+                    return Some(frame);
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    eprintln!("Replay stopped: {}", e);
+                    break;
+                }
+            }
+        }
+        self.finish_replay();
+        None
+    }
+
+    fn finish_replay(&mut self) {
+        let Some(replay) = self.state.replay.take() else { return };
+        match replay.player.first_mismatch {
+            None => eprintln!("Replay finished: {} frames identical to the recording", replay.player.frames),
+            Some(frame) => eprintln!("Replay finished: {} frames, first difference at frame {}", replay.player.frames, frame),
+        }
+        self.state.settings = replay.live_settings;
+        self.rebuild_plugin_cache();
+    }
+
+    /// Record the frame just analysed, or check it against the replay.
+    pub(crate) fn session_frame(&mut self, frame: RecordedFrame, replayed: Option<&RecordedFrame>) {
+        if let Some(recorder) = &mut self.state.recorder {
+            let result = recorder.settings(&self.state.settings).and_then(|_| recorder.frame(&frame));
+            if let Err(e) = result {
+                eprintln!("Recording stopped: {}", e);
+                self.stop_recording();
+            }
+        }
+
+        let (Some(replay), Some(expected)) = (&mut self.state.replay, replayed) else { return };
+        if frame.scene_size != expected.scene_size && !replay.size_warned {
+            replay.size_warned = true;
+            eprintln!(
+                "Replay: scene is {}×{} but was recorded at {}×{}; uniforms will differ",
+                frame.scene_size.0, frame.scene_size.1, expected.scene_size.0, expected.scene_size.1,
+            );
+        }
+        if frame.digest != expected.digest && replay.player.first_mismatch.is_none() {
+            eprintln!("Replay: frame {} differs from the recording", replay.player.frames);
+            replay.player.first_mismatch = Some(replay.player.frames);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Live WGSL editing of fragment shaders
+//!
+//! The editor works on one plugin's source without its preamble. Edits are
+//! recompiled once typing pauses; a failed compile leaves the last good
+//! pipeline running. Saving writes `user_shaders/<name>.wgsl`, which the
+//! loader prefers over the embedded copy from then on.
+
+use super::App;
+use crate::common::error::{AppError, AppResult};
+use crate::config::constants::{EDITOR_RECOMPILE_DELAY_MS, USER_SHADER_DIR};
+use crate::visualization::loader::{embedded_fragment, fragment_source, user_wgsl_path, Preamble};
+use crate::visualization::wgsl::{self, SourceError};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+pub struct ShaderEditor {
+    /// Plugin being edited, by name.
+    pub plugin: String,
+    pub preamble: Preamble,
+    pub code: String,
+    /// File the code was read from or last saved to; `None` for the embedded copy.
+    pub user_file: Option<PathBuf>,
+    /// When the code last changed, while a recompile is pending.
+    pub edited_at: Option<Instant>,
+    /// Why the last compile failed; the shader on screen is the last good one.
+    pub error: Option<SourceError>,
+    /// The code differs from what is on disk (or embedded).
+    pub unsaved: bool,
+    /// Outcome of the last save or revert.
+    pub status: Option<String>,
+}
+
+impl ShaderEditor {
+    /// Open the source of plugin `name`; an error names why it cannot be edited.
+    pub fn open(name: &str) -> AppResult<Self> {
+        let source = fragment_source(name)?.ok_or_else(|| {
+            AppError::Plugin(format!("'{}' is not a WGSL fragment shader and cannot be edited here", name))
+        })?;
+        Ok(Self {
+            plugin: name.to_string(),
+            preamble: source.preamble,
+            code: source.body,
+            user_file: source.user_file,
+            edited_at: None,
+            error: None,
+            unsaved: false,
+            status: None,
+        })
+    }
+
+    /// Note a change to `code` for the next recompile.
+    pub fn touch(&mut self) {
+        self.edited_at = Some(Instant::now());
+        self.unsaved = true;
+    }
+
+    /// Whether an embedded shader exists to go back to.
+    pub fn has_builtin(&self) -> bool {
+        embedded_fragment(&self.plugin).is_some()
+    }
+}
+
+impl App {
+    /// Open the editor on the current visualization, unless it holds unsaved
+    /// changes to another one.
+    pub(crate) fn open_shader_editor(&mut self) {
+        self.state.show_shader_editor = true;
+        let Some(name) = self.state.gpu.as_ref()
+            .and_then(|gpu| gpu.plugins.get(self.state.current_plugin_index))
+            .map(|p| p.name.clone())
+        else { return };
+        if self.state.shader_editor.as_ref().is_some_and(|e| e.plugin == name || e.unsaved) {
+            return;
+        }
+        self.edit_shader(&name);
+    }
+
+    /// Load plugin `name` into the editor, dropping unsaved changes.
+    pub(crate) fn edit_shader(&mut self, name: &str) {
+        match ShaderEditor::open(name) {
+            Ok(editor) => {
+                self.state.shader_editor = Some(editor);
+                self.state.shader_editor_note = None;
+            }
+            Err(e) => {
+                self.state.shader_editor = None;
+                self.state.shader_editor_note = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Re-read the edited shader from disk (or the embedded copy), dropping
+    /// unsaved changes, and put it back on screen.
+    pub(crate) fn reload_shader_editor(&mut self) {
+        let Some(name) = self.state.shader_editor.as_ref().map(|e| e.plugin.clone()) else { return };
+        self.edit_shader(&name);
+        self.recompile_shader_editor();
+    }
+
+    /// Recompile the edited shader once typing has paused.
+    pub(crate) fn update_shader_editor(&mut self) {
+        let due = self.state.shader_editor.as_ref()
+            .and_then(|e| e.edited_at)
+            .is_some_and(|t| t.elapsed() >= Duration::from_millis(EDITOR_RECOMPILE_DELAY_MS));
+        if due {
+            self.recompile_shader_editor();
+        }
+    }
+
+    fn recompile_shader_editor(&mut self) {
+        let Some(editor) = self.state.shader_editor.as_mut() else { return };
+        editor.edited_at = None;
+        editor.error = wgsl::check(editor.preamble.source(), &editor.code, &["vs_main", "fs_main"]).err();
+        if editor.error.is_some() {
+            return;
+        }
+        let Some(gpu) = self.state.gpu.as_mut() else { return };
+        let Some(index) = gpu.plugins.iter().position(|p| p.name == editor.plugin) else { return };
+        if let Err(e) = gpu.reload_fragment(index, editor.preamble, &editor.code) {
+            editor.error = Some(SourceError { line: None, message: e.to_string() });
+        }
+    }
+
+    /// Write the edited code to the user shader directory.
+    pub(crate) fn save_shader_editor(&mut self) {
+        let Some(editor) = self.state.shader_editor.as_mut() else { return };
+        let path = user_wgsl_path(&editor.plugin);
+        let result = std::fs::create_dir_all(USER_SHADER_DIR).and_then(|_| std::fs::write(&path, &editor.code));
+        editor.status = Some(match result {
+            Ok(()) => {
+                editor.unsaved = false;
+                editor.user_file = Some(path.clone());
+                format!("Saved to {}", path.display())
+            }
+            Err(e) => format!("Could not save {}: {}", path.display(), e),
+        });
+    }
+
+    /// Delete the user copy and go back to the embedded shader.
+    pub(crate) fn revert_shader_editor(&mut self) {
+        let Some(editor) = self.state.shader_editor.as_mut() else { return };
+        let Some((preamble, body)) = embedded_fragment(&editor.plugin) else { return };
+        if let Some(path) = &editor.user_file {
+            if let Err(e) = std::fs::remove_file(path) {
+                editor.status = Some(format!("Could not delete {}: {}", path.display(), e));
+                return;
+            }
+        }
+        editor.preamble = preamble;
+        editor.code = body.to_string();
+        editor.user_file = None;
+        editor.unsaved = false;
+        editor.status = Some("Back to the built-in shader".to_string());
+        self.recompile_shader_editor();
+    }
+}
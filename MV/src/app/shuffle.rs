@@ -0,0 +1,104 @@
+//! Play statistics and the weighted shuffle of auto-switch
+
+use super::App;
+use crate::config::constants::{MAX_AVOID_RECENT, NEUTRAL_RATING, PLAY_STATS_SAVE_SECONDS};
+use crate::visualization::shader_category;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// xorshift64* generator for shuffle picks, seeded from the clock.
+pub struct ShuffleRng(u64);
+
+impl ShuffleRng {
+    pub fn new() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64);
+        Self(nanos | 1)
+    }
+
+    /// Uniform in 0..1.
+    pub fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// Shuffle weight of a visualization: the square of its rating, so five
+/// stars come up about three times as often as three, doubled for favorites.
+fn weight(rating: Option<u8>, favorite: bool) -> f32 {
+    let stars = rating.unwrap_or(NEUTRAL_RATING) as f32;
+    stars * stars * if favorite { 2.0 } else { 1.0 }
+}
+
+impl App {
+    /// Count play time of the visualization on screen. When it changes, the
+    /// previous one joins the recent list. The statistics are saved every
+    /// `PLAY_STATS_SAVE_SECONDS`.
+    pub(crate) fn update_play_stats(&mut self, dt: f32) {
+        let Some(name) = self.state.gpu.as_ref()
+            .and_then(|gpu| gpu.plugins.get(self.state.current_plugin_index))
+            .map(|p| p.name.as_str())
+        else { return };
+
+        if self.state.shown_plugin.as_deref() != Some(name) {
+            let name = name.to_string();
+            self.state.play_history.start(&name);
+            if let Some(previous) = self.state.shown_plugin.replace(name) {
+                if self.state.recent_plugins.len() == MAX_AVOID_RECENT {
+                    self.state.recent_plugins.pop_front();
+                }
+                self.state.recent_plugins.push_back(previous);
+            }
+        }
+        if let Some(name) = &self.state.shown_plugin {
+            self.state.play_history.add_time(name, dt);
+        }
+        if self.state.play_history_saved.elapsed().as_secs_f32() >= PLAY_STATS_SAVE_SECONDS {
+            self.state.play_history_saved = Instant::now();
+            if let Err(e) = self.state.play_history.save() {
+                eprintln!("Failed to save play statistics: {}", e);
+            }
+        }
+    }
+
+    /// A weighted random pick among the visualizations auto-switch may show
+    /// next, leaving out the current and the recently shown ones; `None`
+    /// when there is no other.
+    pub(crate) fn shuffle_pick(&mut self, skip_heavy: bool) -> Option<usize> {
+        if self.state.enabled_plugin_cache.is_empty() {
+            self.rebuild_plugin_cache();
+        }
+        let roll = self.state.shuffle_rng.next_f32();
+        let settings = &self.state.settings;
+        let plugins = &self.state.gpu.as_ref()?.plugins;
+        let candidates: Vec<(usize, &str)> = self.state.enabled_plugin_cache.iter()
+            .filter(|&&i| i != self.state.current_plugin_index && self.auto_switch_allows(i, skip_heavy))
+            .filter_map(|&i| Some((i, plugins.get(i)?.name.as_str())))
+            .collect();
+
+        // Never avoid so many that nothing is left.
+        let avoid = settings.auto_switch.avoid_recent.min(candidates.len().saturating_sub(1));
+        let recent: Vec<&str> = self.state.recent_plugins.iter().rev().take(avoid).map(String::as_str).collect();
+        let pool: Vec<(usize, f32)> = candidates.iter()
+            .filter(|(_, name)| !recent.contains(name))
+            .map(|&(i, name)| (i, weight(settings.ratings.get(name).copied(), settings.favorites.contains(name))))
+            .collect();
+
+        let mut target = roll * pool.iter().map(|(_, w)| w).sum::<f32>();
+        for &(index, w) in &pool {
+            if target < w {
+                return Some(index);
+            }
+            target -= w;
+        }
+        pool.last().map(|&(index, _)| index)
+    }
+
+    /// Whether auto-switch may land on plugin `index`: its category passes
+    /// the filter and it is not Heavy while those are skipped.
+    pub(crate) fn auto_switch_allows(&self, index: usize, skip_heavy: bool) -> bool {
+        let Some(plugin) = self.state.gpu.as_ref().and_then(|g| g.plugins.get(index)) else { return false };
+        let heavy = plugin.info.is_some_and(|info| info.performance == crate::visualization::shader_info::PerformanceTier::Heavy);
+        self.state.settings.auto_switch.allows(shader_category(&plugin.name)) && !(skip_heavy && heavy)
+    }
+}
@@ -1,11 +1,24 @@
 //! Application state management
 
 use crate::input::audio::AudioHandler;
+use crate::input::sources::{AudioHost, AudioSource};
+use super::attract::AttractState;
+use super::perf::{DynamicScale, FrameStats};
+use super::power::PowerState;
+use super::text_overlay::TextFades;
+use super::session::Replay;
+use super::shader_editor::ShaderEditor;
+use super::shuffle::ShuffleRng;
+use crate::input::now_playing::{NowPlayingWatcher, TrackInfo};
+use crate::input::pointer::PointerState;
+use crate::input::session::SessionRecorder;
 use crate::config::constants::*;
+use crate::config::persistence::PlayHistory;
 use crate::config::settings::AppSettings;
-use crate::common::types::VisUniforms;
-use cpal::traits::DeviceTrait;
-use std::sync::Arc;
+use crate::common::error::AppResult;
+use crate::common::types::{AudioFeatures, VisUniforms};
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc};
 use std::time::Instant;
 use winit::window::Window;
 
@@ -14,6 +27,9 @@ use winit::window::Window;
 // ──────────────────────────────────────────────────────────────────────────────
 
 /// The three distinct compositing modes the window can be in.
+///
+/// Transparent lets the desktop show through dark pixels; Overlay adds
+/// always-on-top and lets clicks pass through to the windows below.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum WindowMode {
     #[default]
@@ -39,16 +55,14 @@ impl WindowMode {
         }
     }
 
-    pub fn needs_layered(self) -> bool {
+    pub fn needs_alpha(self) -> bool {
         matches!(self, Self::Transparent | Self::Overlay)
     }
 
-    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
     pub fn needs_click_through(self) -> bool {
         matches!(self, Self::Overlay)
     }
 
-    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
     pub fn needs_topmost(self) -> bool {
         matches!(self, Self::Overlay)
     }
@@ -59,11 +73,27 @@ pub struct AppState {
     pub(crate) window: Option<Arc<Window>>,
     pub(crate) gpu: Option<crate::graphics::GpuResources>,
     pub(crate) audio: Option<AudioHandler>,
-    pub(crate) devices: Vec<cpal::Device>,
+    pub(crate) host: AudioHost,
+    pub(crate) devices: Vec<AudioSource>,
+    /// The preferred device is unavailable; `audio` holds the default input
+    /// (or `None` for silence) until it reappears.
+    pub(crate) audio_fallback: bool,
+    pub(crate) last_device_scan: Instant,
+    /// Rescan running on a worker thread while falling back.
+    pub(crate) device_scan: Option<mpsc::Receiver<AppResult<Vec<AudioSource>>>>,
+    pub(crate) refresh_devices_requested: bool,
+    pub(crate) pending_host: Option<String>,
+    /// Why the last host switch or device scan failed.
+    pub(crate) host_error: Option<String>,
+    /// Network input address being edited in the device selector.
+    pub(crate) network_input_edit: String,
     pub(crate) uniforms: VisUniforms,
     pub(crate) current_plugin_index: usize,
     pub(crate) window_mode: WindowMode,
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
     pub(crate) transparency_level: u8,
+    /// What the platform could not do for the current window mode.
+    pub(crate) window_mode_note: Option<String>,
     pub(crate) show_info: bool,
     pub(crate) info_timer: Option<Instant>,
     pub(crate) show_device_selection: bool,
@@ -71,7 +101,8 @@ pub struct AppState {
     pub(crate) settings: AppSettings,
     pub(crate) egui_ctx: egui::Context,
     pub(crate) egui_raw_input: egui::RawInput,
-    pub(crate) egui_pointer_pos: egui::Pos2,
+    /// Text copied from egui fields; MV has no system clipboard access.
+    pub(crate) clipboard: String,
     pub(crate) current_modifiers: winit::keyboard::ModifiersState,
     pub(crate) transition_time: f32,
     pub(crate) transition_active: bool,
@@ -79,59 +110,87 @@ pub struct AppState {
     pub(crate) last_frame_time: Instant,
     pub(crate) enabled_plugin_cache: Vec<usize>,
     pub(crate) show_shader_browser: bool,
+    /// Image path being edited in the shader browser.
+    pub(crate) image_path_edit: String,
+    /// Why the last image assignment failed.
+    pub(crate) image_error: Option<String>,
+    /// Title of the playing track, when a metadata source knows it.
+    pub(crate) track_title: Option<String>,
+    pub(crate) now_playing_watcher: Option<NowPlayingWatcher>,
+    /// What the active media player reports.
+    pub(crate) now_playing: Option<TrackInfo>,
+    /// Why now-playing metadata is unavailable.
+    pub(crate) now_playing_note: Option<String>,
+    pub(crate) text_fades: TextFades,
+    /// Why the configured overlay font could not be loaded.
+    pub(crate) text_font_error: Option<String>,
+    pub(crate) show_shader_editor: bool,
+    pub(crate) shader_editor: Option<ShaderEditor>,
+    /// Why the current visualization cannot be opened in the editor.
+    pub(crate) shader_editor_note: Option<String>,
+    /// Play statistics, saved every `PLAY_STATS_SAVE_SECONDS` and on exit.
+    pub(crate) play_history: PlayHistory,
+    pub(crate) play_history_saved: Instant,
+    /// Name of the visualization the statistics are counting.
+    pub(crate) shown_plugin: Option<String>,
+    /// Previously shown visualizations, newest last.
+    pub(crate) recent_plugins: VecDeque<String>,
+    pub(crate) shuffle_rng: ShuffleRng,
+    /// Pointer state for the shaders, particles and orbit camera.
+    pub(crate) pointer: PointerState,
+    pub(crate) frame_stats: FrameStats,
+    pub(crate) dynamic_scale: DynamicScale,
+    pub(crate) power: PowerState,
+    pub(crate) attract: AttractState,
+    pub(crate) recorder: Option<SessionRecorder>,
+    /// Recording to start once the GPU state exists.
+    pub(crate) pending_record: Option<std::path::PathBuf>,
+    pub(crate) replay: Option<Replay>,
 }
 
 impl AppState {
-    pub fn new(devices: Vec<cpal::Device>, settings: AppSettings) -> Self {
-        let mut audio = None;
-        let mut show_device_selection = false;
-
-        if let Some(selected_name) = &settings.selected_device {
-            if let Some(index) = devices.iter().position(|d| d.name().ok().as_ref() == Some(selected_name)) {
-                if let Ok(audio_handler) = AudioHandler::new(devices[index].clone()) {
-                    audio = Some(audio_handler);
-                } else {
-                    show_device_selection = true;
-                }
-            } else {
-                show_device_selection = true;
-            }
-        } else {
-            show_device_selection = true;
-        }
-
+    pub fn new(host: AudioHost, settings: AppSettings) -> Self {
         Self {
             window: None,
             gpu: None,
-            audio,
-            devices,
+            audio: None,
+            host,
+            devices: Vec::new(),
+            audio_fallback: false,
+            last_device_scan: Instant::now(),
+            device_scan: None,
+            refresh_devices_requested: false,
+            pending_host: None,
+            host_error: None,
+            network_input_edit: settings.network_input.clone().unwrap_or_default(),
             uniforms: VisUniforms {
                 color: DEFAULT_COLOR,
-                intensity: DEFAULT_INTENSITY,
-                padding1: 0.0,
                 resolution: [DEFAULT_WINDOW_WIDTH as f32, DEFAULT_WINDOW_HEIGHT as f32],
-                mode: 0,
-                padding3a: 0,
-                padding3b: 0,
-                padding3c: 0,
-                padding2: [0; 3],
+                intensity: DEFAULT_INTENSITY,
                 time: 0.0,
+                mode: 0,
                 bass_energy: 0.0,
                 smoothing_factor: 0.1,
                 gain: 1.5,
                 beat_intensity: 0.0,
+                mouse_buttons: 0,
+                mouse: [0.0; 2],
+                mouse_drag: [0.0; 2],
+                mouse_scroll: [0.0; 2],
+                features: AudioFeatures::default(),
             },
             current_plugin_index: 0,
             window_mode: WindowMode::Normal,
             transparency_level: DEFAULT_TRANSPARENCY,
+            window_mode_note: None,
             show_info: false,
             info_timer: None,
-            show_device_selection,
+            show_device_selection: false,
             pending_device_index: None,
             settings,
             egui_ctx: egui::Context::default(),
             egui_raw_input: egui::RawInput::default(),
-            egui_pointer_pos: egui::Pos2::ZERO,
+            clipboard: String::new(),
             current_modifiers: winit::keyboard::ModifiersState::default(),
             transition_time: 0.0,
             transition_active: false,
@@ -139,6 +198,30 @@ impl AppState {
             last_frame_time: Instant::now(),
             enabled_plugin_cache: Vec::new(),
             show_shader_browser: false,
+            image_path_edit: String::new(),
+            image_error: None,
+            track_title: None,
+            now_playing_watcher: None,
+            now_playing: None,
+            now_playing_note: None,
+            text_fades: TextFades::new(),
+            text_font_error: None,
+            show_shader_editor: false,
+            shader_editor: None,
+            shader_editor_note: None,
+            play_history: PlayHistory::load(),
+            play_history_saved: Instant::now(),
+            shown_plugin: None,
+            recent_plugins: VecDeque::new(),
+            shuffle_rng: ShuffleRng::new(),
+            pointer: PointerState::default(),
+            frame_stats: FrameStats::new(),
+            dynamic_scale: DynamicScale::new(),
+            power: PowerState::new(),
+            attract: AttractState::new(),
+            recorder: None,
+            pending_record: None,
+            replay: None,
         }
     }
 }
@@ -0,0 +1,102 @@
+//! Text overlay content: the caption, the clock, the track title and the
+//! attract-mode caption
+
+use super::App;
+use crate::graphics::text_overlay::TextLine;
+
+/// How far each overlay line has faded in, 0–1.
+pub struct TextFades {
+    caption: f32,
+    track: f32,
+    clock: f32,
+    attract: f32,
+    /// Track title on screen, kept while it fades out.
+    shown_track: String,
+}
+
+impl TextFades {
+    pub fn new() -> Self {
+        Self { caption: 0.0, track: 0.0, clock: 0.0, attract: 0.0, shown_track: String::new() }
+    }
+}
+
+impl App {
+    /// Advance the fades by `dt` seconds and hand this frame's lines to the
+    /// GPU text layer. A new track title fades in from scratch.
+    pub(crate) fn update_text_overlay(&mut self, dt: f32) {
+        let text = &self.state.settings.text;
+        let attract = &self.state.settings.attract;
+        let plugin_name = self.state.gpu.as_ref()
+            .and_then(|gpu| gpu.plugins.get(self.state.current_plugin_index))
+            .map_or("", |p| p.name.as_str());
+        let attract_caption = attract.caption.replace("{name}", plugin_name);
+        let fades = &mut self.state.text_fades;
+        let Some(gpu) = self.state.gpu.as_mut() else { return };
+        if gpu.text.font_path() != text.font.as_deref() {
+            self.state.text_font_error = gpu.text.set_font(text.font.as_deref()).err().map(|e| e.to_string());
+        }
+
+        let step = if text.fade_seconds > 0.0 { dt / text.fade_seconds } else { 1.0 };
+        let caption_shown = text.caption_style.enabled && !text.caption.trim().is_empty();
+        approach(&mut fades.caption, caption_shown, step);
+
+        let title = self.state.track_title.as_deref().unwrap_or_default();
+        if !title.is_empty() && title != fades.shown_track {
+            fades.shown_track = title.to_string();
+            fades.track = 0.0;
+        }
+        approach(&mut fades.track, text.track_style.enabled && !title.is_empty(), step);
+        if fades.track == 0.0 && title.is_empty() {
+            fades.shown_track.clear();
+        }
+
+        approach(&mut fades.clock, text.clock_style.enabled, step);
+        let attract_shown = self.state.attract.active && attract.caption_style.enabled && !attract_caption.trim().is_empty();
+        approach(&mut fades.attract, attract_shown, step);
+        let clock = if fades.clock > 0.0 {
+            let [h, m, s] = local_time();
+            if text.clock_seconds { format!("{:02}:{:02}:{:02}", h, m, s) } else { format!("{:02}:{:02}", h, m) }
+        } else {
+            String::new()
+        };
+
+        // Hidden lines get no text, which frees their textures.
+        let line = |text: &str, style, fade: f32| TextLine {
+            text: if fade > 0.0 { text.to_string() } else { String::new() },
+            style,
+            fade,
+        };
+        let lines = [
+            line(&text.caption, text.caption_style, fades.caption),
+            line(&fades.shown_track, text.track_style, fades.track),
+            line(&clock, text.clock_style, fades.clock),
+            line(&attract_caption, attract.caption_style, fades.attract),
+        ];
+        let screen = [gpu.config.width, gpu.config.height];
+        let beat = gpu.analyzer.beat_intensity();
+        gpu.text.prepare(&gpu.device, &gpu.queue, &lines, text.color, screen, beat);
+    }
+}
+
+/// Move `value` one `step` towards 1 when `visible`, towards 0 otherwise.
+fn approach(value: &mut f32, visible: bool, step: f32) {
+    *value = if visible { (*value + step).min(1.0) } else { (*value - step).max(0.0) };
+}
+
+/// Local wall-clock time as hours, minutes and seconds.
+#[cfg(unix)]
+fn local_time() -> [u32; 3] {
+    let tm = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&now, &mut tm);
+        tm
+    };
+    [tm.tm_hour as u32, tm.tm_min as u32, tm.tm_sec as u32]
+}
+
+#[cfg(windows)]
+fn local_time() -> [u32; 3] {
+    let time = unsafe { windows::Win32::System::SystemInformation::GetLocalTime() };
+    [time.wHour as u32, time.wMinute as u32, time.wSecond as u32]
+}
@@ -0,0 +1,97 @@
+//! Transparent and overlay window modes
+//!
+//! Built on winit's transparent windows: the composite writes per-pixel
+//! alpha when the surface offers a blending `alpha_mode`, Overlay raises the
+//! window level and turns off cursor hit-testing. On Windows, surfaces without
+//! blending fall back to whole-window layered alpha.
+
+use super::App;
+use crate::config::constants::*;
+use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+use std::sync::Arc;
+use winit::window::{Window, WindowLevel};
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{COLORREF, HWND};
+#[cfg(target_os = "windows")]
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE,
+    LAYERED_WINDOW_ATTRIBUTES_FLAGS, WS_EX_LAYERED,
+};
+
+impl App {
+    /// Apply `window_mode` to the window and note what the platform refused.
+    pub(crate) fn apply_window_mode(&mut self) {
+        let Some(window) = self.state.window.as_ref().map(Arc::clone) else { return };
+        let mode = self.state.window_mode;
+        let mut issues = Vec::new();
+
+        window.set_transparent(mode.needs_alpha());
+        if !self.sync_window_alpha() {
+            issues.push(if cfg!(target_os = "windows") {
+                "no per-pixel alpha, using whole-window opacity"
+            } else {
+                "the surface cannot blend with the desktop"
+            });
+        }
+        #[cfg(target_os = "windows")]
+        self.apply_layered_alpha(&window);
+
+        window.set_window_level(if mode.needs_topmost() { WindowLevel::AlwaysOnTop } else { WindowLevel::Normal });
+        if mode.needs_topmost() && is_wayland(&window) {
+            issues.push("Wayland compositors may ignore always-on-top");
+        }
+        if window.set_cursor_hittest(!mode.needs_click_through()).is_err() && mode.needs_click_through() {
+            issues.push("click-through is not supported here");
+        }
+
+        self.state.window_mode_note = (!issues.is_empty()).then(|| issues.join("; "));
+    }
+
+    /// Keep the composite alpha in step with the mode and the opacity slider.
+    /// Returns false when see-through was asked for but is unavailable.
+    pub(crate) fn sync_window_alpha(&mut self) -> bool {
+        let opacity = self.state.window_mode.needs_alpha().then_some(self.state.settings.transparency);
+        self.state.gpu.as_mut().is_none_or(|gpu| gpu.set_window_alpha(opacity))
+    }
+
+    pub(crate) fn adjust_transparency_level(&mut self, increase: bool) {
+        let step = TRANSPARENCY_STEP as f32 / MAX_TRANSPARENCY_ALPHA as f32;
+        if increase {
+            self.state.settings.transparency = (self.state.settings.transparency + step).min(1.0);
+        } else {
+            self.state.settings.transparency = (self.state.settings.transparency - step).max(0.1);
+        }
+        #[cfg(debug_assertions)]
+        eprintln!("Opacity: {}%", (self.state.settings.transparency * 100.0) as u32);
+    }
+
+    /// Whole-window opacity for surfaces without per-pixel alpha. Only the
+    /// layered attributes are touched; winit owns the click-through styles.
+    #[cfg(target_os = "windows")]
+    pub(crate) fn apply_layered_alpha(&mut self, window: &Window) {
+        let per_pixel = self.state.gpu.as_ref().is_some_and(|gpu| gpu.post.window_alpha.is_some());
+        let alpha = if self.state.window_mode.needs_alpha() && !per_pixel {
+            (self.state.settings.transparency * MAX_TRANSPARENCY_ALPHA as f32)
+                .clamp(MIN_TRANSPARENCY_ALPHA as f32, MAX_TRANSPARENCY_ALPHA as f32) as u8
+        } else {
+            MAX_TRANSPARENCY_ALPHA
+        };
+        self.state.transparency_level = alpha;
+        let Ok(handle) = window.window_handle() else { return };
+        let RawWindowHandle::Win32(win32_handle) = handle.as_raw() else { return };
+        let hwnd = HWND(win32_handle.hwnd.get() as isize);
+        unsafe {
+            let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+            if alpha < MAX_TRANSPARENCY_ALPHA {
+                SetWindowLongPtrW(hwnd, GWL_EXSTYLE, ex_style | WS_EX_LAYERED.0 as isize);
+            } else if ex_style & WS_EX_LAYERED.0 as isize == 0 {
+                return;
+            }
+            let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LAYERED_WINDOW_ATTRIBUTES_FLAGS(2));
+        }
+    }
+}
+
+fn is_wayland(window: &Window) -> bool {
+    window.window_handle().is_ok_and(|handle| matches!(handle.as_raw(), RawWindowHandle::Wayland(_)))
+}
@@ -1,25 +1,165 @@
 //! Shared data types for GPU buffers
 
-/// Uniforms for visualization shaders
+/// Declares a `#[repr(C)]` struct shared with WGSL along with `FIELDS`, its
+/// field names and byte offsets in declaration order, so the layout can be
+/// compared with the WGSL declaration (a test in `visualization::wgsl` does).
+macro_rules! gpu_struct {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $($(#[$field_meta:meta])* pub $field:ident: $ty:ty,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(C)]
+        pub struct $name {
+            $($(#[$field_meta])* pub $field: $ty,)*
+        }
+
+        impl $name {
+            #[allow(dead_code)]
+            pub const FIELDS: &'static [(&'static str, usize)] = &[
+                $((stringify!($field), std::mem::offset_of!($name, $field)),)*
+            ];
+        }
+    };
+}
+
+/// `AudioFeatures::version` of this build; bumped whenever fields are added.
+pub const AUDIO_FEATURES_VERSION: u32 = 1;
+
+gpu_struct! {
+    /// Uniforms for visualization shaders (`Uniforms` in `shaders/uniforms.wgsl`)
+    #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+    pub struct VisUniforms {
+        pub color: [f32; 4],
+        pub resolution: [f32; 2],
+        pub intensity: f32,
+        pub time: f32,
+        pub mode: u32,
+        pub bass_energy: f32,
+        pub smoothing_factor: f32,
+        pub gain: f32,
+        /// Instantaneous beat intensity (0.0 = no beat, 1.0 = strong beat).
+        /// Decays each frame so shaders receive a smooth pulse on every detected beat.
+        pub beat_intensity: f32,
+        /// Held buttons as `input::pointer::MOUSE_*` bits.
+        pub mouse_buttons: u32,
+        /// Pointer position in scene pixels, origin top left.
+        pub mouse: [f32; 2],
+        /// Offset from where the held buttons were pressed, in scene pixels.
+        pub mouse_drag: [f32; 2],
+        /// Scroll accumulated since start, in lines.
+        pub mouse_scroll: [f32; 2],
+        pub features: AudioFeatures,
+    }
+}
+
+gpu_struct! {
+    /// Per-frame audio features (`AudioFeatures` in `shaders/uniforms.wgsl`).
+    /// Levels are before gain, with a full-scale sine reading 1.0.
+    #[derive(Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+    pub struct AudioFeatures {
+        pub version: u32,
+        /// Amplitude below 500 Hz.
+        pub low: f32,
+        /// Amplitude from 500 Hz to 4 kHz.
+        pub mid: f32,
+        /// Amplitude above 4 kHz.
+        pub high: f32,
+        pub rms: f32,
+        /// Largest absolute sample.
+        pub peak: f32,
+        /// RMS level from -60 dBFS (0) to 0 dBFS (1).
+        pub loudness: f32,
+        /// Spectral centroid as a fraction of the Nyquist frequency.
+        pub centroid: f32,
+        /// Spectral flatness: 0 = tonal, 1 = noise-like.
+        pub flatness: f32,
+        /// 0 on a beat, rising to 1 at the next expected one.
+        pub beat_phase: f32,
+        /// Estimated beats per minute, 0 until known.
+        pub tempo: f32,
+        pub padding: f32,
+    }
+}
+
+impl Default for AudioFeatures {
+    fn default() -> Self {
+        Self { version: AUDIO_FEATURES_VERSION, ..bytemuck::Zeroable::zeroed() }
+    }
+}
+
+/// Uniforms for the post-processing chain (`shaders/post/post_fx.wgsl`)
 #[repr(C)]
-#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct VisUniforms {
-    pub color: [f32; 4],
-    pub intensity: f32,
-    pub padding1: f32,
-    pub resolution: [f32; 2],
-    pub mode: u32,
-    pub padding3a: u32,
-    pub padding3b: u32,
-    pub padding3c: u32,
-    pub padding2: [u32; 3],
+#[derive(Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PostUniforms {
     pub time: f32,
-    pub bass_energy: f32,
-    pub smoothing_factor: f32,
-    pub gain: f32,
-    /// Instantaneous beat intensity (0.0 = no beat, 1.0 = strong beat).
-    /// Decays each frame so shaders receive a smooth pulse on every detected beat.
     pub beat_intensity: f32,
+    pub bass_energy: f32,
+    /// Bitmask of enabled effects (see `graphics::postprocess::FX_*`).
+    pub flags: u32,
+    pub bloom_threshold: f32,
+    pub bloom_intensity: f32,
+    pub trails_decay: f32,
+    pub chromatic_amount: f32,
+    pub grain_amount: f32,
+    pub vignette_strength: f32,
+    /// Window opacity for see-through window modes (`FX_TRANSPARENT`).
+    pub opacity: f32,
+    pub padding: f32,
+}
+
+/// Size of the image assigned to an image-input shader (`shaders/common_image.wgsl`)
+#[repr(C)]
+#[derive(Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ImageUniforms {
+    pub size: [f32; 2],
+    pub padding: [f32; 2],
+}
+
+/// One line of the text overlay (`shaders/text_overlay.wgsl`)
+#[repr(C)]
+#[derive(Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TextUniforms {
+    /// Quad corners in clip space: left, top, right, bottom.
+    pub rect: [f32; 4],
+    /// Text colour, with opacity × fade in alpha.
+    pub color: [f32; 4],
+    /// Drop shadow offset in texture UV.
+    pub shadow: [f32; 2],
+    pub padding: [f32; 2],
+}
+
+/// Uniforms for imported Shadertoy shaders (`shaders/shadertoy/common_shadertoy.glsl`)
+#[repr(C)]
+#[derive(Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShadertoyUniforms {
+    pub resolution: [f32; 3],
+    pub time: f32,
+    /// xy = current position while the left button is held, zw = click
+    /// position (negated once released). Pixels, origin bottom-left.
+    pub mouse: [f32; 4],
+    pub time_delta: f32,
+    pub frame: i32,
+    pub padding: [f32; 2],
+}
+
+/// Shared camera for mesh plugins (`shaders/common_mesh.wgsl`)
+#[repr(C)]
+#[derive(Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraUniforms {
+    pub view_proj: [[f32; 4]; 4],
+    /// World-space camera position (w unused).
+    pub eye: [f32; 4],
+}
+
+/// Vertex of a mesh plugin's geometry
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
 }
 
 /// Particle structure for GPU
@@ -28,7 +168,32 @@ pub struct VisUniforms {
 pub struct Particle {
     pub position: [f32; 2],
     pub velocity: [f32; 2],
+    /// Seconds left before the particle respawns.
     pub lifetime: f32,
-    pub padding: [f32; 3],
+    /// Lifetime it spawned with, used to fade it out.
+    pub max_lifetime: f32,
+    pub padding: [f32; 2],
     pub color: [f32; 4],
 }
+
+/// Per-frame parameters of the particle simulation (`shaders/compute_particles.wgsl`)
+#[repr(C)]
+#[derive(Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ParticleParams {
+    pub count: u32,
+    /// `ParticleEmitter` as 0 = floor, 1 = centre, 2 = ring, 3 = screen.
+    pub emitter: u32,
+    /// `ParticleColor` as 0 = scheme, 1 = rainbow, 2 = spectrum.
+    pub color_mode: u32,
+    /// 1 on the frame a beat starts while bursts are enabled.
+    pub burst: u32,
+    pub lifetime: f32,
+    pub gravity: f32,
+    pub dt: f32,
+    pub burst_fraction: f32,
+    /// Click position in clip space.
+    pub emit_pos: [f32; 2],
+    /// 1 on the frame of a click.
+    pub emit: u32,
+    pub emit_fraction: f32,
+}
@@ -0,0 +1,70 @@
+//! Command-line options
+
+use crate::common::error::{AppError, AppResult};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: mv [OPTIONS]
+
+Options:
+  --host <NAME>          Audio host to capture from (saved for later runs)
+  --list-hosts           List audio hosts and whether they are available
+  --listen <URL>         Visualize PCM received on udp://ADDR:PORT or tcp://ADDR:PORT
+  --send-audio <URL>     Send the selected input to a listening MV instead of visualizing
+  --record <FILE>        Record the session (audio, settings, frames) from startup
+  --replay <FILE>        Replay a recorded session instead of live audio
+  --analyze <FILE>       Write per-frame audio features of a WAV file instead of visualizing
+  --output <FILE>        Feature file for --analyze (default: stdout)
+  --format <FORMAT>      csv or jsonl (default: from the --output extension, else csv)
+  --fps <N>              Analysis frame rate for --analyze (default: 60)
+  -h, --help             Show this help";
+
+/// Options given on the command line.
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    pub host: Option<String>,
+    pub list_hosts: bool,
+    pub listen: Option<String>,
+    pub send_audio: Option<String>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub analyze: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub format: Option<String>,
+    pub fps: Option<f32>,
+    pub help: bool,
+}
+
+impl CliArgs {
+    pub fn parse() -> AppResult<Self> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    fn parse_from(mut args: impl Iterator<Item = String>) -> AppResult<Self> {
+        let mut cli = Self::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--host"       => cli.host = Some(value(&mut args, &arg)?),
+                "--list-hosts" => cli.list_hosts = true,
+                "--listen"     => cli.listen = Some(value(&mut args, &arg)?),
+                "--send-audio" => cli.send_audio = Some(value(&mut args, &arg)?),
+                "--record"     => cli.record = Some(value(&mut args, &arg)?.into()),
+                "--replay"     => cli.replay = Some(value(&mut args, &arg)?.into()),
+                "--analyze"    => cli.analyze = Some(value(&mut args, &arg)?.into()),
+                "--output"     => cli.output = Some(value(&mut args, &arg)?.into()),
+                "--format"     => cli.format = Some(value(&mut args, &arg)?),
+                "--fps"        => {
+                    let fps = value(&mut args, &arg)?;
+                    cli.fps = Some(fps.parse().map_err(|_| AppError::Config(format!("--fps needs a number, got '{}'", fps)))?);
+                }
+                "-h" | "--help" => cli.help = true,
+                _ => return Err(AppError::Config(format!("Unknown argument '{}'\n\n{}", arg, USAGE))),
+            }
+        }
+        Ok(cli)
+    }
+}
+
+fn value(args: &mut impl Iterator<Item = String>, flag: &str) -> AppResult<String> {
+    args.next().ok_or_else(|| AppError::Config(format!("{} needs a value", flag)))
+}
@@ -7,8 +7,89 @@ pub const DEFAULT_WINDOW_HEIGHT: u32 = 600;
 /// Audio buffer size
 pub const SAMPLE_SIZE: usize = 512;
 
-/// Number of particles for particle system
-pub const NUM_PARTICLES: u32 = 1000;
+/// Format of the offscreen scene target that plugins and particles render into.
+/// Floating point so bright values survive until the post-processing chain.
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Depth attachment format for mesh plugins.
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Bloom is blurred at 1/N of the window resolution.
+pub const BLOOM_DOWNSCALE: u32 = 2;
+
+/// Directory (relative to the working directory) scanned for user shaders.
+pub const USER_SHADER_DIR: &str = "user_shaders";
+
+/// Buffer passes (A, B, C) available to a multi-buffer shader.
+pub const MAX_SHADER_BUFFERS: usize = 3;
+
+/// Width of the Shadertoy `iChannel0` audio texture (spectrum and waveform rows).
+pub const AUDIO_TEXTURE_WIDTH: u32 = 512;
+
+/// Orbit camera: base yaw speed (rad/s), extra speed at full bass, and radians per dragged pixel.
+pub const CAMERA_ORBIT_SPEED: f32 = 0.15;
+pub const CAMERA_BASS_SPEED: f32 = 0.6;
+pub const CAMERA_DRAG_SENSITIVITY: f32 = 0.008;
+
+/// Seconds without input callbacks after which an audio stream counts as dead.
+pub const AUDIO_STALL_SECONDS: f32 = 2.0;
+
+/// Seconds between device rescans while waiting for the preferred device to return.
+pub const AUDIO_RESCAN_SECONDS: f32 = 3.0;
+
+/// Port used by network audio addresses that do not name one.
+pub const DEFAULT_NETWORK_PORT: u16 = 7878;
+
+/// Network audio queued before playout starts, absorbing arrival jitter.
+pub const NETWORK_TARGET_LATENCY_MS: u32 = 60;
+
+/// Network audio queued beyond this is dropped to bound the delay.
+pub const NETWORK_MAX_LATENCY_MS: u32 = 250;
+
+/// Largest network audio frame sent, kept under a typical MTU.
+pub const NETWORK_MAX_PAYLOAD: usize = 1400;
+
+/// Default number of particles in the particle overlay
+pub const DEFAULT_PARTICLE_COUNT: u32 = 1000;
+
+/// Upper bound for the configurable particle count
+pub const MAX_PARTICLE_COUNT: u32 = 20_000;
+
+/// Fraction of the particles relaunched from a click.
+pub const CLICK_PARTICLE_FRACTION: f32 = 0.1;
+
+/// Frames kept for the performance HUD's frame-time percentiles and graph.
+pub const FRAME_STATS_WINDOW: usize = 240;
+
+/// Seconds the median frame time must stay over budget before the quality policy reacts.
+pub const OVER_BUDGET_SECONDS: f32 = 3.0;
+
+/// Lowest render scale, whether configured or chosen by the quality policy.
+pub const MIN_RENDER_SCALE: f32 = 0.5;
+
+/// Render scale change per step of the `LowerResolution` quality policy.
+pub const RENDER_SCALE_STEP: f32 = 0.1;
+
+/// Seconds frames must stay comfortably within budget before the policy raises the scale again.
+pub const RENDER_SCALE_RECOVER_SECONDS: f32 = 10.0;
+
+/// Highest configurable frame-rate cap.
+pub const MAX_FPS_CAP: u32 = 360;
+
+/// Highest configurable idle frame rate.
+pub const MAX_IDLE_FPS: u32 = 30;
+
+/// Input RMS below which audio counts as silence (-60 dBFS).
+pub const SILENCE_RMS: f32 = 0.001;
+
+/// How often the input is checked for sound while idling, so it wakes at once.
+pub const IDLE_WAKE_POLL_MS: u64 = 30;
+
+/// Longest frame step counted, so time spent paused is not played through.
+pub const MAX_FRAME_SECONDS: f32 = 0.25;
+
+/// Seconds attract mode's synthetic audio takes to fade in or out.
+pub const ATTRACT_FADE_SECONDS: f32 = 2.0;
 
 /// Workgroup size for compute shader
 pub const COMPUTE_WORKGROUP_SIZE: u32 = 64;
@@ -43,7 +124,6 @@ pub const TRANSPARENCY_STEP: u8 = 25;
 pub const MIN_TRANSPARENCY_ALPHA: u8 = 25;
 
 /// Maximum alpha value – fully opaque (255/255 = 100%).
-#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
 pub const MAX_TRANSPARENCY_ALPHA: u8 = 255;
 
 /// Number of waveform history snapshots to keep.
@@ -67,3 +147,34 @@ pub const BEAT_THRESHOLD_HIGH: f32 = 1.8;
 
 /// How fast `beat_intensity` decays toward 0 each frame (multiplicative).
 pub const BEAT_DECAY: f32 = 0.85;
+
+/// Tempo range of the beat clock; beats closer together than the fastest
+/// tempo are ignored by it, longer gaps restart the estimate.
+pub const TEMPO_MIN_BPM: f32 = 40.0;
+pub const TEMPO_MAX_BPM: f32 = 200.0;
+
+/// Weight of each new beat interval in the tempo estimate.
+pub const TEMPO_SMOOTHING: f32 = 0.25;
+
+/// Expected beats that may pass without one before the tempo is forgotten.
+pub const TEMPO_TIMEOUT_BEATS: f32 = 4.0;
+
+/// Level `AudioFeatures::loudness` maps to 0; 0 dBFS maps to 1.
+pub const LOUDNESS_FLOOR_DB: f32 = -60.0;
+
+// ─── Shuffle ──────────────────────────────────────────────────────────────────
+
+/// Highest star rating of a visualization.
+pub const MAX_RATING: u8 = 5;
+
+/// Rating assumed for unrated visualizations when weighting a shuffle.
+pub const NEUTRAL_RATING: u8 = 3;
+
+/// Upper bound for how many recently shown visualizations a shuffle avoids.
+pub const MAX_AVOID_RECENT: usize = 10;
+
+/// Play statistics are written at most this often while running, and on exit.
+pub const PLAY_STATS_SAVE_SECONDS: f32 = 60.0;
+
+/// Pause after the last keystroke before the shader editor recompiles.
+pub const EDITOR_RECOMPILE_DELAY_MS: u64 = 400;
@@ -1 +1,73 @@
-// TODO: Implement 
+//! Play statistics kept between runs in `shader_stats.json`
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::common::error::{AppError, AppResult};
+
+const STATS_FILE: &str = "shader_stats.json";
+
+/// How much one visualization has been shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayStats {
+    /// Times it came on screen.
+    pub plays: u32,
+    /// Total time on screen in seconds.
+    pub seconds: f64,
+    /// Unix time it last came on screen.
+    pub last_played: u64,
+}
+
+/// Play statistics of every visualization shown so far, by name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayHistory {
+    pub shaders: HashMap<String, PlayStats>,
+}
+
+impl PlayHistory {
+    /// The saved history; empty when there is none or it cannot be read.
+    pub fn load() -> Self {
+        if !Path::new(STATS_FILE).exists() {
+            return Self::default();
+        }
+        match fs::read_to_string(STATS_FILE).map(|json| serde_json::from_str(&json)) {
+            Ok(Ok(history)) => history,
+            Ok(Err(e)) => {
+                eprintln!("Warning: failed to parse {STATS_FILE}: {e}. Starting afresh.");
+                Self::default()
+            }
+            Err(e) => {
+                eprintln!("Warning: failed to read {STATS_FILE}: {e}. Starting afresh.");
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> AppResult<()> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| AppError::Config(e.to_string()))?;
+        fs::write(STATS_FILE, json)?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> PlayStats {
+        self.shaders.get(name).copied().unwrap_or_default()
+    }
+
+    /// Count `name` coming on screen now.
+    pub fn start(&mut self, name: &str) {
+        let stats = self.shaders.entry(name.to_string()).or_default();
+        stats.plays += 1;
+        stats.last_played = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    }
+
+    /// Add `seconds` of screen time to `name`.
+    pub fn add_time(&mut self, name: &str, seconds: f32) {
+        if let Some(stats) = self.shaders.get_mut(name) {
+            stats.seconds += seconds as f64;
+        }
+    }
+}
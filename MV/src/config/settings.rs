@@ -1,9 +1,11 @@
 //! Application settings
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
+use crate::config::constants::{BEAT_THRESHOLD_HIGH, BEAT_THRESHOLD_LOW, BEAT_THRESHOLD_MED, MAX_AVOID_RECENT, MAX_FPS_CAP, MAX_IDLE_FPS, MAX_RATING};
+use crate::visualization::ShaderCategory;
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ColorScheme {
@@ -41,25 +43,672 @@ impl BeatSensitivity {
             Self::High   => "High",
         }
     }
+
+    /// Energy ratio over the rolling mean that counts as a beat.
+    pub fn threshold(self) -> f32 {
+        match self {
+            Self::Low    => BEAT_THRESHOLD_HIGH,
+            Self::Medium => BEAT_THRESHOLD_MED,
+            Self::High   => BEAT_THRESHOLD_LOW,
+        }
+    }
+}
+
+/// Post-processing chain applied to the HDR scene before it reaches the window.
+///
+/// Effects run in a fixed order: trails → bloom → chromatic aberration →
+/// vignette → film grain. Each one is skipped entirely when disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PostFxSettings {
+    pub bloom_enabled: bool,
+    /// Luminance above which pixels start to bloom.
+    pub bloom_threshold: f32,
+    pub bloom_intensity: f32,
+    pub trails_enabled: bool,
+    /// Fraction of the previous frame kept each frame (higher = longer trails).
+    pub trails_decay: f32,
+    pub chromatic_enabled: bool,
+    /// Channel offset at the screen edge, in UV units.
+    pub chromatic_amount: f32,
+    pub grain_enabled: bool,
+    pub grain_amount: f32,
+    pub vignette_enabled: bool,
+    pub vignette_strength: f32,
+    /// Boost bloom and chromatic aberration with the detected beat.
+    pub beat_reactive: bool,
+}
+
+impl PostFxSettings {
+    pub fn new() -> Self {
+        Self {
+            bloom_enabled: false,
+            bloom_threshold: 0.6,
+            bloom_intensity: 0.8,
+            trails_enabled: false,
+            trails_decay: 0.85,
+            chromatic_enabled: false,
+            chromatic_amount: 0.004,
+            grain_enabled: false,
+            grain_amount: 0.05,
+            vignette_enabled: false,
+            vignette_strength: 0.6,
+            beat_reactive: true,
+        }
+    }
+
+    fn clamp(&mut self) {
+        self.bloom_threshold = self.bloom_threshold.clamp(0.0, 2.0);
+        self.bloom_intensity = self.bloom_intensity.clamp(0.0, 3.0);
+        self.trails_decay = self.trails_decay.clamp(0.0, 0.98);
+        self.chromatic_amount = self.chromatic_amount.clamp(0.0, 0.02);
+        self.grain_amount = self.grain_amount.clamp(0.0, 0.3);
+        self.vignette_strength = self.vignette_strength.clamp(0.0, 1.0);
+    }
+}
+
+impl Default for PostFxSettings {
+    fn default() -> Self { Self::new() }
+}
+
+/// What to do when frames keep missing the budget.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum QualityPolicy {
+    /// Only report it in the HUD.
+    Off,
+    /// Leave Heavy-tier shaders out of auto-switch.
+    SkipHeavy,
+    /// Render the visualization at a lower resolution until frames fit again.
+    LowerResolution,
+}
+
+impl QualityPolicy {
+    pub const ALL: [Self; 3] = [Self::Off, Self::SkipHeavy, Self::LowerResolution];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Off             => "Off",
+            Self::SkipHeavy       => "Skip Heavy shaders",
+            Self::LowerResolution => "Lower resolution",
+        }
+    }
+}
+
+/// Performance HUD, render scale and automatic quality downgrade.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PerformanceSettings {
+    pub show_hud: bool,
+    pub policy: QualityPolicy,
+    /// Target frame time in milliseconds.
+    pub frame_budget_ms: f32,
+    /// Fraction of the window resolution the visualization renders at.
+    pub render_scale: f32,
+    /// Per-visualization render scale, replacing `render_scale`.
+    pub scale_overrides: HashMap<String, f32>,
+}
+
+impl PerformanceSettings {
+    pub fn new() -> Self {
+        Self {
+            show_hud: false,
+            policy: QualityPolicy::Off,
+            frame_budget_ms: 20.0,
+            render_scale: 1.0,
+            scale_overrides: HashMap::new(),
+        }
+    }
+
+    /// Render scale configured for the visualization `name`.
+    pub fn scale_for(&self, name: &str) -> f32 {
+        self.scale_overrides.get(name).copied().unwrap_or(self.render_scale)
+    }
+
+    fn clamp(&mut self) {
+        use crate::config::constants::MIN_RENDER_SCALE;
+        self.frame_budget_ms = self.frame_budget_ms.clamp(4.0, 100.0);
+        self.render_scale = self.render_scale.clamp(MIN_RENDER_SCALE, 1.0);
+        self.scale_overrides.values_mut().for_each(|s| *s = s.clamp(MIN_RENDER_SCALE, 1.0));
+    }
+}
+
+impl Default for PerformanceSettings {
+    fn default() -> Self { Self::new() }
+}
+
+/// How finished frames reach the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PresentMode {
+    /// Wait for vertical blank; never tears.
+    VSync,
+    /// Replace the queued frame with the newest one; low latency, no tearing.
+    Mailbox,
+    /// Present at once; may tear.
+    Immediate,
+}
+
+impl PresentMode {
+    pub const ALL: [Self; 3] = [Self::VSync, Self::Mailbox, Self::Immediate];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::VSync     => "VSync",
+            Self::Mailbox   => "Mailbox",
+            Self::Immediate => "Immediate",
+        }
+    }
+}
+
+/// Frame pacing and when to render less, or not at all.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerSettings {
+    /// Highest frame rate; 0 leaves it to the present mode.
+    pub max_fps: u32,
+    pub present_mode: PresentMode,
+    /// Stop rendering while the window is minimized or covered.
+    pub pause_when_hidden: bool,
+    /// Drop to `idle_fps` after `idle_after_seconds` without sound or input.
+    pub idle_when_silent: bool,
+    pub idle_after_seconds: f32,
+    pub idle_fps: u32,
+}
+
+impl PowerSettings {
+    pub fn new() -> Self {
+        Self {
+            max_fps: 0,
+            present_mode: PresentMode::VSync,
+            pause_when_hidden: true,
+            idle_when_silent: true,
+            idle_after_seconds: 30.0,
+            idle_fps: 10,
+        }
+    }
+
+    fn clamp(&mut self) {
+        self.max_fps = self.max_fps.min(MAX_FPS_CAP);
+        self.idle_after_seconds = self.idle_after_seconds.clamp(5.0, 600.0);
+        self.idle_fps = self.idle_fps.clamp(1, MAX_IDLE_FPS);
+    }
+}
+
+impl Default for PowerSettings {
+    fn default() -> Self { Self::new() }
+}
+
+/// Where new particles appear.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ParticleEmitter {
+    /// Along the bottom edge, shooting upwards.
+    Floor,
+    /// From the centre of the screen in every direction.
+    Centre,
+    /// From a circle around the centre, moving outwards.
+    Ring,
+    /// Anywhere on screen, drifting slowly.
+    Screen,
+}
+
+impl ParticleEmitter {
+    pub const ALL: [Self; 4] = [Self::Floor, Self::Centre, Self::Ring, Self::Screen];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Floor  => "Floor",
+            Self::Centre => "Centre",
+            Self::Ring   => "Ring",
+            Self::Screen => "Screen",
+        }
+    }
+}
+
+/// How each particle picks its colour when it spawns.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ParticleColor {
+    /// The active colour scheme.
+    Scheme,
+    /// A hue cycling over time, varied per particle.
+    Rainbow,
+    /// Hue by frequency, brightness by that band's energy.
+    Spectrum,
+}
+
+impl ParticleColor {
+    pub const ALL: [Self; 3] = [Self::Scheme, Self::Rainbow, Self::Spectrum];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Scheme   => "Scheme",
+            Self::Rainbow  => "Rainbow",
+            Self::Spectrum => "Spectrum",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ParticleBlend {
+    Alpha,
+    Additive,
+}
+
+/// Look and motion of the particle overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParticleStyle {
+    pub count: u32,
+    pub emitter: ParticleEmitter,
+    /// Mean lifetime of a particle in seconds.
+    pub lifetime: f32,
+    /// Downward acceleration in screen heights per second squared (negative floats up).
+    pub gravity: f32,
+    pub color: ParticleColor,
+    pub blend: ParticleBlend,
+    /// Respawn part of the swarm with extra speed on every detected beat.
+    pub beat_bursts: bool,
+    /// Fraction of particles relaunched by a burst.
+    pub burst_fraction: f32,
+}
+
+impl ParticleStyle {
+    pub fn new() -> Self {
+        Self {
+            count: crate::config::constants::DEFAULT_PARTICLE_COUNT,
+            emitter: ParticleEmitter::Floor,
+            lifetime: 2.0,
+            gravity: 1.5,
+            color: ParticleColor::Scheme,
+            blend: ParticleBlend::Alpha,
+            beat_bursts: true,
+            burst_fraction: 0.2,
+        }
+    }
+
+    fn clamp(&mut self) {
+        self.count = self.count.clamp(64, crate::config::constants::MAX_PARTICLE_COUNT);
+        self.lifetime = self.lifetime.clamp(0.5, 10.0);
+        self.gravity = self.gravity.clamp(-3.0, 3.0);
+        self.burst_fraction = self.burst_fraction.clamp(0.0, 1.0);
+    }
+}
+
+impl Default for ParticleStyle {
+    fn default() -> Self { Self::new() }
+}
+
+/// Particle overlay drawn on top of the visualizations.
+///
+/// `style` applies everywhere unless a visualization has its own entry in
+/// `overrides`; visualizations listed in `hidden_for` draw no particles.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParticleSettings {
+    pub enabled: bool,
+    pub style: ParticleStyle,
+    pub overrides: HashMap<String, ParticleStyle>,
+    pub hidden_for: HashSet<String>,
+}
+
+impl ParticleSettings {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            style: ParticleStyle::new(),
+            overrides: HashMap::new(),
+            hidden_for: HashSet::new(),
+        }
+    }
+
+    /// Whether the overlay is drawn over the visualization `name`.
+    pub fn visible_for(&self, name: &str) -> bool {
+        self.enabled && !self.hidden_for.contains(name)
+    }
+
+    /// Style in effect for the visualization `name`.
+    pub fn style_for(&self, name: &str) -> &ParticleStyle {
+        self.overrides.get(name).unwrap_or(&self.style)
+    }
+
+    fn clamp(&mut self) {
+        self.style.clamp();
+        self.overrides.values_mut().for_each(ParticleStyle::clamp);
+    }
+}
+
+impl Default for ParticleSettings {
+    fn default() -> Self { Self::new() }
+}
+
+/// Where a text overlay line sits on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TextAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Center,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl TextAnchor {
+    pub const ALL: [Self; 7] = [
+        Self::TopLeft, Self::Top, Self::TopRight, Self::Center, Self::BottomLeft, Self::Bottom, Self::BottomRight,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::TopLeft     => "Top left",
+            Self::Top         => "Top",
+            Self::TopRight    => "Top right",
+            Self::Center      => "Centre",
+            Self::BottomLeft  => "Bottom left",
+            Self::Bottom      => "Bottom",
+            Self::BottomRight => "Bottom right",
+        }
+    }
+
+    /// Horizontal and vertical position as fractions of the free space.
+    pub fn align(self) -> [f32; 2] {
+        match self {
+            Self::TopLeft     => [0.0, 0.0],
+            Self::Top         => [0.5, 0.0],
+            Self::TopRight    => [1.0, 0.0],
+            Self::Center      => [0.5, 0.5],
+            Self::BottomLeft  => [0.0, 1.0],
+            Self::Bottom      => [0.5, 1.0],
+            Self::BottomRight => [1.0, 1.0],
+        }
+    }
+}
+
+/// Placement and look of one text overlay line.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextStyle {
+    pub enabled: bool,
+    pub anchor: TextAnchor,
+    /// Text height as a fraction of the window height.
+    pub size: f32,
+    pub opacity: f32,
+    /// Extra scale at full beat intensity (0 = static).
+    pub beat_scale: f32,
+}
+
+impl TextStyle {
+    pub fn new(enabled: bool, anchor: TextAnchor, size: f32, beat_scale: f32) -> Self {
+        Self { enabled, anchor, size, opacity: 0.9, beat_scale }
+    }
+
+    fn clamp(&mut self) {
+        self.size = self.size.clamp(0.02, 0.25);
+        self.opacity = self.opacity.clamp(0.0, 1.0);
+        self.beat_scale = self.beat_scale.clamp(0.0, 0.5);
+    }
+}
+
+impl Default for TextStyle {
+    fn default() -> Self { Self::new(false, TextAnchor::Bottom, 0.05, 0.0) }
+}
+
+/// Text drawn above the visualization: a custom caption, a clock and the
+/// title of the playing track when a metadata source knows it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextOverlaySettings {
+    /// TrueType/OpenType font file; `None` uses the bundled DK Hand.
+    pub font: Option<PathBuf>,
+    pub color: [f32; 3],
+    /// Seconds a line takes to fade in or out.
+    pub fade_seconds: f32,
+    pub caption: String,
+    pub caption_style: TextStyle,
+    pub clock_style: TextStyle,
+    pub clock_seconds: bool,
+    pub track_style: TextStyle,
+}
+
+impl TextOverlaySettings {
+    pub fn new() -> Self {
+        Self {
+            font: None,
+            color: [1.0, 1.0, 1.0],
+            fade_seconds: 1.0,
+            caption: String::new(),
+            caption_style: TextStyle::new(false, TextAnchor::Bottom, 0.06, 0.1),
+            clock_style: TextStyle::new(false, TextAnchor::TopRight, 0.05, 0.0),
+            clock_seconds: false,
+            track_style: TextStyle::new(true, TextAnchor::BottomLeft, 0.045, 0.0),
+        }
+    }
+
+    fn clamp(&mut self) {
+        self.color = self.color.map(|c| c.clamp(0.0, 1.0));
+        self.fade_seconds = self.fade_seconds.clamp(0.0, 5.0);
+        self.caption_style.clamp();
+        self.clock_style.clamp();
+        self.track_style.clamp();
+    }
+}
+
+impl Default for TextOverlaySettings {
+    fn default() -> Self { Self::new() }
+}
+
+/// Audio attract mode plays while the input is silent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SyntheticSignal {
+    /// Pink noise swelling slowly.
+    Noise,
+    /// A chord progression pulsing on the beat.
+    Tones,
+    /// A built-in drum-and-bass loop at 120 BPM.
+    Loop,
+}
+
+impl SyntheticSignal {
+    pub const ALL: [Self; 3] = [Self::Noise, Self::Tones, Self::Loop];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Noise => "Noise",
+            Self::Tones => "Tones",
+            Self::Loop  => "Loop",
+        }
+    }
+}
+
+/// Screensaver-like attract mode after a stretch without sound or input:
+/// chosen visualizations driven by synthetic audio, with an optional caption.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttractSettings {
+    pub enabled: bool,
+    /// Seconds without sound or user input before attract mode starts.
+    pub after_seconds: f32,
+    pub signal: SyntheticSignal,
+    /// Visualizations attract mode cycles through; empty uses every enabled one.
+    pub shaders: HashSet<String>,
+    /// Seconds each visualization is shown.
+    pub switch_seconds: f32,
+    /// Caption text; `{name}` becomes the visualization name.
+    pub caption: String,
+    pub caption_style: TextStyle,
+}
+
+impl AttractSettings {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            after_seconds: 120.0,
+            signal: SyntheticSignal::Loop,
+            shaders: HashSet::new(),
+            switch_seconds: 20.0,
+            caption: "Play some music".to_string(),
+            caption_style: TextStyle::new(true, TextAnchor::Center, 0.07, 0.1),
+        }
+    }
+
+    fn clamp(&mut self) {
+        self.after_seconds = self.after_seconds.clamp(10.0, 3600.0);
+        self.switch_seconds = self.switch_seconds.clamp(5.0, 120.0);
+        self.caption_style.clamp();
+    }
+}
+
+impl Default for AttractSettings {
+    fn default() -> Self { Self::new() }
+}
+
+/// Now-playing metadata from the desktop media player (MPRIS on Linux).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NowPlayingSettings {
+    pub enabled: bool,
+    /// Move to the next visualization whenever the track changes.
+    pub switch_on_track_change: bool,
+}
+
+impl NowPlayingSettings {
+    pub fn new() -> Self {
+        Self { enabled: true, switch_on_track_change: false }
+    }
+}
+
+impl Default for NowPlayingSettings {
+    fn default() -> Self { Self::new() }
+}
+
+/// What pointer input does besides reaching the shader uniforms.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InteractionSettings {
+    /// A left click throws particles out of the pointer position.
+    pub click_particles: bool,
+    /// A left drag orbits the camera of mesh visualizations.
+    pub drag_to_orbit: bool,
+}
+
+impl InteractionSettings {
+    pub fn new() -> Self {
+        Self { click_particles: false, drag_to_orbit: true }
+    }
+}
+
+impl Default for InteractionSettings {
+    fn default() -> Self { Self::new() }
+}
+
+/// How auto-switch picks the next visualization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwitchOrder {
+    /// The enabled visualizations one after another.
+    Sequential,
+    /// A random pick weighted by rating that skips recently shown ones.
+    Shuffle,
+}
+
+impl SwitchOrder {
+    pub const ALL: [Self; 2] = [Self::Sequential, Self::Shuffle];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Sequential => "In order",
+            Self::Shuffle    => "Weighted shuffle",
+        }
+    }
+}
+
+/// Order of auto-switch and the categories it draws from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoSwitchSettings {
+    pub order: SwitchOrder,
+    /// Categories auto-switch stays within; empty allows every category.
+    pub categories: HashSet<ShaderCategory>,
+    /// How many of the last shown visualizations a shuffle leaves out.
+    pub avoid_recent: usize,
+}
+
+impl AutoSwitchSettings {
+    pub fn new() -> Self {
+        Self { order: SwitchOrder::Sequential, categories: HashSet::new(), avoid_recent: 3 }
+    }
+
+    /// Whether auto-switch may show a visualization of `category`.
+    pub fn allows(&self, category: ShaderCategory) -> bool {
+        self.categories.is_empty() || self.categories.contains(&category)
+    }
+
+    fn clamp(&mut self) {
+        self.avoid_recent = self.avoid_recent.min(MAX_AVOID_RECENT);
+    }
+}
+
+impl Default for AutoSwitchSettings {
+    fn default() -> Self { Self::new() }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AppSettings {
     pub show_settings: bool,
     /// Window opacity used by the transparency slider (0.1 = nearly transparent, 1.0 = opaque).
     pub transparency: f32,
     pub auto_switch_modes: bool,
     pub mode_switch_seconds: f32,
+    /// Auto-switch order and category filter.
+    #[serde(default)]
+    pub auto_switch: AutoSwitchSettings,
     pub smoothing_factor: f32,
     pub gain: f32,
     pub color_scheme: ColorScheme,
     pub bass_boost: f32,
     /// Names of visualization plugins that the user has disabled.
     pub disabled_plugins: HashSet<String>,
+    /// Names of starred visualization plugins.
+    #[serde(default)]
+    pub favorites: HashSet<String>,
+    /// Star rating (1–5) of each rated visualization plugin.
+    #[serde(default)]
+    pub ratings: HashMap<String, u8>,
     /// Beat detection sensitivity level.
     pub beat_sensitivity: BeatSensitivity,
     /// Selected audio input device name.
     pub selected_device: Option<String>,
+    /// Audio host name (e.g. "ALSA", "JACK"); `None` uses the platform default.
+    #[serde(default)]
+    pub audio_host: Option<String>,
+    /// Address network audio is received on (`udp://…` or `tcp://…`),
+    /// listed as a source when set.
+    #[serde(default)]
+    pub network_input: Option<String>,
+    /// Post-processing effect chain.
+    #[serde(default)]
+    pub post_fx: PostFxSettings,
+    /// Particle overlay.
+    #[serde(default)]
+    pub particles: ParticleSettings,
+    /// Performance HUD and quality policy.
+    #[serde(default)]
+    pub performance: PerformanceSettings,
+    /// Frame-rate cap, present mode and power saving.
+    #[serde(default)]
+    pub power: PowerSettings,
+    /// Image assigned to each image-input shader, by shader name.
+    #[serde(default)]
+    pub shader_images: HashMap<String, PathBuf>,
+    /// Caption, clock and track title overlay.
+    #[serde(default)]
+    pub text: TextOverlaySettings,
+    /// Media player metadata.
+    #[serde(default)]
+    pub now_playing: NowPlayingSettings,
+    /// Mouse and touch interaction.
+    #[serde(default)]
+    pub interaction: InteractionSettings,
+    /// Attract mode during silence.
+    #[serde(default)]
+    pub attract: AttractSettings,
 }
 
 impl AppSettings {
@@ -69,13 +718,27 @@ impl AppSettings {
             transparency: crate::config::constants::DEFAULT_TRANSPARENCY as f32 / 255.0,
             auto_switch_modes: false,
             mode_switch_seconds: 30.0,
+            auto_switch: AutoSwitchSettings::new(),
             smoothing_factor: 0.1,
             gain: 1.5,
             color_scheme: ColorScheme::Classic,
             bass_boost: 1.0,
             disabled_plugins: HashSet::new(),
+            favorites: HashSet::new(),
+            ratings: HashMap::new(),
             beat_sensitivity: BeatSensitivity::Medium,
             selected_device: None,
+            audio_host: None,
+            network_input: None,
+            post_fx: PostFxSettings::new(),
+            particles: ParticleSettings::new(),
+            performance: PerformanceSettings::new(),
+            power: PowerSettings::new(),
+            shader_images: HashMap::new(),
+            text: TextOverlaySettings::new(),
+            now_playing: NowPlayingSettings::new(),
+            interaction: InteractionSettings::new(),
+            attract: AttractSettings::new(),
         }
     }
 
@@ -107,6 +770,14 @@ impl AppSettings {
                         s.smoothing_factor = s.smoothing_factor.clamp(0.01, 0.3);
                         s.bass_boost = s.bass_boost.clamp(0.0, 2.0);
                         s.mode_switch_seconds = s.mode_switch_seconds.clamp(5.0, 120.0);
+                        s.post_fx.clamp();
+                        s.particles.clamp();
+                        s.performance.clamp();
+                        s.power.clamp();
+                        s.text.clamp();
+                        s.auto_switch.clamp();
+                        s.attract.clamp();
+                        s.ratings.retain(|_, stars| (1..=MAX_RATING).contains(stars));
                         return s;
                     }
                     Err(e) => eprintln!("Warning: failed to parse settings.json: {e}. Using defaults."),
@@ -0,0 +1,79 @@
+//! Shared orbit camera for mesh plugins
+
+use crate::common::types::CameraUniforms;
+use crate::config::constants::{CAMERA_BASS_SPEED, CAMERA_DRAG_SENSITIVITY, CAMERA_ORBIT_SPEED};
+use glam::{Mat4, Vec3};
+use wgpu::util::DeviceExt;
+
+/// Camera circling the origin.
+///
+/// It drifts on its own, faster with more bass, and dips closer on beats;
+/// dragging with the mouse adds to the orbit on top of that.
+pub struct OrbitCamera {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+    last_time: f32,
+    buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl OrbitCamera {
+    pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout) -> Self {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[CameraUniforms::default()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: buffer.as_entire_binding() }],
+            label: Some("camera_bind_group"),
+        });
+        Self { yaw: 0.0, pitch: 0.45, distance: 6.0, last_time: 0.0, buffer, bind_group }
+    }
+
+    /// Orbit by a mouse drag of `dx`, `dy` pixels.
+    pub fn drag(&mut self, dx: f32, dy: f32) {
+        self.yaw -= dx * CAMERA_DRAG_SENSITIVITY;
+        self.pitch = (self.pitch + dy * CAMERA_DRAG_SENSITIVITY).clamp(-1.4, 1.4);
+    }
+
+    /// Advance the automatic orbit to `time` and upload the matrices.
+    pub fn update(&mut self, queue: &wgpu::Queue, aspect: f32, time: f32, bass_energy: f32, beat_intensity: f32) {
+        let dt = (time - self.last_time).clamp(0.0, 0.1);
+        self.last_time = time;
+        self.yaw += dt * (CAMERA_ORBIT_SPEED + bass_energy * CAMERA_BASS_SPEED);
+
+        let distance = self.distance * (1.0 - beat_intensity * 0.08);
+        let eye = Vec3::new(
+            distance * self.pitch.cos() * self.yaw.sin(),
+            distance * self.pitch.sin(),
+            distance * self.pitch.cos() * self.yaw.cos(),
+        );
+        let view = Mat4::look_at_rh(eye, Vec3::ZERO, Vec3::Y);
+        let proj = Mat4::perspective_rh(45f32.to_radians(), aspect.max(0.01), 0.1, 100.0);
+
+        let uniforms = CameraUniforms {
+            view_proj: (proj * view).to_cols_array_2d(),
+            eye: eye.extend(1.0).to_array(),
+        };
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniforms]));
+    }
+}
+
+pub fn create_camera_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }],
+        label: Some("camera_bind_group_layout"),
+    })
+}
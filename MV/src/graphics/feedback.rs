@@ -0,0 +1,123 @@
+//! Ping-pong targets that give feedback shaders their previous frame
+
+use crate::config::constants::HDR_FORMAT;
+use super::target::RenderTarget;
+
+/// Two HDR targets that swap roles every frame: the plugin renders into one
+/// while sampling the other through bind group 1.
+///
+/// Only plugins built on `common_feedback.wgsl` touch these, so shaders that
+/// don't opt in pay for neither the extra pass nor the copy.
+pub struct FeedbackTargets {
+    targets: [RenderTarget; 2],
+    /// `bind_groups[i]` samples `targets[i]`.
+    bind_groups: [wgpu::BindGroup; 2],
+    sampler: wgpu::Sampler,
+    /// Index of the target written most recently.
+    current: usize,
+    /// Plugin whose output is in the targets; history is cleared when it changes.
+    owner: Option<usize>,
+}
+
+impl FeedbackTargets {
+    pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, width: u32, height: u32) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Feedback Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let targets = create_targets(device, width, height);
+        let bind_groups = create_bind_groups(device, layout, &targets, &sampler);
+        Self { targets, bind_groups, sampler, current: 0, owner: None }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, layout: &wgpu::BindGroupLayout, width: u32, height: u32) {
+        self.targets = create_targets(device, width, height);
+        self.bind_groups = create_bind_groups(device, layout, &self.targets, &self.sampler);
+        self.owner = None;
+    }
+
+    /// Swap roles for a new frame of `plugin_index`.
+    ///
+    /// Returns the target to render into and the bind group that samples the
+    /// previous frame. When the plugin differs from last time, the stale
+    /// history is cleared first so one shader never sees another's output.
+    pub fn advance(&mut self, encoder: &mut wgpu::CommandEncoder, plugin_index: usize) -> (&RenderTarget, &wgpu::BindGroup) {
+        let prev = self.current;
+        if self.owner != Some(plugin_index) {
+            clear(encoder, &self.targets[prev].view);
+            self.owner = Some(plugin_index);
+        }
+        self.current = 1 - prev;
+        (&self.targets[self.current], &self.bind_groups[prev])
+    }
+}
+
+pub fn create_feedback_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+        label: Some("feedback_bind_group_layout"),
+    })
+}
+
+fn create_targets(device: &wgpu::Device, width: u32, height: u32) -> [RenderTarget; 2] {
+    [
+        RenderTarget::new(device, "Feedback Target A", width, height, HDR_FORMAT),
+        RenderTarget::new(device, "Feedback Target B", width, height, HDR_FORMAT),
+    ]
+}
+
+fn create_bind_groups(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    targets: &[RenderTarget; 2],
+    sampler: &wgpu::Sampler,
+) -> [wgpu::BindGroup; 2] {
+    targets.each_ref().map(|target| {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&target.view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(sampler) },
+            ],
+            label: Some("feedback_bind_group"),
+        })
+    })
+}
+
+fn clear(encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Feedback Clear Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+}
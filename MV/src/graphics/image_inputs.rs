@@ -0,0 +1,160 @@
+//! User images sampled by image-input shaders (logos, album art)
+
+use crate::common::error::{AppError, AppResult};
+use crate::common::types::ImageUniforms;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use wgpu::util::DeviceExt;
+
+/// Bind group 1 for image-input plugins: one mipmapped image per shader,
+/// the MV logo for shaders that have none assigned.
+pub struct ImageInputs {
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    default: wgpu::BindGroup,
+    /// Assigned images by plugin name.
+    assigned: HashMap<String, (PathBuf, wgpu::BindGroup)>,
+}
+
+impl ImageInputs {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, layout: wgpu::BindGroupLayout) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Image Input Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let logo = image::load_from_memory(include_bytes!("../../assets/logo.png"))
+            .map(|image| image.to_rgba8())
+            .unwrap_or_else(|_| image::RgbaImage::from_pixel(1, 1, image::Rgba([255; 4])));
+        let default = create_bind_group(device, queue, &layout, &sampler, "MV logo", logo);
+        Self { layout, sampler, default, assigned: HashMap::new() }
+    }
+
+    pub fn bind_group(&self, plugin: &str) -> &wgpu::BindGroup {
+        self.assigned.get(plugin).map_or(&self.default, |(_, bind_group)| bind_group)
+    }
+
+    /// Path of the image assigned to `plugin`, if any.
+    pub fn path(&self, plugin: &str) -> Option<&Path> {
+        self.assigned.get(plugin).map(|(path, _)| path.as_path())
+    }
+
+    /// Load `path` for `plugin`, or go back to the default image for `None`.
+    pub fn assign(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, plugin: &str, path: Option<&Path>) -> AppResult<()> {
+        let Some(path) = path else {
+            self.assigned.remove(plugin);
+            return Ok(());
+        };
+        let image = image::open(path)
+            .map_err(|e| AppError::Config(format!("Cannot load image {}: {}", path.display(), e)))?;
+        // Keep within the texture size limit.
+        let max = device.limits().max_texture_dimension_2d;
+        let image = if image.width() > max || image.height() > max {
+            image.resize(max, max, image::imageops::FilterType::Triangle)
+        } else {
+            image
+        };
+        let label = path.display().to_string();
+        let bind_group = create_bind_group(device, queue, &self.layout, &self.sampler, &label, image.to_rgba8());
+        self.assigned.insert(plugin.to_string(), (path.to_path_buf(), bind_group));
+        Ok(())
+    }
+}
+
+/// Upload `image` with a full mip chain, built on the CPU, and bind it.
+fn create_bind_group(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    layout: &wgpu::BindGroupLayout,
+    sampler: &wgpu::Sampler,
+    label: &str,
+    image: image::RgbaImage,
+) -> wgpu::BindGroup {
+    let (width, height) = image.dimensions();
+    let mip_level_count = width.max(height).ilog2() + 1;
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+
+    let mut level_image = image;
+    for level in 0..mip_level_count {
+        if level > 0 {
+            let (w, h) = level_image.dimensions();
+            level_image = image::imageops::resize(&level_image, (w / 2).max(1), (h / 2).max(1), image::imageops::FilterType::Triangle);
+        }
+        let (w, h) = level_image.dimensions();
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: level,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            level_image.as_raw(),
+            wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(4 * w), rows_per_image: Some(h) },
+            wgpu::Extent3d { width: w, height: h, depth_or_array_layers: 1 },
+        );
+    }
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let uniforms = ImageUniforms { size: [width as f32, height as f32], padding: [0.0; 2] };
+    let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Image Input Uniform Buffer"),
+        contents: bytemuck::cast_slice(&[uniforms]),
+        usage: wgpu::BufferUsages::UNIFORM,
+    });
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
+            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(sampler) },
+            wgpu::BindGroupEntry { binding: 2, resource: uniform_buffer.as_entire_binding() },
+        ],
+        label: Some("image_input_bind_group"),
+    })
+}
+
+pub fn create_image_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+        label: Some("image_bind_group_layout"),
+    })
+}
@@ -4,6 +4,16 @@ pub mod init;
 pub mod resources;
 pub mod update;
 pub mod render;
+pub mod target;
+pub mod postprocess;
+pub mod feedback;
+pub mod camera;
+pub mod particles;
+pub mod multibuffer;
+pub mod shadertoy_inputs;
+pub mod image_inputs;
+pub mod text_overlay;
+pub mod timing;
 
 // Re-export the main GPU resources
 pub use resources::GpuResources;
@@ -0,0 +1,153 @@
+//! Persistent Buffer A/B/C textures for multi-buffer shaders
+
+use crate::config::constants::{HDR_FORMAT, MAX_SHADER_BUFFERS};
+use super::target::RenderTarget;
+
+/// Number of distinct read bind groups: one per combination of which half
+/// of each buffer's ping-pong pair is current.
+const READ_COMBINATIONS: usize = 1 << MAX_SHADER_BUFFERS;
+
+/// Ping-pong HDR targets for each shader buffer.
+///
+/// A buffer pass writes the stale half of its pair while bind group 1 samples
+/// the current half of every buffer, so passes later in the frame see this
+/// frame's output and the pass itself sees the previous frame's.
+pub struct MultiBufferTargets {
+    /// `targets[buffer][half]`
+    targets: [[RenderTarget; 2]; MAX_SHADER_BUFFERS],
+    /// Indexed by a bitmask of `current`: bit `i` set means buffer `i` reads half 1.
+    bind_groups: Vec<wgpu::BindGroup>,
+    sampler: wgpu::Sampler,
+    /// Current (most recently written) half of each buffer.
+    current: [usize; MAX_SHADER_BUFFERS],
+    /// Plugin whose state is in the buffers; they are cleared when it changes.
+    owner: Option<usize>,
+}
+
+impl MultiBufferTargets {
+    pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, width: u32, height: u32) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shader Buffer Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let targets = create_targets(device, width, height);
+        let bind_groups = create_bind_groups(device, layout, &targets, &sampler);
+        Self { targets, bind_groups, sampler, current: [0; MAX_SHADER_BUFFERS], owner: None }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, layout: &wgpu::BindGroupLayout, width: u32, height: u32) {
+        self.targets = create_targets(device, width, height);
+        self.bind_groups = create_bind_groups(device, layout, &self.targets, &self.sampler);
+        self.owner = None;
+    }
+
+    /// Start a frame of `plugin_index`, zeroing the buffers when another
+    /// plugin's state is still in them.
+    pub fn begin_frame(&mut self, encoder: &mut wgpu::CommandEncoder, plugin_index: usize) {
+        if self.owner != Some(plugin_index) {
+            for (pair, &half) in self.targets.iter().zip(&self.current) {
+                clear(encoder, &pair[half].view);
+            }
+            self.owner = Some(plugin_index);
+        }
+    }
+
+    /// Flip `buffer` for its pass this frame.
+    ///
+    /// Returns the target to render into and the bind group that samples the
+    /// current contents of every buffer.
+    pub fn advance(&mut self, buffer: usize) -> (&RenderTarget, &wgpu::BindGroup) {
+        let read = self.read_index();
+        self.current[buffer] = 1 - self.current[buffer];
+        (&self.targets[buffer][self.current[buffer]], &self.bind_groups[read])
+    }
+
+    /// Bind group sampling the newest contents of every buffer.
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_groups[self.read_index()]
+    }
+
+    fn read_index(&self) -> usize {
+        self.current.iter().enumerate().map(|(i, &half)| half << i).sum()
+    }
+}
+
+pub fn create_multibuffer_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    };
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            texture_entry(0),
+            texture_entry(1),
+            texture_entry(2),
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+        label: Some("multibuffer_bind_group_layout"),
+    })
+}
+
+fn create_targets(device: &wgpu::Device, width: u32, height: u32) -> [[RenderTarget; 2]; MAX_SHADER_BUFFERS] {
+    ["A", "B", "C"].map(|name| {
+        [
+            RenderTarget::new(device, &format!("Shader Buffer {name} (0)"), width, height, HDR_FORMAT),
+            RenderTarget::new(device, &format!("Shader Buffer {name} (1)"), width, height, HDR_FORMAT),
+        ]
+    })
+}
+
+fn create_bind_groups(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    targets: &[[RenderTarget; 2]; MAX_SHADER_BUFFERS],
+    sampler: &wgpu::Sampler,
+) -> Vec<wgpu::BindGroup> {
+    (0..READ_COMBINATIONS)
+        .map(|mask| {
+            let view = |buffer: usize| &targets[buffer][(mask >> buffer) & 1].view;
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(view(0)) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(view(1)) },
+                    wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(view(2)) },
+                    wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::Sampler(sampler) },
+                ],
+                label: Some("multibuffer_bind_group"),
+            })
+        })
+        .collect()
+}
+
+fn clear(encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Shader Buffer Clear Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+}
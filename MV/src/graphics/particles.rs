@@ -0,0 +1,306 @@
+//! Particle overlay: GPU simulation and instanced draw
+
+use crate::common::types::{Particle, ParticleParams};
+use crate::config::constants::{CLICK_PARTICLE_FRACTION, COMPUTE_WORKGROUP_SIZE, DEFAULT_COLOR, DEFAULT_PARTICLE_COUNT};
+use crate::config::settings::{ParticleBlend, ParticleColor, ParticleEmitter, ParticleStyle};
+use super::init::BufferManager;
+use std::mem;
+use wgpu::util::DeviceExt;
+
+/// Particles simulated by `compute_particles.wgsl` and drawn as instanced quads.
+///
+/// The particle buffer is sized for the current count and reallocated when
+/// the style in effect asks for a different one.
+pub struct ParticleSystem {
+    particle_buffer: wgpu::Buffer,
+    quad_buffer: wgpu::Buffer,
+    params_buffer: wgpu::Buffer,
+    compute_layout: wgpu::BindGroupLayout,
+    compute_bind_group: wgpu::BindGroup,
+    compute_pipeline: wgpu::ComputePipeline,
+    alpha_pipeline: wgpu::RenderPipeline,
+    additive_pipeline: wgpu::RenderPipeline,
+    count: u32,
+    blend: ParticleBlend,
+    /// Whether the overlay is simulated and drawn this frame.
+    pub visible: bool,
+    last_time: f32,
+    last_beat: f32,
+    /// Click position waiting for the next update, in clip space.
+    click: Option<[f32; 2]>,
+}
+
+impl ParticleSystem {
+    pub fn new(
+        device: &wgpu::Device,
+        buffers: &BufferManager,
+        render_pipeline_layout: &wgpu::PipelineLayout,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let count = DEFAULT_PARTICLE_COUNT;
+        let compute_layout = create_compute_layout(device);
+        let particle_buffer = create_particle_buffer(device, count);
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Particle Params Buffer"),
+            contents: bytemuck::cast_slice(&[ParticleParams::default()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let compute_bind_group = create_compute_bind_group(device, &compute_layout, &particle_buffer, buffers, &params_buffer);
+
+        let quad_data: [[f32; 2]; 4] = [
+            [-0.01, -0.01], [0.01, -0.01], [0.01, 0.01], [-0.01, 0.01],
+        ];
+        let quad_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Quad Buffer"),
+            contents: bytemuck::cast_slice(&quad_data),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let compute_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Compute Shader"),
+            source: wgpu::ShaderSource::Wgsl(concat!(
+                include_str!("../../shaders/uniforms.wgsl"),
+                include_str!("../../shaders/compute_particles.wgsl"),
+            ).into()),
+        });
+        let compute_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Compute Pipeline Layout"),
+            bind_group_layouts: &[&compute_layout],
+            push_constant_ranges: &[],
+        });
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Compute Pipeline"),
+            layout: Some(&compute_pipeline_layout),
+            module: &compute_shader,
+            entry_point: "main",
+        });
+
+        let additive = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::OVER,
+        };
+
+        Self {
+            particle_buffer,
+            quad_buffer,
+            params_buffer,
+            compute_layout,
+            compute_bind_group,
+            compute_pipeline,
+            alpha_pipeline: create_render_pipeline(device, render_pipeline_layout, format, wgpu::BlendState::ALPHA_BLENDING),
+            additive_pipeline: create_render_pipeline(device, render_pipeline_layout, format, additive),
+            count,
+            blend: ParticleBlend::Alpha,
+            visible: true,
+            last_time: 0.0,
+            last_beat: 0.0,
+            click: None,
+        }
+    }
+
+    /// Apply `style` and upload this frame's simulation parameters; `None`
+    /// hides the overlay.
+    ///
+    /// A burst fires on the frame the beat intensity rises, i.e. when a new
+    /// beat is detected rather than while the previous one decays.
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        buffers: &BufferManager,
+        style: Option<&ParticleStyle>,
+        time: f32,
+        beat_intensity: f32,
+    ) {
+        self.visible = style.is_some();
+        let click = self.click.take();
+        let Some(style) = style else { return };
+
+        if style.count != self.count {
+            self.particle_buffer = create_particle_buffer(device, style.count);
+            self.compute_bind_group = create_compute_bind_group(device, &self.compute_layout, &self.particle_buffer, buffers, &self.params_buffer);
+            self.count = style.count;
+        }
+        self.blend = style.blend;
+
+        let dt = (time - self.last_time).clamp(0.0, 0.1);
+        let beat_started = beat_intensity > self.last_beat;
+        self.last_time = time;
+        self.last_beat = beat_intensity;
+
+        let params = ParticleParams {
+            count: self.count,
+            emitter: match style.emitter {
+                ParticleEmitter::Floor  => 0,
+                ParticleEmitter::Centre => 1,
+                ParticleEmitter::Ring   => 2,
+                ParticleEmitter::Screen => 3,
+            },
+            color_mode: match style.color {
+                ParticleColor::Scheme   => 0,
+                ParticleColor::Rainbow  => 1,
+                ParticleColor::Spectrum => 2,
+            },
+            burst: (style.beat_bursts && beat_started) as u32,
+            lifetime: style.lifetime,
+            gravity: style.gravity,
+            dt,
+            burst_fraction: style.burst_fraction,
+            emit_pos: click.unwrap_or_default(),
+            emit: click.is_some() as u32,
+            emit_fraction: CLICK_PARTICLE_FRACTION,
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&[params]));
+    }
+
+    /// Relaunch some particles from `at` (in clip space) on the next update.
+    pub fn emit_at(&mut self, at: [f32; 2]) {
+        self.click = Some(at);
+    }
+
+    /// Record the simulation step.
+    pub fn dispatch(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+        cpass.set_pipeline(&self.compute_pipeline);
+        cpass.set_bind_group(0, &self.compute_bind_group, &[]);
+        cpass.dispatch_workgroups(self.count.div_ceil(COMPUTE_WORKGROUP_SIZE), 1, 1);
+    }
+
+    /// Draw the particles into the current pass.
+    pub fn draw<'a>(&'a self, rpass: &mut wgpu::RenderPass<'a>, bind_group: &'a wgpu::BindGroup) {
+        let pipeline = match self.blend {
+            ParticleBlend::Alpha    => &self.alpha_pipeline,
+            ParticleBlend::Additive => &self.additive_pipeline,
+        };
+        rpass.set_pipeline(pipeline);
+        rpass.set_vertex_buffer(0, self.quad_buffer.slice(..));
+        rpass.set_vertex_buffer(1, self.particle_buffer.slice(..));
+        rpass.set_bind_group(0, bind_group, &[]);
+        rpass.draw(0..4, 0..self.count);
+    }
+}
+
+fn create_particle_buffer(device: &wgpu::Device, count: u32) -> wgpu::Buffer {
+    // Dead particles respawn on the first simulation step.
+    let initial_particles: Vec<Particle> = (0..count)
+        .map(|_| Particle {
+            position: [0.0, -1.0],
+            velocity: [0.0, 0.0],
+            lifetime: 0.0,
+            max_lifetime: 1.0,
+            padding: [0.0; 2],
+            color: DEFAULT_COLOR,
+        })
+        .collect();
+
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Particle Buffer"),
+        contents: bytemuck::cast_slice(&initial_particles),
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+    })
+}
+
+fn create_compute_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    let buffer_entry = |binding, ty| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer { ty, has_dynamic_offset: false, min_binding_size: None },
+        count: None,
+    };
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            buffer_entry(0, wgpu::BufferBindingType::Storage { read_only: false }),
+            buffer_entry(1, wgpu::BufferBindingType::Storage { read_only: true }),
+            buffer_entry(2, wgpu::BufferBindingType::Uniform),
+            buffer_entry(3, wgpu::BufferBindingType::Uniform),
+        ],
+        label: Some("compute_bind_group_layout"),
+    })
+}
+
+fn create_compute_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    particle_buffer: &wgpu::Buffer,
+    buffers: &BufferManager,
+    params_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: particle_buffer.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 1, resource: buffers.fft_buffer.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 2, resource: buffers.uniform_buffer.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 3, resource: params_buffer.as_entire_binding() },
+        ],
+        label: Some("particle_bind_group"),
+    })
+}
+
+fn create_render_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    format: wgpu::TextureFormat,
+    blend: wgpu::BlendState,
+) -> wgpu::RenderPipeline {
+    let particle_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Particle Shader"),
+        source: wgpu::ShaderSource::Wgsl(concat!(
+            include_str!("../../shaders/uniforms.wgsl"),
+            include_str!("../../shaders/particle_render.wgsl"),
+        ).into()),
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Particle Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: &particle_shader,
+            entry_point: "vs_main",
+            buffers: &[
+                wgpu::VertexBufferLayout {
+                    array_stride: 8,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &[wgpu::VertexAttribute {
+                        offset: 0,
+                        shader_location: 0,
+                        format: wgpu::VertexFormat::Float32x2,
+                    }],
+                },
+                wgpu::VertexBufferLayout {
+                    array_stride: mem::size_of::<Particle>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &[
+                        wgpu::VertexAttribute { offset: 0, shader_location: 1, format: wgpu::VertexFormat::Float32x2 },
+                        wgpu::VertexAttribute { offset: 32, shader_location: 2, format: wgpu::VertexFormat::Float32x4 },
+                    ],
+                },
+            ],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &particle_shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(blend),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleStrip,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState { count: 1, mask: !0, alpha_to_coverage_enabled: false },
+        multiview: None,
+    })
+}
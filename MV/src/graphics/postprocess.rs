@@ -0,0 +1,333 @@
+//! HDR post-processing chain (trails, bloom, chromatic aberration, vignette, grain)
+
+use crate::common::types::PostUniforms;
+use crate::config::constants::*;
+use crate::config::settings::PostFxSettings;
+use super::target::RenderTarget;
+use wgpu::util::DeviceExt;
+
+// NOTE: must match the FX_* constants in shaders/post/post_fx.wgsl
+pub const FX_BLOOM: u32 = 1 << 0;
+pub const FX_TRAILS: u32 = 1 << 1;
+pub const FX_CHROMATIC: u32 = 1 << 2;
+pub const FX_GRAIN: u32 = 1 << 3;
+pub const FX_VIGNETTE: u32 = 1 << 4;
+pub const FX_TRANSPARENT: u32 = 1 << 5;
+pub const FX_PREMULTIPLIED: u32 = 1 << 6;
+
+/// Alpha written by the composite for a see-through window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowAlpha {
+    pub opacity: f32,
+    /// The surface composites premultiplied rather than straight alpha.
+    pub premultiplied: bool,
+}
+
+/// Owns the HDR scene target and every pass that turns it into the final image.
+pub struct PostProcessor {
+    /// Plugins and particles render into this target instead of the swapchain.
+    pub scene: RenderTarget,
+    trails: [RenderTarget; 2],
+    bloom: [RenderTarget; 2],
+    /// Index into `trails` holding the most recent trail frame.
+    trail_index: usize,
+    /// False until a trail frame has been written since trails were enabled.
+    trails_primed: bool,
+    uniform_buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    trails_pipeline: wgpu::RenderPipeline,
+    bright_pipeline: wgpu::RenderPipeline,
+    blur_h_pipeline: wgpu::RenderPipeline,
+    blur_v_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+    bind_groups: PostBindGroups,
+    /// `None` writes opaque pixels.
+    pub window_alpha: Option<WindowAlpha>,
+}
+
+impl PostProcessor {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32, surface_format: wgpu::TextureFormat) -> Self {
+        let bind_group_layout = create_post_bind_group_layout(device);
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Post FX Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/post/post_fx.wgsl").into()),
+        });
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Post Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[PostUniforms::default()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let (scene, trails, bloom) = create_targets(device, width, height);
+        let bind_groups = create_bind_groups(device, &bind_group_layout, &uniform_buffer, &sampler, &scene, &trails, &bloom);
+
+        Self {
+            scene,
+            trails,
+            bloom,
+            trail_index: 0,
+            trails_primed: false,
+            uniform_buffer,
+            sampler,
+            trails_pipeline: create_fullscreen_pipeline(device, &pipeline_layout, &shader, "fs_trails", HDR_FORMAT),
+            bright_pipeline: create_fullscreen_pipeline(device, &pipeline_layout, &shader, "fs_bright", HDR_FORMAT),
+            blur_h_pipeline: create_fullscreen_pipeline(device, &pipeline_layout, &shader, "fs_blur_h", HDR_FORMAT),
+            blur_v_pipeline: create_fullscreen_pipeline(device, &pipeline_layout, &shader, "fs_blur_v", HDR_FORMAT),
+            composite_pipeline: create_fullscreen_pipeline(device, &pipeline_layout, &shader, "fs_composite", surface_format),
+            bind_group_layout,
+            bind_groups,
+            window_alpha: None,
+        }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        let (scene, trails, bloom) = create_targets(device, width, height);
+        self.scene = scene;
+        self.trails = trails;
+        self.bloom = bloom;
+        self.bind_groups = create_bind_groups(
+            device,
+            &self.bind_group_layout,
+            &self.uniform_buffer,
+            &self.sampler,
+            &self.scene,
+            &self.trails,
+            &self.bloom,
+        );
+        self.trails_primed = false;
+    }
+
+    /// Upload this frame's effect parameters, applying beat reactivity.
+    pub fn prepare(&mut self, queue: &wgpu::Queue, fx: &PostFxSettings, time: f32, beat_intensity: f32, bass_energy: f32) {
+        let mut flags = 0;
+        if fx.bloom_enabled { flags |= FX_BLOOM; }
+        if fx.trails_enabled { flags |= FX_TRAILS; }
+        if fx.chromatic_enabled { flags |= FX_CHROMATIC; }
+        if fx.grain_enabled { flags |= FX_GRAIN; }
+        if fx.vignette_enabled { flags |= FX_VIGNETTE; }
+        if let Some(alpha) = self.window_alpha {
+            flags |= FX_TRANSPARENT;
+            if alpha.premultiplied { flags |= FX_PREMULTIPLIED; }
+        }
+
+        if !fx.trails_enabled {
+            self.trails_primed = false;
+        }
+
+        let beat_boost = if fx.beat_reactive { beat_intensity } else { 0.0 };
+        let uniforms = PostUniforms {
+            time,
+            beat_intensity,
+            bass_energy,
+            flags,
+            bloom_threshold: fx.bloom_threshold,
+            bloom_intensity: fx.bloom_intensity * (1.0 + beat_boost),
+            // The first trail frame has no valid history to fade from.
+            trails_decay: if self.trails_primed { fx.trails_decay } else { 0.0 },
+            chromatic_amount: fx.chromatic_amount * (1.0 + 2.0 * beat_boost),
+            grain_amount: fx.grain_amount,
+            vignette_strength: fx.vignette_strength,
+            opacity: self.window_alpha.map_or(1.0, |a| a.opacity),
+            padding: 0.0,
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+    }
+
+    /// Encode the intermediate HDR passes and return the pipeline and bind
+    /// group for the final composite draw, which the caller issues into the
+    /// surface pass.
+    pub fn encode(&mut self, encoder: &mut wgpu::CommandEncoder, fx: &PostFxSettings) -> (&wgpu::RenderPipeline, &wgpu::BindGroup) {
+        let groups = &self.bind_groups;
+        let mut source = SOURCE_SCENE;
+
+        if fx.trails_enabled {
+            let prev = self.trail_index;
+            let next = 1 - prev;
+            run_pass(encoder, "Trails Pass", &self.trails_pipeline, &groups.trails[prev], &self.trails[next].view);
+            self.trail_index = next;
+            self.trails_primed = true;
+            source = SOURCE_TRAILS + next;
+        }
+
+        if fx.bloom_enabled {
+            run_pass(encoder, "Bloom Bright Pass", &self.bright_pipeline, &groups.bright[source], &self.bloom[0].view);
+            run_pass(encoder, "Bloom Blur H Pass", &self.blur_h_pipeline, &groups.blur_h, &self.bloom[1].view);
+            run_pass(encoder, "Bloom Blur V Pass", &self.blur_v_pipeline, &groups.blur_v, &self.bloom[0].view);
+        }
+
+        (&self.composite_pipeline, &groups.composite[source])
+    }
+}
+
+/// Index of the scene target in the per-source bind group arrays.
+const SOURCE_SCENE: usize = 0;
+/// Index of `trails[0]`; `trails[1]` follows it.
+const SOURCE_TRAILS: usize = 1;
+
+/// Every bind group the chain samples through, rebuilt whenever the targets are.
+struct PostBindGroups {
+    /// `trails[i]` fades the scene over history `trails[i]`.
+    trails: [wgpu::BindGroup; 2],
+    /// Bright pass reading the scene or a trail frame, by `SOURCE_*` index.
+    bright: [wgpu::BindGroup; 3],
+    blur_h: wgpu::BindGroup,
+    blur_v: wgpu::BindGroup,
+    /// Composite of each source with the blurred bloom, by `SOURCE_*` index.
+    composite: [wgpu::BindGroup; 3],
+}
+
+fn create_bind_groups(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    uniform_buffer: &wgpu::Buffer,
+    sampler: &wgpu::Sampler,
+    scene: &RenderTarget,
+    trails: &[RenderTarget; 2],
+    bloom: &[RenderTarget; 2],
+) -> PostBindGroups {
+    let bind_group = |src: &RenderTarget, aux: &RenderTarget| {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: uniform_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&src.view) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(&aux.view) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::Sampler(sampler) },
+            ],
+            label: Some("post_bind_group"),
+        })
+    };
+    let sources = [scene, &trails[0], &trails[1]];
+
+    PostBindGroups {
+        trails: trails.each_ref().map(|history| bind_group(scene, history)),
+        bright: sources.map(|src| bind_group(src, &bloom[1])),
+        blur_h: bind_group(&bloom[0], &bloom[0]),
+        blur_v: bind_group(&bloom[1], &bloom[1]),
+        composite: sources.map(|src| bind_group(src, &bloom[0])),
+    }
+}
+
+fn create_targets(device: &wgpu::Device, width: u32, height: u32) -> (RenderTarget, [RenderTarget; 2], [RenderTarget; 2]) {
+    let (bw, bh) = (width / BLOOM_DOWNSCALE, height / BLOOM_DOWNSCALE);
+    (
+        RenderTarget::new(device, "Scene Target", width, height, HDR_FORMAT),
+        [
+            RenderTarget::new(device, "Trail Target A", width, height, HDR_FORMAT),
+            RenderTarget::new(device, "Trail Target B", width, height, HDR_FORMAT),
+        ],
+        [
+            RenderTarget::new(device, "Bloom Target A", bw, bh, HDR_FORMAT),
+            RenderTarget::new(device, "Bloom Target B", bw, bh, HDR_FORMAT),
+        ],
+    )
+}
+
+fn run_pass(
+    encoder: &mut wgpu::CommandEncoder,
+    label: &str,
+    pipeline: &wgpu::RenderPipeline,
+    bind_group: &wgpu::BindGroup,
+    target: &wgpu::TextureView,
+) {
+    let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some(label),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    rpass.set_pipeline(pipeline);
+    rpass.set_bind_group(0, bind_group, &[]);
+    rpass.draw(0..3, 0..1);
+}
+
+fn create_post_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    };
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            texture_entry(1),
+            texture_entry(2),
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+        label: Some("post_bind_group_layout"),
+    })
+}
+
+fn create_fullscreen_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    entry_point: &str,
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(entry_point),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point,
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    })
+}
@@ -1,54 +1,158 @@
 //! GPU render functions
 
 use crate::common::error::AppResult;
-use crate::config::constants::*;
+use crate::config::settings::PostFxSettings;
+use crate::visualization::PluginBindings;
+use std::time::Instant;
 
+use super::timing::TimingMark;
 use super::GpuResources;
 
 impl GpuResources {
     pub fn render(
         &mut self,
         plugin_index: usize,
+        post_fx: &PostFxSettings,
         paint_jobs: &[egui::ClippedPrimitive],
         screen_desc: &egui_wgpu::ScreenDescriptor,
         textures_delta: &egui::TexturesDelta,
     ) -> AppResult<()> {
+        let acquire_start = Instant::now();
         let output = self.surface.get_current_texture()?;
+        self.surface_wait = acquire_start.elapsed();
         let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
         let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.poll(&self.device);
+            timer.mark(&mut encoder, TimingMark::FrameStart);
+        }
+
         // Update egui textures
         for (id, image_delta) in &textures_delta.set {
             self.egui_renderer.update_texture(&self.device, &self.queue, *id, image_delta);
         }
 
         // Compute particles
-        {
-            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
-            cpass.set_pipeline(&self.compute_pipeline);
-            cpass.set_bind_group(0, &self.particle_bind_group, &[]);
-            cpass.dispatch_workgroups(NUM_PARTICLES / COMPUTE_WORKGROUP_SIZE + 1, 1, 1);
+        if self.particles.visible {
+            self.particles.dispatch(&mut encoder);
+        }
+
+        // Compute plugins advance their own simulation before drawing it
+        if let Some(compute) = &self.plugins[plugin_index].compute {
+            compute.dispatch(&mut encoder, &self.bind_group);
         }
 
         // Update egui vertex/index buffers
         self.egui_renderer.update_buffers(&self.device, &self.queue, &mut encoder, paint_jobs, screen_desc);
 
         // Collect references to avoid borrow conflicts inside the render pass block
-        let plugin_pipeline = &self.plugins[plugin_index].render_pipeline;
+        let plugin = &self.plugins[plugin_index];
+        let plugin_pipeline = &plugin.render_pipeline;
         let bind_group = &self.bind_group;
-        let particle_render_pipeline = &self.particle_render_pipeline;
-        let quad_buffer = &self.buffers.quad_buffer;
-        let particle_buffer = &self.buffers.particle_buffer;
+        let particles = &self.particles;
+
+        // Feedback plugins draw into a ping-pong target first so that next
+        // frame can sample their raw output; the result is copied into the
+        // scene and particles are layered on top as usual. Mesh plugins
+        // draw into the scene in a pass of their own that has a depth buffer.
+        let scene_load = if plugin.bindings == PluginBindings::Feedback {
+            let (target, prev_frame) = self.feedback.advance(&mut encoder, plugin_index);
+            {
+                let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Feedback Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &target.view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                rpass.set_pipeline(plugin_pipeline);
+                rpass.set_bind_group(0, bind_group, &[]);
+                rpass.set_bind_group(1, prev_frame, &[]);
+                rpass.draw(0..3, 0..1);
+            }
+            encoder.copy_texture_to_texture(
+                target.texture.as_image_copy(),
+                self.post.scene.texture.as_image_copy(),
+                target.texture.size(),
+            );
+            wgpu::LoadOp::Load
+        } else if let Some(mesh) = &plugin.mesh {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mesh Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.post.scene.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            rpass.set_pipeline(plugin_pipeline);
+            rpass.set_bind_group(0, bind_group, &[]);
+            rpass.set_bind_group(1, &self.camera.bind_group, &[]);
+            rpass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            rpass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            rpass.draw_indexed(0..mesh.index_count, 0, 0..mesh.instances);
+            wgpu::LoadOp::Load
+        } else {
+            wgpu::LoadOp::Clear(wgpu::Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 })
+        };
 
-        // Render pass
+        // Multi-buffer plugins run their buffer passes in order; each one
+        // samples the newest contents of every buffer, including its own.
+        if plugin.bindings == PluginBindings::MultiBuffer {
+            self.multibuffer.begin_frame(&mut encoder, plugin_index);
+            for (buffer, pipeline) in plugin.buffer_pipelines.iter().enumerate() {
+                let (target, buffers) = self.multibuffer.advance(buffer);
+                let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Shader Buffer Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &target.view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                rpass.set_pipeline(pipeline);
+                rpass.set_bind_group(0, bind_group, &[]);
+                rpass.set_bind_group(1, buffers, &[]);
+                rpass.draw(0..3, 0..1);
+            }
+        }
+
+        // Scene pass (HDR offscreen target)
         {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
+                label: Some("Scene Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view: &self.post.scene.view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 }),
+                        load: scene_load,
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -57,23 +161,73 @@ impl GpuResources {
                 occlusion_query_set: None,
             });
 
-            // Visualization plugin
-            rpass.set_pipeline(plugin_pipeline);
-            rpass.set_bind_group(0, bind_group, &[]);
-            rpass.draw(0..3, 0..1);
+            // Visualization plugin (feedback and mesh plugins were drawn above)
+            if !matches!(plugin.bindings, PluginBindings::Feedback | PluginBindings::Mesh) {
+                rpass.set_pipeline(plugin_pipeline);
+                rpass.set_bind_group(0, bind_group, &[]);
+                match plugin.bindings {
+                    PluginBindings::Shadertoy   => rpass.set_bind_group(1, &self.shadertoy.bind_group, &[]),
+                    PluginBindings::MultiBuffer => rpass.set_bind_group(1, self.multibuffer.bind_group(), &[]),
+                    PluginBindings::Image       => rpass.set_bind_group(1, self.images.bind_group(&plugin.name), &[]),
+                    PluginBindings::Standard | PluginBindings::Feedback | PluginBindings::Compute | PluginBindings::Mesh => {}
+                }
+                match &plugin.compute {
+                    Some(compute) => {
+                        rpass.set_bind_group(1, &compute.render_bind_group, &[]);
+                        rpass.draw(0..compute.vertices, 0..compute.instances);
+                    }
+                    None => rpass.draw(0..3, 0..1),
+                }
+            }
 
             // Particles
-            rpass.set_pipeline(particle_render_pipeline);
-            rpass.set_vertex_buffer(0, quad_buffer.slice(..));
-            rpass.set_vertex_buffer(1, particle_buffer.slice(..));
-            rpass.set_bind_group(0, bind_group, &[]);
-            rpass.draw(0..4, 0..NUM_PARTICLES);
+            if particles.visible {
+                particles.draw(&mut rpass, bind_group);
+            }
+        }
+
+        if let Some(timer) = &self.gpu_timer {
+            timer.mark(&mut encoder, TimingMark::SceneEnd);
+        }
+
+        // Post-processing chain
+        self.post.prepare(&self.queue, post_fx, self.frame_time, self.analyzer.beat_intensity(), self.analyzer.bass_energy());
+        let (composite_pipeline, composite_bind_group) = self.post.encode(&mut encoder, post_fx);
+
+        // Surface pass: composite + text overlay + egui
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Surface Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            rpass.set_pipeline(composite_pipeline);
+            rpass.set_bind_group(0, composite_bind_group, &[]);
+            rpass.draw(0..3, 0..1);
 
-            // egui overlay
+            self.text.draw(&mut rpass);
             self.egui_renderer.render(&mut rpass, paint_jobs, screen_desc);
         }
 
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.mark(&mut encoder, TimingMark::FrameEnd);
+            timer.resolve(&mut encoder);
+        }
+
         self.queue.submit(std::iter::once(encoder.finish()));
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.after_submit();
+        }
         output.present();
 
         // Free egui textures
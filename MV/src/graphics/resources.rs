@@ -1,9 +1,22 @@
 //! GPU resources and state
 
+use crate::analysis::Analyzer;
 use crate::common::error::{AppError, AppResult};
 use super::init::BufferManager;
-use crate::visualization::Plugin;
-use std::time::Instant;
+use super::postprocess::{PostProcessor, WindowAlpha};
+use super::camera::{create_camera_bind_group_layout, OrbitCamera};
+use super::feedback::{create_feedback_bind_group_layout, FeedbackTargets};
+use super::particles::ParticleSystem;
+use super::timing::GpuTimer;
+use super::multibuffer::{create_multibuffer_bind_group_layout, MultiBufferTargets};
+use super::shadertoy_inputs::{create_shadertoy_bind_group_layout, ShadertoyInputs};
+use super::image_inputs::{create_image_bind_group_layout, ImageInputs};
+use super::text_overlay::TextOverlay;
+use crate::config::constants::{HDR_FORMAT, SAMPLE_SIZE};
+use crate::config::settings::PresentMode;
+use crate::visualization::loader::{load_fragment, Preamble};
+use crate::visualization::{Plugin, PluginLayouts};
+use std::time::{Duration, Instant};
 
 /// GPU resources and state
 pub struct GpuResources {
@@ -12,26 +25,44 @@ pub struct GpuResources {
     pub queue: wgpu::Queue,
     pub config: wgpu::SurfaceConfiguration,
     pub buffers: BufferManager,
-    pub particle_bind_group: wgpu::BindGroup,
-    pub compute_pipeline: wgpu::ComputePipeline,
-    pub particle_render_pipeline: wgpu::RenderPipeline,
+    pub particles: ParticleSystem,
     pub bind_group: wgpu::BindGroup,
     pub plugins: Vec<Plugin>,
+    /// Kept to rebuild plugins edited at runtime.
+    pub(crate) plugin_layouts: PluginLayouts,
+    pub post: PostProcessor,
+    pub feedback: FeedbackTargets,
+    pub(crate) feedback_bind_group_layout: wgpu::BindGroupLayout,
+    pub shadertoy: ShadertoyInputs,
+    /// Images sampled by image-input plugins.
+    pub images: ImageInputs,
+    /// Caption, clock and track title drawn over the composite.
+    pub text: TextOverlay,
+    pub multibuffer: MultiBufferTargets,
+    pub(crate) multibuffer_bind_group_layout: wgpu::BindGroupLayout,
+    pub camera: OrbitCamera,
+    /// Depth attachment for mesh plugins, sized like the scene target.
+    pub(crate) depth_view: wgpu::TextureView,
+    /// Fraction of the surface resolution that scene-sized targets use.
+    pub render_scale: f32,
+    /// Time the last frame spent waiting for a swapchain image.
+    pub surface_wait: Duration,
+    /// GPU frame timing; `None` when the adapter lacks timestamp queries.
+    pub gpu_timer: Option<GpuTimer>,
     pub egui_renderer: egui_wgpu::Renderer,
     pub(crate) start_time: Instant,
-    pub(crate) smoothed_fft: Vec<f32>,
-    pub bass_energy: f32,
-    pub(crate) waveform_history: Vec<f32>,
-    pub(crate) history_frame_counter: u32,
-    /// Rolling energy history for beat detection (ring-buffer, newest at index 0).
-    pub(crate) energy_history: Vec<f32>,
-    /// Instantaneous beat intensity that peaks on beat and decays each frame.
-    pub beat_intensity: f32,
-    /// Pre-allocated complex buffer reused every FFT call to avoid per-frame heap allocation.
-    pub(crate) fft_complex_buf: Vec<rustfft::num_complex::Complex<f32>>,
-    /// Cached FFT planner; plans are memoized internally so reusing the planner
-    /// avoids redundant algorithm selection on every call.
-    pub(crate) fft_planner: rustfft::FftPlanner<f32>,
+    /// Shader clock of the current frame, as passed to `update`.
+    pub(crate) frame_time: f32,
+    /// Hash the uploads in `update` into `upload_digest` (session record and replay).
+    pub digest_uploads: bool,
+    pub upload_digest: u64,
+    pub analyzer: Analyzer,
+    /// Gain-scaled waveform for Shadertoy shaders, reused every frame.
+    pub(crate) waveform: Vec<f32>,
+    /// Composite alpha modes the surface supports.
+    alpha_modes: Vec<wgpu::CompositeAlphaMode>,
+    /// Present modes the surface supports.
+    present_modes: Vec<wgpu::PresentMode>,
 }
 
 impl GpuResources {
@@ -53,7 +84,8 @@ impl GpuResources {
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    required_features: wgpu::Features::empty(),
+                    // Timestamps feed the performance HUD when the adapter has them.
+                    required_features: adapter.features() & wgpu::Features::TIMESTAMP_QUERY,
                     required_limits: wgpu::Limits::default(),
                     label: None,
                 },
@@ -75,7 +107,7 @@ impl GpuResources {
             width: size.width,
             height: size.height,
             present_mode: wgpu::PresentMode::Fifo,
-            alpha_mode: surface_caps.alpha_modes[0],
+            alpha_mode: opaque_alpha_mode(&surface_caps.alpha_modes),
             view_formats: vec![],
             desired_maximum_frame_latency: 2,
         };
@@ -91,10 +123,67 @@ impl GpuResources {
             push_constant_ranges: &[],
         });
 
-        let plugins = crate::visualization::load_plugins(&device, &render_pipeline_layout, config.format)?;
+        let feedback_bind_group_layout = create_feedback_bind_group_layout(&device);
+        let feedback_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Feedback Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, &feedback_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shadertoy_bind_group_layout = create_shadertoy_bind_group_layout(&device);
+        let shadertoy_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadertoy Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, &shadertoy_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let multibuffer_bind_group_layout = create_multibuffer_bind_group_layout(&device);
+        let multibuffer_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Multi-buffer Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, &multibuffer_bind_group_layout],
+            push_constant_ranges: &[],
+        });
 
-        let (particle_bind_group, compute_pipeline, particle_render_pipeline) =
-            super::init::create_particle_system(&device, &buffers.fft_buffer, &buffers.uniform_buffer, &buffers.particle_buffer, &buffers.quad_buffer, &render_pipeline_layout, config.format)?;
+        let image_bind_group_layout = create_image_bind_group_layout(&device);
+        let image_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Image Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, &image_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let camera_bind_group_layout = create_camera_bind_group_layout(&device);
+        let mesh_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Mesh Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, &camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let plugin_layouts = PluginLayouts {
+            shared: bind_group_layout,
+            standard: render_pipeline_layout,
+            feedback: feedback_pipeline_layout,
+            shadertoy: shadertoy_pipeline_layout,
+            multibuffer: multibuffer_pipeline_layout,
+            mesh: mesh_pipeline_layout,
+            image: image_pipeline_layout,
+        };
+
+        // Plugins and particles draw into the HDR scene target; only the
+        // post-processing composite and egui touch the surface format.
+        let plugins = crate::visualization::load_plugins(&device, &plugin_layouts, HDR_FORMAT)?;
+
+        let particles = ParticleSystem::new(&device, &buffers, &plugin_layouts.standard, HDR_FORMAT);
+
+        let post = PostProcessor::new(&device, size.width, size.height, surface_format);
+        let feedback = FeedbackTargets::new(&device, &feedback_bind_group_layout, size.width, size.height);
+        let shadertoy = ShadertoyInputs::new(&device, &shadertoy_bind_group_layout);
+        let images = ImageInputs::new(&device, &queue, image_bind_group_layout);
+        let text = TextOverlay::new(&device, surface_format);
+        let multibuffer = MultiBufferTargets::new(&device, &multibuffer_bind_group_layout, size.width, size.height);
+        let camera = OrbitCamera::new(&device, &camera_bind_group_layout);
+        let depth_view = super::target::create_depth_view(&device, size.width, size.height);
+
+        let gpu_timer = GpuTimer::new(&device, &queue);
 
         let egui_renderer = egui_wgpu::Renderer::new(&device, surface_format, None, 1);
 
@@ -104,29 +193,126 @@ impl GpuResources {
             queue,
             config,
             buffers,
-            particle_bind_group,
-            compute_pipeline,
-            particle_render_pipeline,
+            particles,
             bind_group,
             plugins,
+            plugin_layouts,
+            post,
+            feedback,
+            feedback_bind_group_layout,
+            shadertoy,
+            images,
+            text,
+            multibuffer,
+            multibuffer_bind_group_layout,
+            camera,
+            depth_view,
+            render_scale: 1.0,
+            surface_wait: Duration::ZERO,
+            gpu_timer,
             egui_renderer,
             start_time: Instant::now(),
-            smoothed_fft: vec![0.0f32; crate::config::constants::SAMPLE_SIZE / 2],
-            bass_energy: 0.0,
-            waveform_history: vec![0.0f32; crate::config::constants::WAVEFORM_HISTORY_SIZE * crate::config::constants::SAMPLE_SIZE],
-            history_frame_counter: 0,
-            energy_history: vec![0.0f32; crate::config::constants::BEAT_HISTORY_SIZE],
-            beat_intensity: 0.0,
-            fft_complex_buf: vec![rustfft::num_complex::Complex::new(0.0, 0.0); crate::config::constants::SAMPLE_SIZE],
-            fft_planner: rustfft::FftPlanner::new(),
+            frame_time: 0.0,
+            digest_uploads: false,
+            upload_digest: 0,
+            analyzer: Analyzer::new(),
+            waveform: Vec::with_capacity(SAMPLE_SIZE),
+            alpha_modes: surface_caps.alpha_modes,
+            present_modes: surface_caps.present_modes,
         })
     }
 
+    /// Composite with `opacity` and per-pixel alpha, or opaquely for `None`.
+    /// Returns false when the surface cannot blend with the desktop, in which
+    /// case the window stays opaque.
+    pub fn set_window_alpha(&mut self, opacity: Option<f32>) -> bool {
+        let blended = [wgpu::CompositeAlphaMode::PreMultiplied, wgpu::CompositeAlphaMode::PostMultiplied]
+            .into_iter()
+            .find(|mode| self.alpha_modes.contains(mode));
+        let (alpha_mode, window_alpha) = match (opacity, blended) {
+            (Some(opacity), Some(mode)) => (mode, Some(WindowAlpha {
+                opacity,
+                premultiplied: mode == wgpu::CompositeAlphaMode::PreMultiplied,
+            })),
+            _ => (opaque_alpha_mode(&self.alpha_modes), None),
+        };
+        if alpha_mode != self.config.alpha_mode {
+            self.config.alpha_mode = alpha_mode;
+            self.surface.configure(&self.device, &self.config);
+        }
+        self.post.window_alpha = window_alpha;
+        opacity.is_none() || blended.is_some()
+    }
+
+    /// Whether the surface can present with `mode`.
+    pub fn supports_present_mode(&self, mode: PresentMode) -> bool {
+        self.present_modes.contains(&wgpu_present_mode(mode))
+    }
+
+    /// Present with `mode`, or with VSync when the surface lacks it.
+    pub fn set_present_mode(&mut self, mode: PresentMode) {
+        let mode = if self.supports_present_mode(mode) { wgpu_present_mode(mode) } else { wgpu::PresentMode::Fifo };
+        if mode != self.config.present_mode {
+            self.config.present_mode = mode;
+            self.surface.configure(&self.device, &self.config);
+        }
+    }
+
+    /// Rebuild plugin `index` from the WGSL fragment shader `body`. The old
+    /// plugin stays in place when the new source does not compile.
+    pub fn reload_fragment(&mut self, index: usize, preamble: Preamble, body: &str) -> AppResult<()> {
+        let Some(old) = self.plugins.get(index) else { return Ok(()) };
+        let plugin = load_fragment(&self.device, &self.plugin_layouts, &old.name, preamble, body, HDR_FORMAT)?;
+        self.plugins[index] = plugin;
+        Ok(())
+    }
+
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
+            self.resize_scene_targets();
+        }
+    }
+
+    /// Size of the visualization render: the surface size times `render_scale`.
+    pub fn scene_size(&self) -> (u32, u32) {
+        let scale = |v: u32| ((v as f32 * self.render_scale).round() as u32).max(1);
+        (scale(self.config.width), scale(self.config.height))
+    }
+
+    /// Render the visualization at `scale` of the surface resolution; the
+    /// composite pass upscales it while egui stays at native resolution.
+    pub fn set_render_scale(&mut self, scale: f32) {
+        if (scale - self.render_scale).abs() > 0.001 {
+            self.render_scale = scale;
+            self.resize_scene_targets();
         }
     }
+
+    fn resize_scene_targets(&mut self) {
+        let (width, height) = self.scene_size();
+        self.post.resize(&self.device, width, height);
+        self.feedback.resize(&self.device, &self.feedback_bind_group_layout, width, height);
+        self.multibuffer.resize(&self.device, &self.multibuffer_bind_group_layout, width, height);
+        self.depth_view = super::target::create_depth_view(&self.device, width, height);
+    }
+}
+
+fn wgpu_present_mode(mode: PresentMode) -> wgpu::PresentMode {
+    match mode {
+        PresentMode::VSync     => wgpu::PresentMode::Fifo,
+        PresentMode::Mailbox   => wgpu::PresentMode::Mailbox,
+        PresentMode::Immediate => wgpu::PresentMode::Immediate,
+    }
+}
+
+/// `Opaque` when the surface offers it, else whatever it prefers.
+fn opaque_alpha_mode(modes: &[wgpu::CompositeAlphaMode]) -> wgpu::CompositeAlphaMode {
+    if modes.contains(&wgpu::CompositeAlphaMode::Opaque) {
+        wgpu::CompositeAlphaMode::Opaque
+    } else {
+        modes[0]
+    }
 }
@@ -0,0 +1,152 @@
+//! GPU inputs for imported Shadertoy shaders (uniforms and the iChannel0 audio texture)
+
+use crate::common::types::ShadertoyUniforms;
+use crate::config::constants::AUDIO_TEXTURE_WIDTH;
+use wgpu::util::DeviceExt;
+
+/// Bind group 1 for Shadertoy plugins.
+///
+/// `iChannel0` mirrors Shadertoy's audio input: a 512×2 R8 texture whose
+/// first row is the spectrum and second row the waveform (0.5 = silence).
+pub struct ShadertoyInputs {
+    uniform_buffer: wgpu::Buffer,
+    audio_texture: wgpu::Texture,
+    pub bind_group: wgpu::BindGroup,
+    /// Mouse state in Shadertoy's `iMouse` convention, set by the app.
+    pub mouse: [f32; 4],
+    frame: i32,
+    last_time: f32,
+    audio_texels: Vec<u8>,
+}
+
+impl ShadertoyInputs {
+    pub fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout) -> Self {
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Shadertoy Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[ShadertoyUniforms::default()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let audio_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shadertoy Audio Texture"),
+            size: wgpu::Extent3d { width: AUDIO_TEXTURE_WIDTH, height: 2, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let audio_view = audio_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadertoy Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: uniform_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&audio_view) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(&sampler) },
+            ],
+            label: Some("shadertoy_bind_group"),
+        });
+
+        Self {
+            uniform_buffer,
+            audio_texture,
+            bind_group,
+            mouse: [0.0; 4],
+            frame: 0,
+            last_time: 0.0,
+            audio_texels: vec![0; AUDIO_TEXTURE_WIDTH as usize * 2],
+        }
+    }
+
+    /// Upload this frame's uniforms and audio rows.
+    ///
+    /// `spectrum` is the smoothed magnitude spectrum (0..1) and `waveform` the
+    /// gain-scaled samples (-1..1); both are stretched to the texture width.
+    pub fn update(&mut self, queue: &wgpu::Queue, resolution: [f32; 2], time: f32, spectrum: &[f32], waveform: &[f32]) {
+        let uniforms = ShadertoyUniforms {
+            resolution: [resolution[0], resolution[1], 1.0],
+            time,
+            mouse: self.mouse,
+            time_delta: (time - self.last_time).max(0.0),
+            frame: self.frame,
+            padding: [0.0; 2],
+        };
+        self.frame = self.frame.wrapping_add(1);
+        self.last_time = time;
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+
+        let width = AUDIO_TEXTURE_WIDTH as usize;
+        let (spectrum_row, waveform_row) = self.audio_texels.split_at_mut(width);
+        fill_row(spectrum_row, spectrum, |v| v);
+        fill_row(waveform_row, waveform, |v| v * 0.5 + 0.5);
+
+        queue.write_texture(
+            self.audio_texture.as_image_copy(),
+            &self.audio_texels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(AUDIO_TEXTURE_WIDTH),
+                rows_per_image: Some(2),
+            },
+            self.audio_texture.size(),
+        );
+    }
+}
+
+/// Resample `values` across `row`, mapping each through `to_unit` into 0..1.
+fn fill_row(row: &mut [u8], values: &[f32], to_unit: impl Fn(f32) -> f32) {
+    if values.is_empty() {
+        row.fill(0);
+        return;
+    }
+    let len = row.len();
+    for (i, texel) in row.iter_mut().enumerate() {
+        let v = values[i * values.len() / len];
+        *texel = (to_unit(v).clamp(0.0, 1.0) * 255.0) as u8;
+    }
+}
+
+pub fn create_shadertoy_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+        label: Some("shadertoy_bind_group_layout"),
+    })
+}
@@ -0,0 +1,325 @@
+//! Text overlay: caption, clock and track title drawn over the composite
+
+use crate::common::error::{AppError, AppResult};
+use crate::common::types::TextUniforms;
+use crate::config::settings::{TextAnchor, TextStyle};
+use ab_glyph::{point, Font, FontArc, GlyphId, PxScale, ScaleFont};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use wgpu::util::DeviceExt;
+
+/// Font used when none is configured or the configured one fails to load.
+const BUNDLED_FONT: &[u8] = include_bytes!("../../assets/DkHandRegular-orna.ttf");
+
+/// One line of the overlay for this frame.
+pub struct TextLine {
+    pub text: String,
+    pub style: TextStyle,
+    /// Fade progress, 0 (hidden) to 1.
+    pub fade: f32,
+}
+
+/// Lines of text rasterized on the CPU whenever they change and drawn as
+/// quads in the surface pass, between the composite and egui, so they stay on
+/// screen whichever visualization is running.
+pub struct TextOverlay {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    font: FontArc,
+    font_path: Option<PathBuf>,
+    slots: Vec<TextSlot>,
+}
+
+/// GPU state of one line, kept while its text and pixel size stay the same.
+struct TextSlot {
+    text: String,
+    px: u32,
+    uniform_buffer: wgpu::Buffer,
+    /// Glyph texture bind group and size; `None` for empty text.
+    texture: Option<(wgpu::BindGroup, [u32; 2])>,
+    visible: bool,
+}
+
+impl TextOverlay {
+    pub fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+        let layout = create_text_bind_group_layout(device);
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Text Overlay Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Text Overlay Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/text_overlay.wgsl").into()),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text Overlay Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Text Overlay Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self { pipeline, layout, sampler, font: bundled_font(), font_path: None, slots: Vec::new() }
+    }
+
+    /// Font file in use, `None` for the bundled one.
+    pub fn font_path(&self) -> Option<&Path> {
+        self.font_path.as_deref()
+    }
+
+    /// Switch to the font at `path`, or the bundled one for `None`. A font
+    /// that cannot be loaded is reported and replaced by the bundled one.
+    pub fn set_font(&mut self, path: Option<&Path>) -> AppResult<()> {
+        let loaded = path.map(load_font).transpose();
+        self.font = match &loaded {
+            Ok(Some(font)) => font.clone(),
+            _ => bundled_font(),
+        };
+        self.font_path = path.map(Path::to_path_buf);
+        // Force every line to be rasterized again.
+        self.slots.iter_mut().for_each(|slot| slot.px = 0);
+        loaded.map(|_| ())
+    }
+
+    /// Rasterize lines whose text or size changed and place all of them for
+    /// a `screen`-sized surface. Lines sharing an anchor stack away from its
+    /// edge in order; `beat` scales each line by its `beat_scale`.
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        lines: &[TextLine],
+        color: [f32; 3],
+        screen: [u32; 2],
+        beat: f32,
+    ) {
+        while self.slots.len() < lines.len() {
+            self.slots.push(TextSlot {
+                text: String::new(),
+                px: 0,
+                uniform_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Text Overlay Uniform Buffer"),
+                    contents: bytemuck::cast_slice(&[TextUniforms::default()]),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                }),
+                texture: None,
+                visible: false,
+            });
+        }
+
+        let [width, height] = screen.map(|v| v.max(1) as f32);
+        let margin = height * 0.03;
+        let max_size = device.limits().max_texture_dimension_2d;
+        let mut stacked: HashMap<TextAnchor, f32> = HashMap::new();
+        for (i, slot) in self.slots.iter_mut().enumerate() {
+            slot.visible = false;
+            let Some(line) = lines.get(i) else { continue };
+
+            let px = (line.style.size * height).round().max(8.0) as u32;
+            if slot.text != line.text || slot.px != px {
+                slot.texture = rasterize(&self.font, &line.text, px, max_size).map(|(coverage, size)| {
+                    let bind_group = create_text_bind_group(device, queue, &self.layout, &self.sampler, &slot.uniform_buffer, &coverage, size);
+                    (bind_group, size)
+                });
+                slot.text.clone_from(&line.text);
+                slot.px = px;
+            }
+            let alpha = line.style.opacity * line.fade.clamp(0.0, 1.0);
+            let Some((_, [w, h])) = slot.texture else { continue };
+            if alpha <= 0.0 { continue; }
+
+            let (w, h) = (w as f32, h as f32);
+            let [ax, ay] = line.style.anchor.align();
+            let offset = stacked.entry(line.style.anchor).or_insert(0.0);
+            let x = margin + ax * (width - 2.0 * margin - w);
+            let y = margin + ay * (height - 2.0 * margin - h) + if ay == 1.0 { -*offset } else { *offset };
+            *offset += h;
+
+            let scale = 1.0 + line.style.beat_scale * beat;
+            let (cx, cy) = (x + w * 0.5, y + h * 0.5);
+            let (hw, hh) = (w * scale * 0.5, h * scale * 0.5);
+            let shadow = (px as f32 / 24.0).max(1.0);
+            let uniforms = TextUniforms {
+                rect: [
+                    (cx - hw) / width * 2.0 - 1.0,
+                    1.0 - (cy - hh) / height * 2.0,
+                    (cx + hw) / width * 2.0 - 1.0,
+                    1.0 - (cy + hh) / height * 2.0,
+                ],
+                color: [color[0], color[1], color[2], alpha],
+                shadow: [shadow / w, shadow / h],
+                padding: [0.0; 2],
+            };
+            queue.write_buffer(&slot.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+            slot.visible = true;
+        }
+    }
+
+    pub fn draw<'a>(&'a self, rpass: &mut wgpu::RenderPass<'a>) {
+        rpass.set_pipeline(&self.pipeline);
+        for slot in self.slots.iter().filter(|slot| slot.visible) {
+            if let Some((bind_group, _)) = &slot.texture {
+                rpass.set_bind_group(0, bind_group, &[]);
+                rpass.draw(0..4, 0..1);
+            }
+        }
+    }
+}
+
+fn bundled_font() -> FontArc {
+    FontArc::try_from_slice(BUNDLED_FONT).expect("bundled font is valid")
+}
+
+fn load_font(path: &Path) -> AppResult<FontArc> {
+    let data = std::fs::read(path)
+        .map_err(|e| AppError::Config(format!("Cannot read font {}: {}", path.display(), e)))?;
+    FontArc::try_from_vec(data)
+        .map_err(|_| AppError::Config(format!("{} is not a TrueType/OpenType font", path.display())))
+}
+
+/// Glyph coverage of `text` at `px` pixels, one row per line, with room
+/// around it for the drop shadow. `None` when there is nothing to draw.
+fn rasterize(font: &FontArc, text: &str, px: u32, max_size: u32) -> Option<(Vec<u8>, [u32; 2])> {
+    let scaled = font.as_scaled(PxScale::from(px as f32));
+    let pad = (px / 12).max(2) as f32;
+    let line_height = scaled.height() + scaled.line_gap();
+
+    let mut glyphs = Vec::new();
+    let mut text_width = 0.0f32;
+    let mut rows = 0;
+    for (row, line) in text.lines().enumerate() {
+        let baseline = pad + scaled.ascent() + row as f32 * line_height;
+        let mut x = pad;
+        let mut previous: Option<GlyphId> = None;
+        for c in line.chars().filter(|c| !c.is_control()) {
+            let id = scaled.glyph_id(c);
+            if let Some(previous) = previous {
+                x += scaled.kern(previous, id);
+            }
+            glyphs.push(id.with_scale_and_position(scaled.scale(), point(x, baseline)));
+            x += scaled.h_advance(id);
+            previous = Some(id);
+        }
+        text_width = text_width.max(x);
+        rows = row + 1;
+    }
+    if glyphs.is_empty() {
+        return None;
+    }
+
+    let width = ((text_width + pad).ceil() as u32).clamp(1, max_size);
+    let height = ((2.0 * pad + scaled.height() + (rows - 1) as f32 * line_height).ceil() as u32).clamp(1, max_size);
+    let mut coverage = vec![0u8; (width * height) as usize];
+    for glyph in glyphs {
+        let Some(outlined) = font.outline_glyph(glyph) else { continue };
+        let bounds = outlined.px_bounds();
+        outlined.draw(|gx, gy, c| {
+            let x = bounds.min.x as i32 + gx as i32;
+            let y = bounds.min.y as i32 + gy as i32;
+            if x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height {
+                let pixel = &mut coverage[(y as u32 * width + x as u32) as usize];
+                *pixel = (*pixel).max((c * 255.0) as u8);
+            }
+        });
+    }
+    Some((coverage, [width, height]))
+}
+
+fn create_text_bind_group(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    layout: &wgpu::BindGroupLayout,
+    sampler: &wgpu::Sampler,
+    uniform_buffer: &wgpu::Buffer,
+    coverage: &[u8],
+    [width, height]: [u32; 2],
+) -> wgpu::BindGroup {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Text Overlay Texture"),
+        size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::R8Unorm,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        texture.as_image_copy(),
+        coverage,
+        wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(width), rows_per_image: Some(height) },
+        wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+    );
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry { binding: 0, resource: uniform_buffer.as_entire_binding() },
+            wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&view) },
+            wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(sampler) },
+        ],
+        label: Some("text_overlay_bind_group"),
+    })
+}
+
+fn create_text_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+        label: Some("text_overlay_bind_group_layout"),
+    })
+}
@@ -0,0 +1,154 @@
+//! GPU frame timing via timestamp queries
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+/// Points in the frame where a timestamp is written.
+#[derive(Debug, Clone, Copy)]
+pub enum TimingMark {
+    FrameStart = 0,
+    SceneEnd = 1,
+    FrameEnd = 2,
+}
+
+const MARK_COUNT: u32 = 3;
+const RESOLVE_SIZE: u64 = MARK_COUNT as u64 * 8;
+
+// Readback states shared with the map callback.
+const MAP_PENDING: u8 = 0;
+const MAP_READY: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+/// GPU time of the last measured frame, in milliseconds.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GpuTimes {
+    /// Particles, plugin passes and the scene pass.
+    pub scene_ms: f32,
+    /// Post-processing chain, composite and egui.
+    pub post_ms: f32,
+}
+
+impl GpuTimes {
+    pub fn total_ms(&self) -> f32 {
+        self.scene_ms + self.post_ms
+    }
+}
+
+/// Timestamp queries around the main stages of a frame.
+///
+/// Results are read back asynchronously; while one readback is in flight
+/// further frames are not resolved, so `last` updates every few frames.
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+    /// Set from the map callback once the readback finishes.
+    map_state: Arc<AtomicU8>,
+    in_flight: bool,
+    resolved: bool,
+    pub last: Option<GpuTimes>,
+}
+
+impl GpuTimer {
+    /// `None` when the device was created without `TIMESTAMP_QUERY`.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Frame Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: MARK_COUNT,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Resolve Buffer"),
+            size: RESOLVE_SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Readback Buffer"),
+            size: RESOLVE_SIZE,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            period: queue.get_timestamp_period(),
+            map_state: Arc::new(AtomicU8::new(MAP_PENDING)),
+            in_flight: false,
+            resolved: false,
+            last: None,
+        })
+    }
+
+    /// Write the timestamp for `mark`.
+    ///
+    /// Uses an empty compute pass so only `TIMESTAMP_QUERY` is required, not
+    /// timestamps inside encoders.
+    pub fn mark(&self, encoder: &mut wgpu::CommandEncoder, mark: TimingMark) {
+        encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Timestamp"),
+            timestamp_writes: Some(wgpu::ComputePassTimestampWrites {
+                query_set: &self.query_set,
+                beginning_of_pass_write_index: Some(mark as u32),
+                end_of_pass_write_index: None,
+            }),
+        });
+    }
+
+    /// Copy this frame's timestamps out, unless a readback is still pending.
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if self.in_flight {
+            return;
+        }
+        encoder.resolve_query_set(&self.query_set, 0..MARK_COUNT, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, RESOLVE_SIZE);
+        self.resolved = true;
+    }
+
+    /// Start reading back what `resolve` copied; call after submitting.
+    pub fn after_submit(&mut self) {
+        if !self.resolved {
+            return;
+        }
+        self.resolved = false;
+        self.in_flight = true;
+        let map_state = Arc::clone(&self.map_state);
+        self.readback_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            let state = if result.is_ok() { MAP_READY } else { MAP_FAILED };
+            map_state.store(state, Ordering::Release);
+        });
+    }
+
+    /// Collect a finished readback into `last`.
+    pub fn poll(&mut self, device: &wgpu::Device) {
+        if !self.in_flight {
+            return;
+        }
+        device.poll(wgpu::Maintain::Poll);
+        match self.map_state.swap(MAP_PENDING, Ordering::Acquire) {
+            MAP_READY => {}
+            MAP_FAILED => {
+                self.in_flight = false;
+                return;
+            }
+            _ => return,
+        }
+        {
+            let data = self.readback_buffer.slice(..).get_mapped_range();
+            let ticks: &[u64] = bytemuck::cast_slice(&data);
+            let ms = |from: u64, to: u64| to.saturating_sub(from) as f32 * self.period / 1_000_000.0;
+            self.last = Some(GpuTimes {
+                scene_ms: ms(ticks[0], ticks[1]),
+                post_ms: ms(ticks[1], ticks[2]),
+            });
+        }
+        self.readback_buffer.unmap();
+        self.in_flight = false;
+    }
+}
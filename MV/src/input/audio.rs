@@ -1,112 +1,204 @@
 //! Audio input handling using cpal
 
-use crate::config::constants::SAMPLE_SIZE;
+use crate::config::constants::{AUDIO_STALL_SECONDS, SAMPLE_SIZE};
 use crate::common::error::{AppError, AppResult};
+use super::network::NetworkReceiver;
+use super::sources::{route_pulse_capture, AudioSource, SourceKind};
 use cpal::traits::{DeviceTrait, StreamTrait};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 /// Audio handler for capturing input from a selected device
 pub struct AudioHandler {
     pub buffer: Arc<Mutex<Vec<f32>>>,
-    _input_stream: cpal::Stream,
+    /// Name of the device the stream captures from.
+    pub device_name: String,
+    /// Set by the stream's error callback when the device goes away.
+    lost: Arc<Mutex<Option<String>>>,
+    /// Incremented by every input callback.
+    callbacks: Arc<AtomicU64>,
+    last_callbacks: u64,
+    last_progress: Instant,
+    _capture: Capture,
 }
 
-impl AudioHandler {
-    /// Create a new audio handler with specified input device
-    pub fn new(device: cpal::Device) -> AppResult<Self> {
-
-        // Try to find a supported input config
-        let supported_configs = device
-            .supported_input_configs()
-            .map_err(|e| AppError::Audio(format!("Failed to get supported configs: {}", e)))?;
-
-        let config = supported_configs
-            .filter(|config| config.channels() == 1 || config.channels() == 2)
-            .find_map(|config| {
-                // Prefer 44100 Hz, but accept others
-                let sample_rate = if config.min_sample_rate().0 <= 44100 && config.max_sample_rate().0 >= 44100 {
-                    cpal::SampleRate(44100)
-                } else {
-                    config.min_sample_rate()
-                };
-                Some(config.with_sample_rate(sample_rate))
-            })
-            .ok_or_else(|| AppError::Audio("No suitable input config found for device".to_string()))?;
+/// Keeps the capture running while the handler is alive.
+enum Capture {
+    Device { _stream: cpal::Stream },
+    Network { _receiver: NetworkReceiver },
+}
 
-        let channels = config.channels();
-        let sample_format = config.sample_format();
-        let stream_config = config.into();
+impl AudioHandler {
+    /// Create a new audio handler capturing from `source`
+    pub fn new(source: &AudioSource) -> AppResult<Self> {
         let buffer = Arc::new(Mutex::new(vec![0.0; SAMPLE_SIZE]));
+        let callbacks = Arc::new(AtomicU64::new(0));
+        let lost = Arc::new(Mutex::new(None));
 
-        let err_fn = |err| eprintln!("Audio error: {}", err);
-
-        let stream = match sample_format {
-            cpal::SampleFormat::F32 => {
-                let buffer_clone = Arc::clone(&buffer);
-                device.build_input_stream(
-                    &stream_config,
-                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                        let mut buf = buffer_clone.lock().unwrap();
-                        mix_to_mono(data, &mut buf, channels);
-                    },
-                    err_fn,
-                    None,
-                )
-            }
-            cpal::SampleFormat::I16 => {
-                let buffer_clone = Arc::clone(&buffer);
-                device.build_input_stream(
-                    &stream_config,
-                    move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                        let mut buf = buffer_clone.lock().unwrap();
-                        let f32_data: Vec<f32> = data.iter().map(|&s| s as f32 / 32768.0).collect();
-                        mix_to_mono(&f32_data, &mut buf, channels);
-                    },
-                    err_fn,
-                    None,
-                )
-            }
-            cpal::SampleFormat::U16 => {
-                let buffer_clone = Arc::clone(&buffer);
-                device.build_input_stream(
-                    &stream_config,
-                    move |data: &[u16], _: &cpal::InputCallbackInfo| {
-                        let mut buf = buffer_clone.lock().unwrap();
-                        let f32_data: Vec<f32> = data.iter().map(|&s| (s as f32 - 32768.0) / 32768.0).collect();
-                        mix_to_mono(&f32_data, &mut buf, channels);
-                    },
-                    err_fn,
-                    None,
-                )
+        let capture = match &source.kind {
+            SourceKind::Device { device, output, pulse_source } => {
+                let config = capture_config(device, *output)?;
+                let stream = device_stream(device, config, &buffer, &callbacks, &lost)?;
+                if let Some(name) = pulse_source {
+                    route_pulse_capture(name)?;
+                }
+                Capture::Device { _stream: stream }
             }
-            cpal::SampleFormat::U8 => {
-                let buffer_clone = Arc::clone(&buffer);
-                device.build_input_stream(
-                    &stream_config,
-                    move |data: &[u8], _: &cpal::InputCallbackInfo| {
-                        let mut buf = buffer_clone.lock().unwrap();
-                        let f32_data: Vec<f32> = data.iter().map(|&s| (s as f32 - 128.0) / 128.0).collect();
-                        mix_to_mono(&f32_data, &mut buf, channels);
-                    },
-                    err_fn,
-                    None,
-                )
+            SourceKind::Network(addr) => {
+                Capture::Network { _receiver: NetworkReceiver::start(addr, Arc::clone(&buffer), Arc::clone(&callbacks))? }
             }
-            _ => return Err(AppError::Audio(format!("Unsupported sample format: {:?}", sample_format))),
-        }?;
-
-        stream.play().map_err(|e| AppError::Audio(format!("Failed to start input stream: {}", e)))?;
+        };
 
         Ok(Self {
             buffer,
-            _input_stream: stream,
+            device_name: source.name.clone(),
+            lost,
+            callbacks,
+            last_callbacks: 0,
+            last_progress: Instant::now(),
+            _capture: capture,
         })
     }
+
+    /// Why the stream is no longer usable, if it has failed: the device was
+    /// reported unavailable or no data has arrived for `AUDIO_STALL_SECONDS`.
+    pub fn failure(&mut self) -> Option<String> {
+        if let Some(reason) = self.lost.lock().unwrap().clone() {
+            return Some(reason);
+        }
+        let count = self.callbacks.load(Ordering::Relaxed);
+        if count != self.last_callbacks {
+            self.last_callbacks = count;
+            self.last_progress = Instant::now();
+        } else if self.last_progress.elapsed().as_secs_f32() > AUDIO_STALL_SECONDS {
+            return Some(format!("no audio data for {AUDIO_STALL_SECONDS} s"));
+        }
+        None
+    }
+}
+
+/// Pick a mono or stereo config for capturing from `device`, preferring 44.1 kHz.
+///
+/// Loopback capture (`output`) uses the output device's configs.
+pub(crate) fn capture_config(device: &cpal::Device, output: bool) -> AppResult<cpal::SupportedStreamConfig> {
+    let supported_configs: Vec<_> = if output {
+        device.supported_output_configs().map(Iterator::collect)
+    } else {
+        device.supported_input_configs().map(Iterator::collect)
+    }
+    .map_err(|e| AppError::Audio(format!("Failed to get supported configs: {}", e)))?;
+
+    supported_configs
+        .into_iter()
+        .filter(|config| config.channels() == 1 || config.channels() == 2)
+        .map(|config| {
+            // Prefer 44100 Hz, but accept others
+            let sample_rate = if config.min_sample_rate().0 <= 44100 && config.max_sample_rate().0 >= 44100 {
+                cpal::SampleRate(44100)
+            } else {
+                config.min_sample_rate()
+            };
+            config.with_sample_rate(sample_rate)
+        })
+        .next()
+        .ok_or_else(|| AppError::Audio("No suitable input config found for device".to_string()))
+}
+
+/// Start a stream that mixes each callback's samples into `buffer`.
+fn device_stream(
+    device: &cpal::Device,
+    config: cpal::SupportedStreamConfig,
+    buffer: &Arc<Mutex<Vec<f32>>>,
+    callbacks: &Arc<AtomicU64>,
+    lost: &Arc<Mutex<Option<String>>>,
+) -> AppResult<cpal::Stream> {
+    let channels = config.channels();
+    let sample_format = config.sample_format();
+    let stream_config = config.into();
+
+    // Other backend errors (e.g. xruns) are often recoverable; a stream
+    // that stops delivering data is caught by `failure` instead.
+    let err_fn = {
+        let lost = Arc::clone(lost);
+        move |err| {
+            eprintln!("Audio error: {}", err);
+            if let cpal::StreamError::DeviceNotAvailable = err {
+                *lost.lock().unwrap() = Some(err.to_string());
+            }
+        }
+    };
+
+    let stream = match sample_format {
+        cpal::SampleFormat::F32 => {
+            let buffer_clone = Arc::clone(buffer);
+            let callbacks = Arc::clone(callbacks);
+            device.build_input_stream(
+                &stream_config,
+                move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                    callbacks.fetch_add(1, Ordering::Relaxed);
+                    let mut buf = buffer_clone.lock().unwrap();
+                    mix_to_mono(data, &mut buf, channels);
+                },
+                err_fn,
+                None,
+            )
+        }
+        cpal::SampleFormat::I16 => {
+            let buffer_clone = Arc::clone(buffer);
+            let callbacks = Arc::clone(callbacks);
+            device.build_input_stream(
+                &stream_config,
+                move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                    callbacks.fetch_add(1, Ordering::Relaxed);
+                    let mut buf = buffer_clone.lock().unwrap();
+                    let f32_data: Vec<f32> = data.iter().map(|&s| s as f32 / 32768.0).collect();
+                    mix_to_mono(&f32_data, &mut buf, channels);
+                },
+                err_fn,
+                None,
+            )
+        }
+        cpal::SampleFormat::U16 => {
+            let buffer_clone = Arc::clone(buffer);
+            let callbacks = Arc::clone(callbacks);
+            device.build_input_stream(
+                &stream_config,
+                move |data: &[u16], _: &cpal::InputCallbackInfo| {
+                    callbacks.fetch_add(1, Ordering::Relaxed);
+                    let mut buf = buffer_clone.lock().unwrap();
+                    let f32_data: Vec<f32> = data.iter().map(|&s| (s as f32 - 32768.0) / 32768.0).collect();
+                    mix_to_mono(&f32_data, &mut buf, channels);
+                },
+                err_fn,
+                None,
+            )
+        }
+        cpal::SampleFormat::U8 => {
+            let buffer_clone = Arc::clone(buffer);
+            let callbacks = Arc::clone(callbacks);
+            device.build_input_stream(
+                &stream_config,
+                move |data: &[u8], _: &cpal::InputCallbackInfo| {
+                    callbacks.fetch_add(1, Ordering::Relaxed);
+                    let mut buf = buffer_clone.lock().unwrap();
+                    let f32_data: Vec<f32> = data.iter().map(|&s| (s as f32 - 128.0) / 128.0).collect();
+                    mix_to_mono(&f32_data, &mut buf, channels);
+                },
+                err_fn,
+                None,
+            )
+        }
+        _ => return Err(AppError::Audio(format!("Unsupported sample format: {:?}", sample_format))),
+    }?;
+
+    stream.play().map_err(|e| AppError::Audio(format!("Failed to start input stream: {}", e)))?;
+    Ok(stream)
 }
 
 /// Mix multi-channel interleaved samples down to mono and write into `out`.
 /// `channels` is the number of channels per frame.
-fn mix_to_mono(data: &[f32], out: &mut Vec<f32>, channels: u16) {
+pub(crate) fn mix_to_mono(data: &[f32], out: &mut [f32], channels: u16) {
     let ch = channels as usize;
     if ch == 0 {
         for x in out.iter_mut() { *x = 0.0; }
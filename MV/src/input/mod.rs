@@ -1,5 +1,11 @@
 //! Centralised keyboard shortcut and input management.
 
 pub mod audio;
+pub mod network;
+pub mod now_playing;
+pub mod pointer;
+pub mod session;
 pub mod shortcuts;
-pub use shortcuts::{ShortcutAction, ShortcutDef, SHORTCUTS, key_to_action};
+pub mod sources;
+pub mod synthetic;
+pub use shortcuts::{ShortcutAction, SHORTCUTS, key_to_action};
@@ -0,0 +1,576 @@
+//! Network PCM audio: a receiver feeding the analysis path and the
+//! `--send-audio` sender
+//!
+//! Every frame is a 20-byte little-endian header followed by interleaved
+//! samples:
+//!
+//! | offset | size | field                                      |
+//! |--------|------|--------------------------------------------|
+//! | 0      | 4    | magic `MVPC`                               |
+//! | 4      | 1    | version, currently 1                       |
+//! | 5      | 1    | sample format: 0 = f32, 1 = i16            |
+//! | 6      | 1    | channels, 1–8                              |
+//! | 7      | 1    | reserved, 0                                |
+//! | 8      | 4    | sample rate in Hz                          |
+//! | 12     | 4    | sequence number, +1 per frame, wrapping    |
+//! | 16     | 4    | payload length in bytes                    |
+//!
+//! A frame, header included, is at most `NETWORK_MAX_PAYLOAD` bytes; the
+//! receiver rejects headers announcing more. Over UDP each datagram carries
+//! one frame; over TCP frames follow each other on the stream. The receiver
+//! reorders nothing: late and duplicate frames are dropped and gaps are
+//! filled with silence.
+
+use crate::common::error::{AppError, AppResult};
+use crate::config::constants::{
+    DEFAULT_NETWORK_PORT, NETWORK_MAX_LATENCY_MS, NETWORK_MAX_PAYLOAD, NETWORK_TARGET_LATENCY_MS,
+};
+use super::audio::{capture_config, mix_to_mono};
+use super::sources::{route_pulse_capture, AudioSource, SourceKind};
+use cpal::traits::{DeviceTrait, StreamTrait};
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const MAGIC: &[u8; 4] = b"MVPC";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 20;
+const FORMAT_F32: u8 = 0;
+const FORMAT_I16: u8 = 1;
+const MAX_CHANNELS: u8 = 8;
+
+/// How often the receiver releases buffered audio to the visualizer.
+const PLAYOUT_TICK: Duration = Duration::from_millis(10);
+
+/// Frames whose sequence number is this far behind are taken as a restarted
+/// sender rather than late packets.
+const RESYNC_DISTANCE: u32 = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Udp,
+    Tcp,
+}
+
+/// `udp://host:port` or `tcp://host:port`; the port defaults to
+/// `DEFAULT_NETWORK_PORT`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkAddr {
+    pub transport: Transport,
+    pub host_port: String,
+}
+
+impl NetworkAddr {
+    pub fn parse(url: &str) -> AppResult<Self> {
+        let (transport, rest) = if let Some(rest) = url.strip_prefix("udp://") {
+            (Transport::Udp, rest)
+        } else if let Some(rest) = url.strip_prefix("tcp://") {
+            (Transport::Tcp, rest)
+        } else {
+            return Err(AppError::Config(format!("'{}' is not a udp:// or tcp:// address", url)));
+        };
+        if rest.is_empty() {
+            return Err(AppError::Config(format!("'{}' has no host", url)));
+        }
+        // A colon after the last `]` means a port is given (IPv6 hosts are bracketed).
+        let has_port = rest.rsplit_once(']').map_or(rest, |(_, tail)| tail).contains(':');
+        let host_port = if has_port { rest.to_string() } else { format!("{}:{}", rest, DEFAULT_NETWORK_PORT) };
+        Ok(Self { transport, host_port })
+    }
+}
+
+impl std::fmt::Display for NetworkAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scheme = match self.transport {
+            Transport::Udp => "udp",
+            Transport::Tcp => "tcp",
+        };
+        write!(f, "{}://{}", scheme, self.host_port)
+    }
+}
+
+/// Frame header, see the module docs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Header {
+    format: u8,
+    channels: u8,
+    sample_rate: u32,
+    sequence: u32,
+    payload_len: u32,
+}
+
+impl Header {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&[VERSION, self.format, self.channels, 0]);
+        out.extend_from_slice(&self.sample_rate.to_le_bytes());
+        out.extend_from_slice(&self.sequence.to_le_bytes());
+        out.extend_from_slice(&self.payload_len.to_le_bytes());
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_LEN {
+            return Err("short frame".to_string());
+        }
+        if &bytes[0..4] != MAGIC {
+            return Err("bad magic".to_string());
+        }
+        if bytes[4] != VERSION {
+            return Err(format!("unsupported version {}", bytes[4]));
+        }
+        let word = |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+        let header = Self {
+            format: bytes[5],
+            channels: bytes[6],
+            sample_rate: word(8),
+            sequence: word(12),
+            payload_len: word(16),
+        };
+        let sample_size = match header.format {
+            FORMAT_F32 => 4,
+            FORMAT_I16 => 2,
+            other => return Err(format!("unknown sample format {}", other)),
+        };
+        if header.channels == 0 || header.channels > MAX_CHANNELS {
+            return Err(format!("unsupported channel count {}", header.channels));
+        }
+        if header.sample_rate == 0 {
+            return Err("zero sample rate".to_string());
+        }
+        if header.payload_len as usize > NETWORK_MAX_PAYLOAD - HEADER_LEN {
+            return Err(format!("payload of {} bytes is too large", header.payload_len));
+        }
+        if !(header.payload_len as usize).is_multiple_of(sample_size * header.channels as usize) {
+            return Err("payload is not a whole number of frames".to_string());
+        }
+        Ok(header)
+    }
+
+    fn decode(&self, payload: &[u8]) -> Vec<f32> {
+        match self.format {
+            FORMAT_F32 => payload.chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect(),
+            _ => payload.chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+                .collect(),
+        }
+    }
+}
+
+/// Smooths bursty network arrival into a steady stream.
+///
+/// Playout starts once `NETWORK_TARGET_LATENCY_MS` of audio is queued and
+/// pauses again on underrun; audio beyond `NETWORK_MAX_LATENCY_MS` is dropped
+/// from the front so a sender running fast cannot build up delay.
+struct JitterBuffer {
+    /// Interleaved samples.
+    queue: VecDeque<f32>,
+    channels: u8,
+    sample_rate: u32,
+    next_sequence: Option<u32>,
+    playing: bool,
+}
+
+impl JitterBuffer {
+    fn new() -> Self {
+        Self { queue: VecDeque::new(), channels: 1, sample_rate: 44100, next_sequence: None, playing: false }
+    }
+
+    fn frames_for_ms(&self, ms: u32) -> usize {
+        (self.sample_rate as u64 * ms as u64 / 1000) as usize
+    }
+
+    fn push(&mut self, header: &Header, samples: &[f32]) {
+        if header.channels != self.channels || header.sample_rate != self.sample_rate {
+            *self = Self { channels: header.channels, sample_rate: header.sample_rate, ..Self::new() };
+        }
+        let channels = self.channels as usize;
+        let max_samples = self.frames_for_ms(NETWORK_MAX_LATENCY_MS) * channels;
+
+        if let Some(expected) = self.next_sequence {
+            let ahead = header.sequence.wrapping_sub(expected);
+            let behind = expected.wrapping_sub(header.sequence);
+            if ahead > u32::MAX / 2 && behind <= RESYNC_DISTANCE {
+                return; // late or duplicate
+            }
+            if ahead > 0 && ahead <= u32::MAX / 2 {
+                // Conceal lost frames with silence of the same length.
+                let missing = (ahead as usize * samples.len()).min(max_samples);
+                self.queue.extend(std::iter::repeat_n(0.0, missing));
+            }
+        }
+        self.next_sequence = Some(header.sequence.wrapping_add(1));
+        self.queue.extend(samples);
+
+        if self.queue.len() > max_samples {
+            let excess = self.queue.len() - max_samples;
+            let excess = excess - excess % channels;
+            self.queue.drain(..excess);
+        }
+    }
+
+    /// Take up to `frames` frames of interleaved audio; empty while buffering.
+    fn pop(&mut self, frames: usize) -> Vec<f32> {
+        let channels = self.channels as usize;
+        if !self.playing {
+            if self.queue.len() < self.frames_for_ms(NETWORK_TARGET_LATENCY_MS) * channels {
+                return Vec::new();
+            }
+            self.playing = true;
+        }
+        let wanted = frames * channels;
+        if self.queue.len() < wanted {
+            self.playing = false;
+        }
+        let n = wanted.min(self.queue.len());
+        self.queue.drain(..n).collect()
+    }
+}
+
+/// Incoming bytes on one UDP socket or TCP connection.
+enum Link {
+    Udp(UdpSocket),
+    Tcp { listener: TcpListener, stream: Option<TcpStream>, pending: Vec<u8> },
+}
+
+impl Link {
+    fn bind(addr: &NetworkAddr) -> std::io::Result<Self> {
+        match addr.transport {
+            Transport::Udp => {
+                let socket = UdpSocket::bind(&addr.host_port)?;
+                socket.set_read_timeout(Some(PLAYOUT_TICK / 2))?;
+                Ok(Self::Udp(socket))
+            }
+            Transport::Tcp => {
+                let listener = TcpListener::bind(&addr.host_port)?;
+                listener.set_nonblocking(true)?;
+                Ok(Self::Tcp { listener, stream: None, pending: Vec::new() })
+            }
+        }
+    }
+
+    /// Wait briefly for data and hand every complete frame to `on_frame`.
+    fn receive(&mut self, on_frame: &mut impl FnMut(&[u8])) {
+        match self {
+            Self::Udp(socket) => {
+                let mut packet = [0u8; 65536];
+                if let Ok(len) = socket.recv(&mut packet) {
+                    on_frame(&packet[..len]);
+                }
+            }
+            Self::Tcp { listener, stream, pending } => {
+                // A new sender replaces the current one.
+                if let Ok((incoming, peer)) = listener.accept() {
+                    eprintln!("Network audio: sender connected from {}", peer);
+                    let _ = incoming.set_nonblocking(false);
+                    let _ = incoming.set_read_timeout(Some(PLAYOUT_TICK / 2));
+                    *stream = Some(incoming);
+                    pending.clear();
+                }
+                let Some(conn) = stream else {
+                    std::thread::sleep(PLAYOUT_TICK / 2);
+                    return;
+                };
+                let mut chunk = [0u8; 16384];
+                match conn.read(&mut chunk) {
+                    Ok(0) => {
+                        eprintln!("Network audio: sender disconnected");
+                        *stream = None;
+                    }
+                    Ok(len) => pending.extend_from_slice(&chunk[..len]),
+                    Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                    Err(e) => {
+                        eprintln!("Network audio: {}", e);
+                        *stream = None;
+                    }
+                }
+                while pending.len() >= HEADER_LEN {
+                    let len = match Header::parse(pending) {
+                        Ok(header) => HEADER_LEN + header.payload_len as usize,
+                        Err(e) => {
+                            // The stream is out of sync; drop the connection.
+                            eprintln!("Network audio: {}", e);
+                            *stream = None;
+                            pending.clear();
+                            break;
+                        }
+                    };
+                    if pending.len() < len {
+                        break;
+                    }
+                    on_frame(&pending[..len]);
+                    pending.drain(..len);
+                }
+            }
+        }
+    }
+}
+
+/// Receives network PCM on a background thread and writes it to the
+/// audio buffer at the sender's sample rate.
+pub struct NetworkReceiver {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl NetworkReceiver {
+    pub fn start(
+        addr: &NetworkAddr,
+        buffer: Arc<Mutex<Vec<f32>>>,
+        callbacks: Arc<AtomicU64>,
+    ) -> AppResult<Self> {
+        let mut link = Link::bind(addr)
+            .map_err(|e| AppError::Audio(format!("Cannot listen on {}: {}", addr, e)))?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                let mut jitter = JitterBuffer::new();
+                let mut last_tick = Instant::now();
+                let mut owed_frames = 0.0f64;
+                while !stop.load(Ordering::Relaxed) {
+                    link.receive(&mut |frame| match Header::parse(frame) {
+                        Ok(header) => {
+                            let payload = &frame[HEADER_LEN..];
+                            if payload.len() == header.payload_len as usize {
+                                jitter.push(&header, &header.decode(payload));
+                            }
+                        }
+                        Err(e) => eprintln!("Network audio: dropped frame ({})", e),
+                    });
+
+                    let elapsed = last_tick.elapsed();
+                    if elapsed < PLAYOUT_TICK {
+                        continue;
+                    }
+                    last_tick = Instant::now();
+                    owed_frames += elapsed.as_secs_f64() * jitter.sample_rate as f64;
+                    let frames = owed_frames as usize;
+                    owed_frames -= frames as f64;
+
+                    // While buffering this writes silence, which keeps the
+                    // stall detection quiet when the sender pauses.
+                    let samples = jitter.pop(frames);
+                    mix_to_mono(&samples, &mut buffer.lock().unwrap(), jitter.channels as u16);
+                    callbacks.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+        };
+        Ok(Self { stop, thread: Some(thread) })
+    }
+}
+
+impl Drop for NetworkReceiver {
+    fn drop(&mut self) {
+        // Joining frees the port before a new receiver binds it.
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Outgoing frames to a receiver; TCP reconnects after errors.
+enum Sink {
+    Udp(UdpSocket),
+    Tcp { addr: String, stream: Option<TcpStream>, retry_at: Instant },
+}
+
+impl Sink {
+    fn connect(addr: &NetworkAddr) -> AppResult<Self> {
+        match addr.transport {
+            Transport::Udp => {
+                let socket = UdpSocket::bind("0.0.0.0:0")
+                    .and_then(|s| s.connect(&addr.host_port).map(|_| s))
+                    .map_err(|e| AppError::Audio(format!("Cannot send to {}: {}", addr, e)))?;
+                Ok(Self::Udp(socket))
+            }
+            Transport::Tcp => Ok(Self::Tcp { addr: addr.host_port.clone(), stream: None, retry_at: Instant::now() }),
+        }
+    }
+
+    fn send(&mut self, frame: &[u8]) {
+        match self {
+            // UDP drops are the receiver's problem; it conceals them.
+            Self::Udp(socket) => { let _ = socket.send(frame); }
+            Self::Tcp { addr, stream, retry_at } => {
+                if stream.is_none() && Instant::now() >= *retry_at {
+                    match TcpStream::connect(addr.as_str()) {
+                        Ok(conn) => {
+                            let _ = conn.set_nodelay(true);
+                            eprintln!("Connected to {}", addr);
+                            *stream = Some(conn);
+                        }
+                        Err(e) => {
+                            eprintln!("Cannot connect to {}: {}", addr, e);
+                            *retry_at = Instant::now() + Duration::from_secs(1);
+                        }
+                    }
+                }
+                if let Some(conn) = stream {
+                    if let Err(e) = conn.write_all(frame) {
+                        eprintln!("Lost connection to {}: {}", addr, e);
+                        *stream = None;
+                        *retry_at = Instant::now() + Duration::from_secs(1);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Capture from `source` and stream it to `addr` as 16-bit PCM until the
+/// process is stopped.
+pub fn send_audio(source: &AudioSource, addr: &NetworkAddr) -> AppResult<()> {
+    let SourceKind::Device { device, output, pulse_source } = &source.kind else {
+        return Err(AppError::Audio("Only local devices can be sent".to_string()));
+    };
+    let config = capture_config(device, *output)?;
+    let channels = config.channels() as u8;
+    let sample_rate = config.sample_rate().0;
+
+    let (tx, rx) = mpsc::channel::<Vec<f32>>();
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => sender_stream::<f32>(device, &config.into(), tx),
+        cpal::SampleFormat::I16 => sender_stream::<i16>(device, &config.into(), tx),
+        cpal::SampleFormat::U16 => sender_stream::<u16>(device, &config.into(), tx),
+        cpal::SampleFormat::U8  => sender_stream::<u8>(device, &config.into(), tx),
+        other => return Err(AppError::Audio(format!("Unsupported sample format: {:?}", other))),
+    }?;
+    stream.play().map_err(|e| AppError::Audio(format!("Failed to start input stream: {}", e)))?;
+    if let Some(name) = pulse_source {
+        route_pulse_capture(name)?;
+    }
+
+    let mut sink = Sink::connect(addr)?;
+    eprintln!("Sending {} ({} Hz, {} ch) to {}", source.name, sample_rate, channels, addr);
+
+    let samples_per_frame = (NETWORK_MAX_PAYLOAD - HEADER_LEN) / 2 / channels as usize * channels as usize;
+    let mut pending: Vec<f32> = Vec::new();
+    let mut sequence = 0u32;
+    let mut frame = Vec::with_capacity(NETWORK_MAX_PAYLOAD);
+    for chunk in rx {
+        pending.extend_from_slice(&chunk);
+        while pending.len() >= samples_per_frame {
+            frame.clear();
+            Header {
+                format: FORMAT_I16,
+                channels,
+                sample_rate,
+                sequence,
+                payload_len: (samples_per_frame * 2) as u32,
+            }
+            .write(&mut frame);
+            for &sample in &pending[..samples_per_frame] {
+                let value = (sample.clamp(-1.0, 1.0) * 32767.0) as i16;
+                frame.extend_from_slice(&value.to_le_bytes());
+            }
+            sink.send(&frame);
+            pending.drain(..samples_per_frame);
+            sequence = sequence.wrapping_add(1);
+        }
+    }
+    Err(AppError::Audio("Input stream ended".to_string()))
+}
+
+fn sender_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    tx: mpsc::Sender<Vec<f32>>,
+) -> AppResult<cpal::Stream>
+where
+    T: cpal::SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    Ok(device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let _ = tx.send(data.iter().map(|&s| s.to_sample::<f32>()).collect());
+        },
+        |err| eprintln!("Audio error: {}", err),
+        None,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(sequence: u32, payload_len: u32) -> Header {
+        Header { format: FORMAT_I16, channels: 1, sample_rate: 1000, sequence, payload_len }
+    }
+
+    fn bytes(header: &Header) -> Vec<u8> {
+        let mut out = Vec::new();
+        header.write(&mut out);
+        out
+    }
+
+    #[test]
+    fn header_round_trips() {
+        let header = Header { format: FORMAT_F32, channels: 2, sample_rate: 48000, sequence: u32::MAX, payload_len: 1376 };
+        let out = bytes(&header);
+        assert_eq!(out.len(), HEADER_LEN);
+        assert_eq!(Header::parse(&out), Ok(header));
+    }
+
+    #[test]
+    fn bad_headers_are_rejected() {
+        let good = bytes(&header(0, 64));
+        let corrupt = |at: usize, value: u8| {
+            let mut out = good.clone();
+            out[at] = value;
+            out
+        };
+        assert!(Header::parse(&good[..HEADER_LEN - 1]).is_err());
+        assert!(Header::parse(&corrupt(0, b'X')).is_err());
+        assert!(Header::parse(&corrupt(4, VERSION + 1)).is_err());
+        assert!(Header::parse(&corrupt(5, 7)).is_err());
+        assert!(Header::parse(&corrupt(6, 0)).is_err());
+        assert!(Header::parse(&corrupt(6, MAX_CHANNELS + 1)).is_err());
+        assert!(Header::parse(&bytes(&Header { sample_rate: 0, ..header(0, 64) })).is_err());
+        assert!(Header::parse(&bytes(&header(0, 63))).is_err());
+    }
+
+    #[test]
+    fn oversized_payloads_are_rejected() {
+        let largest = (NETWORK_MAX_PAYLOAD - HEADER_LEN) as u32 & !1;
+        assert!(Header::parse(&bytes(&header(0, largest))).is_ok());
+        assert!(Header::parse(&bytes(&header(0, largest + 2))).is_err());
+        assert!(Header::parse(&bytes(&header(0, u32::MAX - 1))).is_err());
+    }
+
+    #[test]
+    fn jitter_buffer_keeps_order_and_fills_gaps() {
+        // 1 kHz mono: 20 samples per frame, playout from 60 queued samples.
+        let mut jitter = JitterBuffer::new();
+        let frame = |value: f32| [value; 20];
+        jitter.push(&header(0, 40), &frame(1.0));
+        assert!(jitter.pop(10).is_empty(), "still buffering");
+        jitter.push(&header(1, 40), &frame(2.0));
+        jitter.push(&header(1, 40), &frame(9.0)); // duplicate
+        jitter.push(&header(0, 40), &frame(9.0)); // late
+        jitter.push(&header(2, 40), &frame(3.0));
+        jitter.push(&header(4, 40), &frame(5.0)); // 3 is lost
+
+        let expected: Vec<f32> = [1.0, 2.0, 3.0, 0.0, 5.0].iter().flat_map(|&v| frame(v)).collect();
+        assert_eq!(jitter.pop(100), expected);
+    }
+
+    #[test]
+    fn jitter_buffer_follows_a_restarted_sender() {
+        let mut jitter = JitterBuffer::new();
+        for sequence in 0..3 {
+            jitter.push(&header(sequence, 40), &[1.0; 20]);
+        }
+        assert_eq!(jitter.pop(60).len(), 60);
+        // Far behind the expected sequence: a new sender, not a late frame.
+        jitter.push(&header(3u32.wrapping_sub(RESYNC_DISTANCE + 10), 40), &[7.0; 20]);
+        assert_eq!(jitter.pop(20), vec![7.0; 20]);
+    }
+}
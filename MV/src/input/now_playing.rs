@@ -0,0 +1,220 @@
+//! Now-playing metadata from desktop media players
+//!
+//! On Linux (with the `mpris` feature) a background thread polls the MPRIS
+//! players on the D-Bus session bus and reports the active one: the first
+//! player that is playing, else the one followed so far while it remains,
+//! else the first paused one. Elsewhere, or without a session bus, the
+//! watcher reports itself unavailable once and stops.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+#[cfg(all(target_os = "linux", feature = "mpris"))]
+use std::time::Duration;
+
+/// How often the players are polled.
+#[cfg(all(target_os = "linux", feature = "mpris"))]
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[cfg_attr(not(all(target_os = "linux", feature = "mpris")), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackState {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+impl PlaybackState {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Playing => "Playing",
+            Self::Paused  => "Paused",
+            Self::Stopped => "Stopped",
+        }
+    }
+}
+
+/// What the active player reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackInfo {
+    /// Player name from its bus name, e.g. `spotify`.
+    pub player: String,
+    pub title: String,
+    /// Artists joined with ", ".
+    pub artist: String,
+    pub art_url: Option<String>,
+    pub state: PlaybackState,
+    /// `mpris:trackid`, or the title and artist for players without one.
+    pub track_id: String,
+}
+
+impl TrackInfo {
+    /// "Artist — Title", or whichever of the two is known.
+    pub fn display_title(&self) -> String {
+        match (self.artist.is_empty(), self.title.is_empty()) {
+            (false, false) => format!("{} — {}", self.artist, self.title),
+            (true, _)      => self.title.clone(),
+            (false, true)  => self.artist.clone(),
+        }
+    }
+}
+
+#[cfg_attr(not(all(target_os = "linux", feature = "mpris")), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum NowPlayingEvent {
+    /// Metadata or playback state of the active player changed; `None` once
+    /// no player is left.
+    Updated(Option<TrackInfo>),
+    /// The active player moved on to a different track. Not sent for the
+    /// first track seen.
+    TrackChanged(TrackInfo),
+    /// Watching stopped for good, with the reason.
+    Unavailable(String),
+}
+
+/// Follows the active media player on a background thread.
+pub struct NowPlayingWatcher {
+    events: mpsc::Receiver<NowPlayingEvent>,
+    stop: Arc<AtomicBool>,
+}
+
+impl NowPlayingWatcher {
+    pub fn start() -> Self {
+        let (tx, events) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        {
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || watch(tx, stop));
+        }
+        Self { events, stop }
+    }
+
+    /// Events since the last call, oldest first.
+    pub fn poll(&self) -> Vec<NowPlayingEvent> {
+        self.events.try_iter().collect()
+    }
+}
+
+impl Drop for NowPlayingWatcher {
+    fn drop(&mut self) {
+        // The thread notices within one poll interval; nothing waits for it.
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(not(all(target_os = "linux", feature = "mpris")))]
+fn watch(tx: mpsc::Sender<NowPlayingEvent>, _stop: Arc<AtomicBool>) {
+    let reason = if cfg!(target_os = "linux") { "built without the mpris feature" } else { "MPRIS is only available on Linux" };
+    let _ = tx.send(NowPlayingEvent::Unavailable(reason.to_string()));
+}
+
+#[cfg(all(target_os = "linux", feature = "mpris"))]
+fn watch(tx: mpsc::Sender<NowPlayingEvent>, stop: Arc<AtomicBool>) {
+    let connection = match zbus::blocking::Connection::session() {
+        Ok(connection) => connection,
+        Err(e) => {
+            let _ = tx.send(NowPlayingEvent::Unavailable(format!("no D-Bus session bus ({})", e)));
+            return;
+        }
+    };
+    let mut followed: Option<String> = None;
+    let mut last: Option<TrackInfo> = None;
+    while !stop.load(Ordering::Relaxed) {
+        let current = match mpris::active_player(&connection, &mut followed) {
+            Ok(current) => current,
+            Err(e) => {
+                let _ = tx.send(NowPlayingEvent::Unavailable(format!("session bus lost ({})", e)));
+                return;
+            }
+        };
+        if current != last {
+            let changed = match (&last, &current) {
+                (Some(last), Some(current)) => last.track_id != current.track_id,
+                _ => false,
+            };
+            if tx.send(NowPlayingEvent::Updated(current.clone())).is_err() {
+                return;
+            }
+            if let (true, Some(track)) = (changed, &current) {
+                let _ = tx.send(NowPlayingEvent::TrackChanged(track.clone()));
+            }
+            last = current;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "mpris"))]
+mod mpris {
+    use super::{PlaybackState, TrackInfo};
+    use std::collections::HashMap;
+    use zbus::blocking::{fdo::DBusProxy, Connection, Proxy};
+    use zbus::proxy::CacheProperties;
+    use zbus::zvariant::{OwnedValue, Value};
+
+    const BUS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+    const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+    const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+    /// The player to show, updating `followed` to its bus name. Players that
+    /// fail to answer are skipped; only a failing bus is an error.
+    pub(super) fn active_player(connection: &Connection, followed: &mut Option<String>) -> zbus::Result<Option<TrackInfo>> {
+        let names = DBusProxy::new(connection)?.list_names()?;
+        let players: Vec<(String, TrackInfo)> = names.iter()
+            .map(|name| name.as_str())
+            .filter(|name| name.starts_with(BUS_PREFIX))
+            .filter_map(|name| Some((name.to_string(), read_player(connection, name).ok()?)))
+            .collect();
+
+        let playing = |(name, info): &&(String, TrackInfo)| info.state == PlaybackState::Playing && Some(name) == followed.as_ref();
+        let chosen = players.iter().find(playing)
+            .or_else(|| players.iter().find(|(_, info)| info.state == PlaybackState::Playing))
+            .or_else(|| players.iter().find(|(name, _)| Some(name) == followed.as_ref()))
+            .or_else(|| players.iter().find(|(_, info)| info.state == PlaybackState::Paused))
+            .or_else(|| players.first());
+        *followed = chosen.map(|(name, _)| name.clone());
+        Ok(chosen.map(|(_, info)| info.clone()))
+    }
+
+    fn read_player(connection: &Connection, name: &str) -> zbus::Result<TrackInfo> {
+        let proxy: Proxy = zbus::blocking::proxy::Builder::new(connection)
+            .destination(name)?
+            .path(OBJECT_PATH)?
+            .interface(PLAYER_INTERFACE)?
+            .cache_properties(CacheProperties::No)
+            .build()?;
+        let state = match proxy.get_property::<String>("PlaybackStatus")?.as_str() {
+            "Playing" => PlaybackState::Playing,
+            "Paused"  => PlaybackState::Paused,
+            _         => PlaybackState::Stopped,
+        };
+        let metadata: HashMap<String, OwnedValue> = proxy.get_property("Metadata").unwrap_or_default();
+        let title = metadata.get("xesam:title").and_then(|v| text(v)).unwrap_or_default();
+        let artist = metadata.get("xesam:artist").map(|v| texts(v).join(", ")).unwrap_or_default();
+        let art_url = metadata.get("mpris:artUrl").and_then(|v| text(v)).filter(|url| !url.is_empty());
+        let track_id = metadata.get("mpris:trackid").and_then(|v| text(v))
+            .unwrap_or_else(|| format!("{}\n{}", artist, title));
+
+        let player = name.trim_start_matches(BUS_PREFIX);
+        let player = player.split(".instance").next().unwrap_or(player).to_string();
+        Ok(TrackInfo { player, title, artist, art_url, state, track_id })
+    }
+
+    /// A string or object path value.
+    fn text(value: &Value) -> Option<String> {
+        match value {
+            Value::Str(s)        => Some(s.to_string()),
+            Value::ObjectPath(p) => Some(p.to_string()),
+            Value::Value(inner)  => text(inner),
+            _ => None,
+        }
+    }
+
+    /// The strings of an array value; a single string is accepted too.
+    fn texts(value: &Value) -> Vec<String> {
+        match value {
+            Value::Array(array) => array.inner().iter().filter_map(text).collect(),
+            Value::Value(inner) => texts(inner),
+            other => text(other).into_iter().collect(),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Pointer state for shaders: position, buttons, drag and scroll
+
+use winit::event::{DeviceId, ElementState, MouseButton, MouseScrollDelta, Touch, TouchPhase, WindowEvent};
+
+/// `VisUniforms::mouse_buttons` bits.
+pub const MOUSE_LEFT: u32 = 1;
+pub const MOUSE_RIGHT: u32 = 2;
+pub const MOUSE_MIDDLE: u32 = 4;
+
+/// Pixels of smooth (touchpad) scrolling counted as one line.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// Mouse state outside egui panels, in window pixels with the origin at the
+/// top left. A drag that starts outside the panels is followed across them.
+///
+/// This is the one pointer model: the shader uniforms, Shadertoy `iMouse`,
+/// click-to-emit particles and the orbit camera are all derived from it.
+#[derive(Debug, Default)]
+pub struct PointerState {
+    pub position: [f32; 2],
+    /// Last cursor position anywhere in the window, panels included.
+    cursor: [f32; 2],
+    /// Held buttons as `MOUSE_*` bits.
+    pub buttons: u32,
+    /// Where the first held button was pressed.
+    drag_origin: Option<[f32; 2]>,
+    /// Scroll accumulated since start, in lines.
+    pub scroll: [f32; 2],
+    /// Last left click not yet taken.
+    click: Option<[f32; 2]>,
+    /// Where the left button last went down, and the last position it was
+    /// held at; Shadertoy's `iMouse` keeps both after the release.
+    left_down: Option<[f32; 2]>,
+    left_held_at: [f32; 2],
+    /// Movement with the left button held, not yet taken.
+    left_drag: [f32; 2],
+    /// Touch id standing in for the mouse.
+    finger: Option<u64>,
+}
+
+impl PointerState {
+    /// Offset from where the held buttons were pressed, zero when none is held.
+    pub fn drag(&self) -> [f32; 2] {
+        self.drag_origin.map_or([0.0; 2], |[x, y]| [self.position[0] - x, self.position[1] - y])
+    }
+
+    /// Last cursor position, over the panels or not.
+    pub fn cursor(&self) -> [f32; 2] {
+        self.cursor
+    }
+
+    /// The last left click since the previous call.
+    pub fn take_click(&mut self) -> Option<[f32; 2]> {
+        self.click.take()
+    }
+
+    /// Movement with the left button held since the previous call.
+    pub fn take_left_drag(&mut self) -> [f32; 2] {
+        std::mem::take(&mut self.left_drag)
+    }
+
+    /// Shadertoy `iMouse` for a window `height` pixels tall: xy is where the
+    /// left button is or was last held, zw where it went down, negated once
+    /// it is released; the origin is the bottom left.
+    pub fn shadertoy_mouse(&self, height: f32) -> [f32; 4] {
+        let Some([down_x, down_y]) = self.left_down else { return [0.0; 4] };
+        let [x, y] = self.left_held_at;
+        let sign = if self.buttons & MOUSE_LEFT != 0 { 1.0 } else { -1.0 };
+        [x, height - y, sign * down_x, sign * (height - down_y)]
+    }
+
+    /// Follow `event`. While `egui_has_pointer` only releases and moves of an
+    /// ongoing drag are taken.
+    pub fn handle(&mut self, event: &WindowEvent, egui_has_pointer: bool) {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = [position.x as f32, position.y as f32];
+                if self.buttons != 0 || !egui_has_pointer {
+                    if self.buttons & MOUSE_LEFT != 0 {
+                        self.left_drag[0] += self.cursor[0] - self.position[0];
+                        self.left_drag[1] += self.cursor[1] - self.position[1];
+                        self.left_held_at = self.cursor;
+                    }
+                    self.position = self.cursor;
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let bit = match button {
+                    MouseButton::Left   => MOUSE_LEFT,
+                    MouseButton::Right  => MOUSE_RIGHT,
+                    MouseButton::Middle => MOUSE_MIDDLE,
+                    _ => return,
+                };
+                match state {
+                    ElementState::Pressed if !egui_has_pointer => {
+                        if self.buttons == 0 {
+                            self.drag_origin = Some(self.position);
+                        }
+                        self.buttons |= bit;
+                        if bit == MOUSE_LEFT {
+                            self.click = Some(self.position);
+                            self.left_down = Some(self.position);
+                            self.left_held_at = self.position;
+                        }
+                    }
+                    ElementState::Released => {
+                        self.buttons &= !bit;
+                        if self.buttons == 0 {
+                            self.drag_origin = None;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } if !egui_has_pointer => {
+                let [x, y] = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => [*x, *y],
+                    MouseScrollDelta::PixelDelta(pos) => [pos.x as f32 / PIXELS_PER_LINE, pos.y as f32 / PIXELS_PER_LINE],
+                };
+                self.scroll[0] += x;
+                self.scroll[1] += y;
+            }
+            _ => {}
+        }
+    }
+
+    /// Mouse events standing in for `touch`: the first finger down moves the
+    /// cursor and holds the left button until it lifts; others are ignored.
+    pub fn touch_as_mouse(&mut self, touch: &Touch) -> Vec<WindowEvent> {
+        let moved = |device_id: DeviceId| WindowEvent::CursorMoved { device_id, position: touch.location };
+        let button = |device_id: DeviceId, state| WindowEvent::MouseInput { device_id, state, button: MouseButton::Left };
+        match touch.phase {
+            TouchPhase::Started if self.finger.is_none() => {
+                self.finger = Some(touch.id);
+                vec![moved(touch.device_id), button(touch.device_id, ElementState::Pressed)]
+            }
+            TouchPhase::Moved if self.finger == Some(touch.id) => vec![moved(touch.device_id)],
+            TouchPhase::Ended | TouchPhase::Cancelled if self.finger == Some(touch.id) => {
+                self.finger = None;
+                vec![moved(touch.device_id), button(touch.device_id, ElementState::Released)]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
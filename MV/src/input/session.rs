@@ -0,0 +1,207 @@
+//! Session recording and replay for reproducible debugging
+//!
+//! A session file starts with the magic `MVSESSION` and a version byte,
+//! followed by records of a one-byte tag, a little-endian `u32` payload
+//! length and the payload:
+//!
+//! | tag | payload                                                        |
+//! |-----|----------------------------------------------------------------|
+//! | `A` | analysis state at the start, JSON (`AnalysisSnapshot`)         |
+//! | `S` | settings in effect from the next frame on, JSON (`AppSettings`) |
+//! | `F` | one frame, see `RecordedFrame`                                 |
+//!
+//! Frames hold everything `GpuResources::update` consumes plus a digest of
+//! what it uploaded, so a replay can report the first frame that differs.
+
+use crate::common::error::{AppError, AppResult};
+use crate::common::types::VisUniforms;
+use crate::config::settings::AppSettings;
+use crate::analysis::AnalysisSnapshot;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 9] = b"MVSESSION";
+const VERSION: u8 = 3;
+
+/// Largest record accepted when reading, far above any real one; a longer
+/// length means the file is corrupt.
+const MAX_RECORD_LEN: usize = 16 << 20;
+
+const TAG_ANALYSIS: u8 = b'A';
+const TAG_SETTINGS: u8 = b'S';
+const TAG_FRAME: u8 = b'F';
+
+/// Inputs and result of one `GpuResources::update` call.
+#[derive(Clone)]
+pub struct RecordedFrame {
+    pub plugin: String,
+    pub time: f32,
+    pub beat_threshold: f32,
+    pub uniforms: VisUniforms,
+    pub scene_size: (u32, u32),
+    pub samples: Vec<f32>,
+    pub digest: u64,
+}
+
+impl RecordedFrame {
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(128 + self.samples.len() * 4);
+        out.extend_from_slice(&(self.plugin.len() as u16).to_le_bytes());
+        out.extend_from_slice(self.plugin.as_bytes());
+        out.extend_from_slice(&self.time.to_le_bytes());
+        out.extend_from_slice(&self.beat_threshold.to_le_bytes());
+        out.extend_from_slice(bytemuck::bytes_of(&self.uniforms));
+        out.extend_from_slice(&self.scene_size.0.to_le_bytes());
+        out.extend_from_slice(&self.scene_size.1.to_le_bytes());
+        out.extend_from_slice(&(self.samples.len() as u32).to_le_bytes());
+        out.extend_from_slice(bytemuck::cast_slice(&self.samples));
+        out.extend_from_slice(&self.digest.to_le_bytes());
+        out
+    }
+
+    fn decode(payload: &[u8]) -> Option<Self> {
+        let mut cursor = payload;
+        let mut take = |n: usize| -> Option<&[u8]> {
+            if cursor.len() < n {
+                return None;
+            }
+            let (head, tail) = cursor.split_at(n);
+            cursor = tail;
+            Some(head)
+        };
+        let plugin_len = u16::from_le_bytes(take(2)?.try_into().ok()?) as usize;
+        let plugin = String::from_utf8(take(plugin_len)?.to_vec()).ok()?;
+        let time = f32::from_le_bytes(take(4)?.try_into().ok()?);
+        let beat_threshold = f32::from_le_bytes(take(4)?.try_into().ok()?);
+        let uniforms = bytemuck::pod_read_unaligned(take(std::mem::size_of::<VisUniforms>())?);
+        let width = u32::from_le_bytes(take(4)?.try_into().ok()?);
+        let height = u32::from_le_bytes(take(4)?.try_into().ok()?);
+        let count = u32::from_le_bytes(take(4)?.try_into().ok()?) as usize;
+        let samples = take(count * 4)?
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let digest = u64::from_le_bytes(take(8)?.try_into().ok()?);
+        Some(Self { plugin, time, beat_threshold, uniforms, scene_size: (width, height), samples, digest })
+    }
+}
+
+/// Writes a session file as frames are rendered.
+pub struct SessionRecorder {
+    writer: BufWriter<File>,
+    pub path: PathBuf,
+    pub frames: u64,
+    last_settings: Option<AppSettings>,
+}
+
+impl SessionRecorder {
+    pub fn create(path: &Path, analysis: &AnalysisSnapshot) -> AppResult<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        let analysis = serde_json::to_vec(analysis)
+            .map_err(|e| AppError::Config(format!("Failed to encode analysis state: {}", e)))?;
+        write_record(&mut writer, TAG_ANALYSIS, &analysis)?;
+        Ok(Self { writer, path: path.to_path_buf(), frames: 0, last_settings: None })
+    }
+
+    /// Record `settings` if they changed since the last call.
+    pub fn settings(&mut self, settings: &AppSettings) -> AppResult<()> {
+        if self.last_settings.as_ref() == Some(settings) {
+            return Ok(());
+        }
+        let json = serde_json::to_vec(settings)
+            .map_err(|e| AppError::Config(format!("Failed to encode settings: {}", e)))?;
+        write_record(&mut self.writer, TAG_SETTINGS, &json)?;
+        self.last_settings = Some(settings.clone());
+        Ok(())
+    }
+
+    pub fn frame(&mut self, frame: &RecordedFrame) -> AppResult<()> {
+        write_record(&mut self.writer, TAG_FRAME, &frame.encode())?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> AppResult<PathBuf> {
+        self.writer.flush()?;
+        Ok(self.path)
+    }
+}
+
+/// A recorded event, in file order.
+pub enum SessionEvent {
+    Settings(Box<AppSettings>),
+    Frame(RecordedFrame),
+}
+
+/// Reads a session file back.
+pub struct SessionPlayer {
+    reader: BufReader<File>,
+    pub analysis: AnalysisSnapshot,
+    pub frames: u64,
+    /// First frame whose uploads differed from the recording.
+    pub first_mismatch: Option<u64>,
+}
+
+impl SessionPlayer {
+    pub fn open(path: &Path) -> AppResult<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; MAGIC.len() + 1];
+        reader.read_exact(&mut magic)?;
+        if &magic[..MAGIC.len()] != MAGIC || magic[MAGIC.len()] != VERSION {
+            return Err(AppError::Config(format!("{} is not a version {} session file", path.display(), VERSION)));
+        }
+        let analysis = match read_record(&mut reader)? {
+            Some((TAG_ANALYSIS, payload)) => serde_json::from_slice(&payload)
+                .map_err(|e| AppError::Config(format!("Bad analysis state in session: {}", e)))?,
+            _ => return Err(AppError::Config("Session file has no analysis state".to_string())),
+        };
+        Ok(Self { reader, analysis, frames: 0, first_mismatch: None })
+    }
+
+    /// The next event, or `None` at the end of the file.
+    pub fn next_event(&mut self) -> AppResult<Option<SessionEvent>> {
+        let Some((tag, payload)) = read_record(&mut self.reader)? else { return Ok(None) };
+        match tag {
+            TAG_SETTINGS => serde_json::from_slice(&payload)
+                .map(|s| Some(SessionEvent::Settings(Box::new(s))))
+                .map_err(|e| AppError::Config(format!("Bad settings in session: {}", e))),
+            TAG_FRAME => {
+                self.frames += 1;
+                RecordedFrame::decode(&payload)
+                    .map(|f| Some(SessionEvent::Frame(f)))
+                    .ok_or_else(|| AppError::Config(format!("Truncated frame {} in session", self.frames)))
+            }
+            other => Err(AppError::Config(format!("Unknown session record '{}'", other as char))),
+        }
+    }
+}
+
+fn write_record(writer: &mut impl Write, tag: u8, payload: &[u8]) -> AppResult<()> {
+    writer.write_all(&[tag])?;
+    writer.write_all(&(payload.len() as u32).to_le_bytes())?;
+    writer.write_all(payload)?;
+    Ok(())
+}
+
+/// `None` at a clean end of file; a record cut short by a crash counts as the end.
+fn read_record(reader: &mut impl Read) -> AppResult<Option<(u8, Vec<u8>)>> {
+    let mut head = [0u8; 5];
+    match reader.read_exact(&mut head) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let len = u32::from_le_bytes([head[1], head[2], head[3], head[4]]) as usize;
+    if len > MAX_RECORD_LEN {
+        return Err(AppError::Config(format!("Session record of {} bytes is too large; the file is corrupt", len)));
+    }
+    let mut payload = vec![0u8; len];
+    match reader.read_exact(&mut payload) {
+        Ok(()) => Ok(Some((head[0], payload))),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -17,6 +17,9 @@ pub enum ShortcutAction {
     ToggleSettings,
     ToggleDeviceSelector,
     ToggleShaderBrowser,
+    TogglePerfHud,
+    ToggleRecording,
+    ToggleShaderEditor,
     Exit,
 }
 
@@ -25,6 +28,7 @@ pub struct ShortcutDef {
     pub key_label:   &'static str,
     pub description: &'static str,
     pub category:    &'static str,
+    #[allow(dead_code)]
     pub action:      ShortcutAction,
 }
 
@@ -40,6 +44,9 @@ pub const SHORTCUTS: &[ShortcutDef] = &[
     ShortcutDef { key_label: "F2",          description: "Open settings",                            category: "Interface",      action: ShortcutAction::ToggleSettings },
     ShortcutDef { key_label: "F3",          description: "Select audio device",                      category: "Interface",      action: ShortcutAction::ToggleDeviceSelector },
     ShortcutDef { key_label: "F4",          description: "Open shader browser",                      category: "Interface",      action: ShortcutAction::ToggleShaderBrowser },
+    ShortcutDef { key_label: "F5",          description: "Toggle performance HUD",                   category: "Interface",      action: ShortcutAction::TogglePerfHud },
+    ShortcutDef { key_label: "F7",          description: "Edit the current shader (WGSL)",           category: "Interface",      action: ShortcutAction::ToggleShaderEditor },
+    ShortcutDef { key_label: "F6",          description: "Start / stop session recording",           category: "Application",    action: ShortcutAction::ToggleRecording },
     ShortcutDef { key_label: "Esc",         description: "Exit (or exit fullscreen)",                category: "Application",    action: ShortcutAction::Exit },
 ];
 
@@ -57,6 +64,9 @@ pub fn key_to_action(key: KeyCode) -> Option<ShortcutAction> {
         KeyCode::F2                    => Some(ShortcutAction::ToggleSettings),
         KeyCode::F3                    => Some(ShortcutAction::ToggleDeviceSelector),
         KeyCode::F4                    => Some(ShortcutAction::ToggleShaderBrowser),
+        KeyCode::F5                    => Some(ShortcutAction::TogglePerfHud),
+        KeyCode::F6                    => Some(ShortcutAction::ToggleRecording),
+        KeyCode::F7                    => Some(ShortcutAction::ToggleShaderEditor),
         KeyCode::Escape                => Some(ShortcutAction::Exit),
         _                              => None,
     }
@@ -0,0 +1,252 @@
+//! Audio hosts and the capture sources they offer
+
+use crate::common::error::{AppError, AppResult};
+use super::network::NetworkAddr;
+use cpal::traits::{DeviceTrait, HostTrait};
+
+/// Name of the PulseAudio pseudo-host.
+///
+/// cpal has no PulseAudio backend; its sources, including the monitors of
+/// output sinks, are captured through the ALSA `pulse` device, whose stream
+/// is then moved to the chosen source with `pactl`.
+#[cfg(target_os = "linux")]
+pub const PULSE_HOST: &str = "PulseAudio";
+
+/// Names of the hosts this build supports, available or not.
+pub fn host_names() -> Vec<&'static str> {
+    #[allow(unused_mut)]
+    let mut names: Vec<_> = cpal::ALL_HOSTS.iter().map(|id| id.name()).collect();
+    #[cfg(target_os = "linux")]
+    names.push(PULSE_HOST);
+    names
+}
+
+/// A capturable source: an input device, a monitor of an output or a
+/// network stream.
+pub struct AudioSource {
+    /// Name saved in settings, unique within the host.
+    pub name: String,
+    /// Name shown in the UI where it differs from `name`.
+    pub description: Option<String>,
+    /// Monitor or loopback source, i.e. captures what is played back.
+    pub monitor: bool,
+    pub(crate) kind: SourceKind,
+}
+
+pub(crate) enum SourceKind {
+    Device {
+        device: cpal::Device,
+        /// Output device captured through WASAPI loopback.
+        output: bool,
+        /// PulseAudio source the ALSA `pulse` device is routed to.
+        pulse_source: Option<String>,
+    },
+    /// PCM received over the network, see `input::network`.
+    Network(NetworkAddr),
+}
+
+impl AudioSource {
+    fn input(device: cpal::Device) -> Self {
+        let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+        let lower = name.to_lowercase();
+        Self {
+            monitor: lower.contains("monitor") || lower.contains("loopback"),
+            name,
+            description: None,
+            kind: SourceKind::Device { device, output: false, pulse_source: None },
+        }
+    }
+
+    /// Source receiving PCM on `addr`.
+    pub fn network(addr: NetworkAddr) -> Self {
+        Self {
+            name: format!("Network {}", addr),
+            description: None,
+            monitor: false,
+            kind: SourceKind::Network(addr),
+        }
+    }
+
+    /// Displayed name with a marker for monitor and network sources.
+    pub fn label(&self) -> String {
+        let name = self.description.as_deref().unwrap_or(&self.name);
+        match self.kind {
+            SourceKind::Network(_)   => format!("🌐 {}", name),
+            _ if self.monitor        => format!("🔁 {} (monitor)", name),
+            _                        => name.to_string(),
+        }
+    }
+}
+
+/// An opened audio host.
+pub struct AudioHost {
+    backend: cpal::Host,
+    #[cfg(target_os = "linux")]
+    pulse: bool,
+}
+
+impl AudioHost {
+    /// Open the host called `name` (case-insensitive), or the platform
+    /// default for `None`.
+    pub fn open(name: Option<&str>) -> AppResult<Self> {
+        let Some(name) = name else {
+            return Ok(Self::from_backend(cpal::default_host()));
+        };
+
+        #[cfg(target_os = "linux")]
+        if name.eq_ignore_ascii_case(PULSE_HOST) {
+            let host = Self { backend: cpal::default_host(), pulse: true };
+            host.pulse_device()?;
+            return Ok(host);
+        }
+
+        let Some(id) = cpal::ALL_HOSTS.iter().find(|id| id.name().eq_ignore_ascii_case(name)) else {
+            let hint = if name.eq_ignore_ascii_case("jack") { " (build with `--features jack`)" } else { "" };
+            return Err(AppError::Audio(format!(
+                "Audio host '{}' is not supported by this build{}; known hosts: {}",
+                name, hint, host_names().join(", "),
+            )));
+        };
+        cpal::host_from_id(*id)
+            .map(Self::from_backend)
+            .map_err(|_| AppError::Audio(format!("Audio host {} is not available (is its server running?)", id.name())))
+    }
+
+    fn from_backend(backend: cpal::Host) -> Self {
+        Self {
+            backend,
+            #[cfg(target_os = "linux")]
+            pulse: false,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        #[cfg(target_os = "linux")]
+        if self.pulse {
+            return PULSE_HOST;
+        }
+        self.backend.id().name()
+    }
+
+    /// Sources that offer at least one usable config.
+    pub fn sources(&self) -> AppResult<Vec<AudioSource>> {
+        #[cfg(target_os = "linux")]
+        if self.pulse {
+            return self.pulse_sources();
+        }
+
+        let devices = self.backend.input_devices()
+            .map_err(|e| AppError::Audio(format!("Failed to get input devices: {}", e)))?;
+        #[allow(unused_mut)]
+        let mut sources: Vec<_> = devices
+            .filter(|device| device.supported_input_configs().is_ok_and(|mut iter| iter.next().is_some()))
+            .map(AudioSource::input)
+            .collect();
+
+        // WASAPI captures what an output device plays through an input
+        // stream built on the output device itself.
+        #[cfg(target_os = "windows")]
+        if self.backend.id() == cpal::HostId::Wasapi {
+            let outputs = self.backend.output_devices()
+                .map_err(|e| AppError::Audio(format!("Failed to get output devices: {}", e)))?;
+            sources.extend(outputs.map(|device| AudioSource {
+                name: device.name().unwrap_or_else(|_| "Unknown".to_string()),
+                description: None,
+                monitor: true,
+                kind: SourceKind::Device { device, output: true, pulse_source: None },
+            }));
+        }
+
+        Ok(sources)
+    }
+
+    /// The host's default input, used while the preferred source is missing.
+    pub fn default_source(&self) -> Option<AudioSource> {
+        #[cfg(target_os = "linux")]
+        if self.pulse {
+            return self.pulse_device().ok().map(|device| AudioSource {
+                name: "PulseAudio default".to_string(),
+                description: None,
+                monitor: false,
+                kind: SourceKind::Device { device, output: false, pulse_source: None },
+            });
+        }
+        self.backend.default_input_device().map(AudioSource::input)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn pulse_device(&self) -> AppResult<cpal::Device> {
+        let mut devices = self.backend.input_devices()
+            .map_err(|e| AppError::Audio(format!("Failed to get input devices: {}", e)))?;
+        devices
+            .find(|d| d.name().is_ok_and(|n| n == "pulse"))
+            .ok_or_else(|| AppError::Audio(
+                "PulseAudio is not available: ALSA has no `pulse` device (install the ALSA PulseAudio plugin)".to_string(),
+            ))
+    }
+
+    /// PulseAudio sources as listed by `pactl list sources`, keyed by their
+    /// unique source name; descriptions may repeat.
+    #[cfg(target_os = "linux")]
+    fn pulse_sources(&self) -> AppResult<Vec<AudioSource>> {
+        let listing = pactl(&["list", "sources"])?;
+        let device = self.pulse_device()?;
+        let mut sources = Vec::new();
+        let mut source_name: Option<String> = None;
+        for line in listing.lines() {
+            let line = line.trim();
+            if let Some(name) = line.strip_prefix("Name: ") {
+                source_name = Some(name.to_string());
+            } else if let Some(description) = line.strip_prefix("Description: ") {
+                if let Some(name) = source_name.take() {
+                    sources.push(AudioSource {
+                        monitor: name.ends_with(".monitor"),
+                        description: Some(description.to_string()),
+                        name: name.clone(),
+                        kind: SourceKind::Device { device: device.clone(), output: false, pulse_source: Some(name) },
+                    });
+                }
+            }
+        }
+        Ok(sources)
+    }
+}
+
+/// Move this process's newest PulseAudio capture stream to `source`.
+///
+/// The ALSA `pulse` device always records from the default source, so a
+/// stream opened on it is routed afterwards, as `pactl move-source-output`
+/// would from a shell.
+pub(crate) fn route_pulse_capture(source: &str) -> AppResult<()> {
+    let pid = std::process::id().to_string();
+    let mut newest: Option<u32> = None;
+    let mut current: Option<u32> = None;
+    for line in pactl(&["list", "source-outputs"])?.lines() {
+        let line = line.trim();
+        if let Some(index) = line.strip_prefix("Source Output #") {
+            current = index.parse().ok();
+        } else if let Some(value) = line.strip_prefix("application.process.id = ") {
+            if value.trim_matches('"') == pid {
+                newest = newest.max(current);
+            }
+        }
+    }
+    let index = newest.ok_or_else(|| AppError::Audio("PulseAudio lists no capture stream of MV to route".to_string()))?;
+    pactl(&["move-source-output", &index.to_string(), source]).map(drop)
+}
+
+/// Run `pactl` with `args` and return its output.
+fn pactl(args: &[&str]) -> AppResult<String> {
+    let output = std::process::Command::new("pactl")
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .map_err(|e| AppError::Audio(format!("PulseAudio is not available: cannot run pactl: {}", e)))?;
+    if !output.status.success() {
+        return Err(AppError::Audio(format!(
+            "PulseAudio is not available: {}",
+            String::from_utf8_lossy(&output.stderr).trim(),
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
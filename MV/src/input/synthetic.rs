@@ -0,0 +1,113 @@
+//! Synthetic audio for attract mode: pink noise, a chord that pulses on
+//! the beat and a built-in drum-and-bass loop
+//!
+//! Every sample is computed from its index alone, so any stretch of the
+//! signal can be produced without keeping state between frames.
+
+use crate::analysis::NOMINAL_SAMPLE_RATE;
+use crate::config::settings::SyntheticSignal;
+use std::f32::consts::TAU;
+
+/// Output level, about that of music on a line input with some headroom.
+const LEVEL: f32 = 0.3;
+
+/// Tempo of the tone pulses and the loop.
+const BPM: f64 = 120.0;
+
+/// Sixteenth-note steps in the loop.
+const LOOP_STEPS: u64 = 32;
+
+/// Bass note of each eighth of the loop, in semitones above A1 (55 Hz).
+const BASS_LINE: [i32; 16] = [0, 0, 12, 0, 3, 3, 15, 3, 5, 5, 17, 5, 7, 7, 10, 12];
+
+/// Chord roots of the tone signal, in semitones above A2 (110 Hz), a bar each.
+const CHORDS: [i32; 4] = [0, -4, 3, -2];
+
+/// Fill `out` with the samples of `signal` ending at `time` seconds.
+pub fn synthesize(signal: SyntheticSignal, time: f64, out: &mut [f32]) {
+    let end = (time * NOMINAL_SAMPLE_RATE as f64) as u64;
+    let start = end.saturating_sub(out.len() as u64);
+    for (i, sample) in out.iter_mut().enumerate() {
+        let n = start + i as u64;
+        *sample = LEVEL * match signal {
+            SyntheticSignal::Noise => noise(n),
+            SyntheticSignal::Tones => tones(n),
+            SyntheticSignal::Loop  => drum_loop(n),
+        };
+    }
+}
+
+/// Pink-ish noise breathing slowly in level.
+fn noise(n: u64) -> f32 {
+    let t = seconds(n);
+    // Voss-McCartney: octaves of sample-and-hold white noise.
+    let pink: f32 = (0..8).map(|octave| hash(n >> octave, octave)).sum::<f32>() / 8.0;
+    let swell = 0.6 + 0.4 * (TAU * 0.1 * t).sin();
+    pink * swell
+}
+
+/// A major triad changing every bar, pulsing on each beat.
+fn tones(n: u64) -> f32 {
+    let beats = beats(n);
+    let root = CHORDS[(beats / 4.0) as usize % CHORDS.len()];
+    let t = seconds(n);
+    let pulse = 0.3 + 0.7 * (-4.0 * beats.fract() as f32).exp();
+    let chord: f32 = [0, 4, 7].iter()
+        .map(|interval| (TAU * note(110.0, root + interval) * t).sin())
+        .sum();
+    chord / 3.0 * pulse
+}
+
+/// Kick on every beat, snare on two and four, off-beat hats and a bass line.
+fn drum_loop(n: u64) -> f32 {
+    let sixteenths = beats(n) * 4.0;
+    let step = sixteenths as u64 % LOOP_STEPS;
+    let since = (sixteenths.fract() * 60.0 / BPM / 4.0) as f32;
+    let since_beat = since + (step % 4) as f32 * (60.0 / BPM / 4.0) as f32;
+
+    let kick = {
+        let phase = TAU * (45.0 * since_beat + 12.0 * (1.0 - (-30.0 * since_beat).exp()));
+        phase.sin() * (-7.0 * since_beat).exp()
+    };
+    let snare = if step % 8 == 4 {
+        (hash(n, 9) * 0.7 + (TAU * 180.0 * since).sin() * 0.3) * (-18.0 * since).exp()
+    } else {
+        0.0
+    };
+    let hat = if step % 2 == 1 { (hash(n, 10) - hash(n + 1, 10)) * 0.5 * (-60.0 * since).exp() } else { 0.0 };
+    let bass = {
+        let eighth = (step / 2) as usize % BASS_LINE.len();
+        let freq = note(55.0, BASS_LINE[eighth]);
+        let saw = 2.0 * (freq * seconds(n)).fract() - 1.0;
+        let since_eighth = since + (step % 2) as f32 * (60.0 / BPM / 4.0) as f32;
+        saw * (-5.0 * since_eighth).exp()
+    };
+    kick * 0.45 + snare * 0.25 + hat * 0.12 + bass * 0.18
+}
+
+fn seconds(n: u64) -> f32 {
+    // Wrapped to keep f32 phase precision over long runs; a multiple of the
+    // loop length so the rhythm does not skip.
+    const PERIOD: u64 = NOMINAL_SAMPLE_RATE as u64 * 64;
+    (n % PERIOD) as f32 / NOMINAL_SAMPLE_RATE as f32
+}
+
+/// Beats elapsed at sample `n`.
+fn beats(n: u64) -> f64 {
+    n as f64 / NOMINAL_SAMPLE_RATE as f64 * BPM / 60.0
+}
+
+/// Frequency `semitones` above `base` Hz.
+fn note(base: f32, semitones: i32) -> f32 {
+    base * 2f32.powf(semitones as f32 / 12.0)
+}
+
+/// Deterministic white noise in -1..1 for sample `n` of stream `stream`.
+fn hash(n: u64, stream: u64) -> f32 {
+    // splitmix64
+    let mut z = n.wrapping_add(stream.wrapping_mul(0x9E37_79B9_7F4A_7C15)).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+}
@@ -1,5 +1,6 @@
 //! Music Visualizer - Main entry point
 
+mod analysis;
 mod app;
 mod input;
 mod common;
@@ -8,32 +9,97 @@ mod graphics;
 mod visualization;
 mod config;
 
+use crate::analysis::export::{analyze_file, ExportFormat};
+use crate::analysis::AnalysisSettings;
 use crate::app::App;
 use crate::common::error::{AppError, AppResult};
+use crate::config::cli::{CliArgs, USAGE};
 use crate::config::settings::AppSettings;
+use crate::input::network::{send_audio, NetworkAddr};
+use crate::input::session::SessionPlayer;
+use crate::input::sources::{host_names, AudioHost, AudioSource};
 use winit::event_loop::EventLoop;
-use cpal::traits::{DeviceTrait, HostTrait};
 
-fn main() -> AppResult<()> {
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> AppResult<()> {
+    let cli = CliArgs::parse()?;
+    if cli.help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    if cli.list_hosts {
+        for name in host_names() {
+            match AudioHost::open(Some(name)) {
+                Ok(_) => println!("{}", name),
+                Err(e) => println!("{} (unavailable: {})", name, e),
+            }
+        }
+        return Ok(());
+    }
+
     // Load settings
-    let settings = AppSettings::load();
+    let mut settings = AppSettings::load();
 
-    // Get audio input devices
-    let host = cpal::default_host();
-    let all_devices: Vec<_> = host.input_devices().map_err(|e| AppError::Audio(format!("Failed to get input devices: {}", e)))?.collect();
+    if let Some(input) = &cli.analyze {
+        let format = match (&cli.format, &cli.output) {
+            (Some(name), _)    => ExportFormat::parse(name)?,
+            (None, Some(path)) => ExportFormat::for_path(path),
+            (None, None)       => ExportFormat::Csv,
+        };
+        let analysis = AnalysisSettings {
+            smoothing: settings.smoothing_factor,
+            gain: settings.gain,
+            beat_threshold: settings.beat_sensitivity.threshold(),
+            ..AnalysisSettings::default()
+        };
+        return analyze_file(input, cli.output.as_deref(), format, cli.fps.unwrap_or(60.0), analysis);
+    }
 
-    // Filter devices that support input configs
-    let devices: Vec<_> = all_devices.into_iter()
-        .filter(|device| device.supported_input_configs().map_or(false, |mut iter| iter.next().is_some()))
-        .collect();
+    // A host named on the command line must open; a saved one that has gone
+    // away falls back to the default host.
+    let host = if let Some(name) = &cli.host {
+        let host = AudioHost::open(Some(name))?;
+        settings.audio_host = Some(host.name().to_string());
+        settings.save().ok();
+        host
+    } else {
+        AudioHost::open(settings.audio_host.as_deref()).or_else(|e| {
+            eprintln!("{}; using the default host", e);
+            AudioHost::open(None)
+        })?
+    };
 
-    if devices.is_empty() {
-        return Err(AppError::Audio("No input devices available".to_string()));
+    if let Some(url) = &cli.send_audio {
+        let addr = NetworkAddr::parse(url)?;
+        let sources = host.sources()?;
+        let source = settings.selected_device.as_ref()
+            .and_then(|name| sources.into_iter().find(|s| &s.name == name))
+            .or_else(|| host.default_source())
+            .ok_or_else(|| AppError::Audio("No input device to send".to_string()))?;
+        return send_audio(&source, &addr);
     }
 
-    // Create event loop and app
+    if let Some(url) = &cli.listen {
+        let addr = NetworkAddr::parse(url)?;
+        settings.network_input = Some(addr.to_string());
+        settings.selected_device = Some(AudioSource::network(addr).name);
+        settings.save().ok();
+    }
+
+    // Create event loop and app; without input devices MV starts in silence
+    // and keeps rescanning
     let event_loop = EventLoop::builder().build().map_err(|e| AppError::Config(format!("Failed to create event loop: {:?}", e)))?;
-    let mut app = App::new(devices, settings);
+    let mut app = App::new(host, settings);
+    if let Some(path) = &cli.replay {
+        app.start_replay(SessionPlayer::open(path)?);
+    }
+    app.state.pending_record = cli.record;
 
     // Run the application
     event_loop.run_app(&mut app).map_err(|e| AppError::Config(format!("Failed to run app: {:?}", e)))?;
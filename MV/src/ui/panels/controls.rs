@@ -10,7 +10,11 @@ impl App {
             .map(|p| p.name.clone())
             .unwrap_or_default();
 
-        let device_name = self.state.settings.selected_device.as_deref().unwrap_or("None");
+        let device_name = match &self.state.audio {
+            Some(audio) => audio.device_name.clone(),
+            None => "None".to_string(),
+        };
+        let fallback_note = self.fallback_note();
 
         egui::Window::new("ℹ Controls")
             .open(&mut self.state.show_info)
@@ -24,7 +28,7 @@ impl App {
                 });
                 ui.horizontal(|ui| {
                     ui.label(egui::RichText::new("🎤").size(14.0));
-                    ui.label(device_name);
+                    ui.label(&device_name);
                     ui.separator();
                     ui.label(egui::RichText::new("🪟").size(14.0));
                     ui.label(self.state.window_mode.label());
@@ -32,6 +36,25 @@ impl App {
                     ui.label(egui::RichText::new("🥁").size(14.0));
                     ui.label(self.state.settings.beat_sensitivity.label());
                 });
+                if let Some(track) = &self.state.now_playing {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new("🎵").size(14.0));
+                        ui.label(track.display_title());
+                        ui.label(egui::RichText::new(format!("{} · {}", track.player, track.state.label()))
+                            .small().color(egui::Color32::GRAY));
+                    });
+                    if let Some(url) = &track.art_url {
+                        ui.label(egui::RichText::new(format!("Art: {}", url)).small().color(egui::Color32::GRAY));
+                    }
+                } else if let Some(note) = &self.state.now_playing_note {
+                    ui.label(egui::RichText::new(format!("🎵 Now playing unavailable: {}", note)).small().color(egui::Color32::GRAY));
+                }
+                if let Some(note) = &fallback_note {
+                    ui.colored_label(egui::Color32::from_rgb(255, 200, 60), note);
+                }
+                if let Some(note) = &self.state.window_mode_note {
+                    ui.colored_label(egui::Color32::from_rgb(255, 200, 60), format!("{}: {}", self.state.window_mode.label(), note));
+                }
                 ui.separator();
 
                 egui::Grid::new("shortcuts_grid")
@@ -1,11 +1,16 @@
 //! Audio device selector panel (F3)
 
 use crate::app::App;
-use cpal::traits::DeviceTrait;
+use crate::input::network::NetworkAddr;
+use crate::input::sources::host_names;
 
 impl App {
     pub fn show_device_selector(&mut self, ctx: &egui::Context) {
         let mut pending = self.state.pending_device_index;
+        let mut refresh = false;
+        let mut host = self.state.host.name();
+        let mut network_input = None;
+        let fallback_note = self.fallback_note();
 
         egui::Window::new("🎤 Audio Device")
             .open(&mut self.state.show_device_selection)
@@ -13,17 +18,74 @@ impl App {
             .collapsible(false)
             .frame(egui::Frame::window(&ctx.style()).shadow(egui::epaint::Shadow::NONE))
             .show(ctx, |ui| {
-                ui.label("Choose an audio input device:");
+                ui.horizontal(|ui| {
+                    ui.label("Host:");
+                    egui::ComboBox::from_id_source("audio_host")
+                        .selected_text(host)
+                        .show_ui(ui, |ui| {
+                            for name in host_names() {
+                                ui.selectable_value(&mut host, name, name);
+                            }
+                        });
+                });
+                if let Some(error) = &self.state.host_error {
+                    ui.colored_label(egui::Color32::from_rgb(255, 80, 80), error);
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Choose an audio input device:");
+                    if ui.small_button("⟳ Refresh").clicked() {
+                        refresh = true;
+                    }
+                });
+                if let Some(note) = &fallback_note {
+                    ui.colored_label(egui::Color32::from_rgb(255, 200, 60), note);
+                }
                 ui.separator();
-                for (i, device) in self.state.devices.iter().enumerate() {
-                    let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
-                    let is_selected = self.state.settings.selected_device.as_ref() == Some(&name);
-                    if ui.selectable_label(is_selected, &name).clicked() {
+                if self.state.devices.is_empty() {
+                    ui.label("No input devices found.");
+                }
+                for (i, source) in self.state.devices.iter().enumerate() {
+                    let is_selected = self.state.settings.selected_device.as_ref() == Some(&source.name);
+                    if ui.selectable_label(is_selected, source.label()).clicked() {
                         pending = Some(i);
                     }
                 }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("🌐 Listen on:");
+                    ui.add(egui::TextEdit::singleline(&mut self.state.network_input_edit)
+                        .hint_text("udp://0.0.0.0:7878")
+                        .desired_width(180.0));
+                    let edit = self.state.network_input_edit.trim();
+                    let parsed = (!edit.is_empty()).then(|| NetworkAddr::parse(edit));
+                    let valid = !matches!(parsed, Some(Err(_)));
+                    if ui.add_enabled(valid, egui::Button::new("Apply")).clicked() {
+                        network_input = Some(parsed.and_then(Result::ok).map(|addr| addr.to_string()));
+                    }
+                });
+                ui.label(egui::RichText::new("Receives PCM sent with `mv --send-audio`; leave empty to disable.").small().weak());
             });
 
         self.state.pending_device_index = pending;
+        self.state.refresh_devices_requested |= refresh;
+        if let Some(network_input) = network_input {
+            self.state.settings.network_input = network_input;
+            self.save_settings();
+            self.state.refresh_devices_requested = true;
+        }
+        if host != self.state.host.name() {
+            self.state.pending_host = Some(host.to_string());
+        }
+    }
+
+    /// Status line while the preferred device is unavailable.
+    pub(crate) fn fallback_note(&self) -> Option<String> {
+        if !self.state.audio_fallback {
+            return None;
+        }
+        let preferred = self.state.settings.selected_device.as_deref().unwrap_or("Device");
+        let current = self.state.audio.as_ref().map_or("silence", |a| a.device_name.as_str());
+        Some(format!("⚠ {} unavailable, using {}", preferred, current))
     }
 }
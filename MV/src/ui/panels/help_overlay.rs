@@ -1,6 +1,6 @@
 //! Status overlay (bottom-left)
 
-use crate::app::{App, state::WindowMode};
+use crate::app::{App, WindowMode};
 
 impl App {
     pub fn show_status_overlay(&mut self, ctx: &egui::Context) {
@@ -9,7 +9,7 @@ impl App {
             .map(|p| p.name.clone())
             .unwrap_or_default();
 
-        let beat_intensity = self.state.gpu.as_ref().map(|g| g.beat_intensity).unwrap_or(0.0);
+        let beat_intensity = self.state.gpu.as_ref().map(|g| g.analyzer.beat_intensity()).unwrap_or(0.0);
 
         egui::Window::new("##status")
             .title_bar(false)
@@ -59,6 +59,16 @@ impl App {
                         egui::Color32::from_rgb(80, 200, 80)
                     };
                     ui.label(egui::RichText::new(bar).monospace().size(11.0).color(beat_color));
+
+                    let session = match (&self.state.recorder, &self.state.replay) {
+                        (Some(recorder), _) => Some(("⏺ REC", recorder.frames, egui::Color32::from_rgb(255, 80, 80))),
+                        (None, Some(replay)) => Some(("▶ REPLAY", replay.frames(), egui::Color32::from_rgb(100, 180, 255))),
+                        (None, None) => None,
+                    };
+                    if let Some((label, frames, color)) = session {
+                        ui.label(egui::RichText::new("│").size(11.0).color(egui::Color32::DARK_GRAY));
+                        ui.label(egui::RichText::new(format!("{} {}", label, frames)).monospace().size(11.0).color(color));
+                    }
                 });
             });
     }
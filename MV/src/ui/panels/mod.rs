@@ -3,5 +3,7 @@
 pub mod controls;
 pub mod settings;
 pub mod shader_browser;
+pub mod shader_editor;
 pub mod help_overlay;
+pub mod perf_hud;
 pub mod device_selector;
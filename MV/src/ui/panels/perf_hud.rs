@@ -0,0 +1,92 @@
+//! Performance HUD (F5, top-right)
+
+use crate::app::App;
+use crate::config::settings::QualityPolicy;
+
+impl App {
+    pub fn show_perf_hud(&self, ctx: &egui::Context) {
+        if !self.state.settings.performance.show_hud {
+            return;
+        }
+        let stats = &self.state.frame_stats;
+        let perf = &self.state.settings.performance;
+        let gpu_timer = self.state.gpu.as_ref().and_then(|g| g.gpu_timer.as_ref());
+        let scene_size = self.state.gpu.as_ref().map(|g| (g.render_scale, g.scene_size()));
+
+        egui::Window::new("##perf_hud")
+            .title_bar(false)
+            .resizable(false)
+            .collapsible(false)
+            .auto_sized()
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-8.0, 8.0))
+            .frame(
+                egui::Frame::none()
+                    .fill(egui::Color32::from_black_alpha(150))
+                    .inner_margin(egui::Margin::same(6.0))
+                    .rounding(egui::Rounding::same(4.0)),
+            )
+            .show(ctx, |ui| {
+                let text = |s: String| egui::RichText::new(s).monospace().size(11.0);
+                ui.label(text(format!("{:5.1} fps   CPU {:5.2} ms", stats.fps(), stats.cpu_ms)));
+                match gpu_timer {
+                    Some(timer) => match timer.last {
+                        Some(t) => ui.label(text(format!(
+                            "GPU {:5.2} ms (scene {:.2} · post {:.2})",
+                            t.total_ms(), t.scene_ms, t.post_ms,
+                        ))),
+                        None => ui.label(text("GPU  measuring…".to_string())),
+                    },
+                    None => ui.label(text("GPU  n/a (no timestamp queries)".to_string())),
+                };
+                if let Some((scale, (w, h))) = scene_size {
+                    ui.label(text(format!("scene {w}×{h} ({:.0}%)", scale * 100.0)));
+                }
+                ui.label(text(format!(
+                    "p50 {:5.1}  p95 {:5.1}  p99 {:5.1} ms",
+                    stats.percentile(50.0), stats.percentile(95.0), stats.percentile(99.0),
+                )));
+
+                let over = stats.over_budget();
+                let (status, color) = match (over, perf.policy) {
+                    (false, _)                             => ("within budget", egui::Color32::from_rgb(80, 200, 80)),
+                    (true, QualityPolicy::Off)             => ("over budget", egui::Color32::from_rgb(255, 80, 80)),
+                    (true, QualityPolicy::SkipHeavy)       => ("over budget · skipping Heavy", egui::Color32::from_rgb(255, 200, 60)),
+                    (true, QualityPolicy::LowerResolution) => ("over budget · lowering resolution", egui::Color32::from_rgb(255, 200, 60)),
+                };
+                ui.label(text(format!(
+                    "budget {:.1} ms · work {:.1} ms: {status}",
+                    perf.frame_budget_ms, stats.work_percentile(50.0),
+                )).color(color));
+
+                frame_time_graph(ui, stats.intervals(), perf.frame_budget_ms);
+            });
+    }
+}
+
+/// Bar graph of recent frame times with the budget drawn as a line.
+fn frame_time_graph(ui: &mut egui::Ui, intervals: impl ExactSizeIterator<Item = f32>, budget_ms: f32) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(220.0, 40.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, egui::Color32::from_black_alpha(100));
+
+    // Scale so twice the budget fills the graph.
+    let scale = rect.height() / (budget_ms * 2.0);
+    let count = intervals.len().max(1);
+    let bar_width = rect.width() / count as f32;
+    for (i, ms) in intervals.enumerate() {
+        let height = (ms * scale).min(rect.height());
+        let x = rect.left() + i as f32 * bar_width;
+        let color = if ms > budget_ms {
+            egui::Color32::from_rgb(255, 80, 80)
+        } else {
+            egui::Color32::from_rgb(80, 200, 80)
+        };
+        painter.rect_filled(
+            egui::Rect::from_min_max(egui::pos2(x, rect.bottom() - height), egui::pos2(x + bar_width, rect.bottom())),
+            0.0,
+            color,
+        );
+    }
+    let budget_y = rect.bottom() - budget_ms * scale;
+    painter.hline(rect.x_range(), budget_y, egui::Stroke::new(1.0, egui::Color32::from_gray(200)));
+}
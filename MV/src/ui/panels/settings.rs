@@ -1,7 +1,9 @@
 //! Settings panel (F2)
 
 use crate::app::{App, build_plugin_groups};
-use crate::config::settings::{BeatSensitivity, ColorScheme};
+use crate::config::constants::{MAX_AVOID_RECENT, MAX_FPS_CAP, MAX_IDLE_FPS, MAX_PARTICLE_COUNT, MIN_RENDER_SCALE};
+use crate::config::settings::{BeatSensitivity, ColorScheme, ParticleBlend, ParticleColor, ParticleEmitter, PresentMode, QualityPolicy, SwitchOrder, SyntheticSignal, TextAnchor, TextStyle};
+use crate::visualization::ShaderCategory;
 
 impl App {
     pub fn show_settings_panel(&self, ctx: &egui::Context, settings_copy: &mut crate::config::settings::AppSettings) {
@@ -11,6 +13,9 @@ impl App {
                 .unwrap_or_default();
             build_plugin_groups(&names)
         };
+        let current_plugin = self.state.gpu.as_ref()
+            .and_then(|g| g.plugins.get(self.state.current_plugin_index))
+            .map(|p| p.name.clone());
 
         egui::Window::new("⚙ Settings")
             .open(&mut settings_copy.show_settings)
@@ -41,13 +46,265 @@ impl App {
                     });
                 });
 
+                ui.collapsing("🌈 Post Effects", |ui| {
+                    let fx = &mut settings_copy.post_fx;
+                    ui.checkbox(&mut fx.bloom_enabled, "Bloom");
+                    if fx.bloom_enabled {
+                        ui.add(egui::Slider::new(&mut fx.bloom_threshold, 0.0..=2.0).text("Threshold"));
+                        ui.add(egui::Slider::new(&mut fx.bloom_intensity, 0.0..=3.0).text("Intensity"));
+                    }
+                    ui.checkbox(&mut fx.trails_enabled, "Motion trails");
+                    if fx.trails_enabled {
+                        ui.add(egui::Slider::new(&mut fx.trails_decay, 0.0..=0.98).text("Decay"));
+                    }
+                    ui.checkbox(&mut fx.chromatic_enabled, "Chromatic aberration");
+                    if fx.chromatic_enabled {
+                        ui.add(egui::Slider::new(&mut fx.chromatic_amount, 0.0..=0.02).text("Amount"));
+                    }
+                    ui.checkbox(&mut fx.vignette_enabled, "Vignette");
+                    if fx.vignette_enabled {
+                        ui.add(egui::Slider::new(&mut fx.vignette_strength, 0.0..=1.0).text("Strength"));
+                    }
+                    ui.checkbox(&mut fx.grain_enabled, "Film grain");
+                    if fx.grain_enabled {
+                        ui.add(egui::Slider::new(&mut fx.grain_amount, 0.0..=0.3).text("Amount"));
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut fx.beat_reactive, "Pulse bloom and aberration on beats");
+                });
+
+                ui.collapsing("💫 Particles", |ui| {
+                    let particles = &mut settings_copy.particles;
+                    ui.checkbox(&mut particles.enabled, "Show particle overlay");
+                    if !particles.enabled {
+                        return;
+                    }
+
+                    // Per-visualization switches apply to the one on screen.
+                    if let Some(name) = &current_plugin {
+                        let mut shown = !particles.hidden_for.contains(name);
+                        if ui.checkbox(&mut shown, format!("Show over {name}")).changed() {
+                            if shown {
+                                particles.hidden_for.remove(name);
+                            } else {
+                                particles.hidden_for.insert(name.clone());
+                            }
+                        }
+                        let mut custom = particles.overrides.contains_key(name);
+                        if ui.checkbox(&mut custom, format!("Custom style for {name}")).changed() {
+                            if custom {
+                                particles.overrides.insert(name.clone(), particles.style);
+                            } else {
+                                particles.overrides.remove(name);
+                            }
+                        }
+                    }
+                    ui.separator();
+
+                    let style = match current_plugin.as_ref().and_then(|n| particles.overrides.get_mut(n)) {
+                        Some(style) => style,
+                        None => &mut particles.style,
+                    };
+                    ui.add(egui::Slider::new(&mut style.count, 64..=MAX_PARTICLE_COUNT).logarithmic(true).text("Count"));
+                    ui.add(egui::Slider::new(&mut style.lifetime, 0.5..=10.0).text("Lifetime (s)"));
+                    ui.add(egui::Slider::new(&mut style.gravity, -3.0..=3.0).text("Gravity"));
+                    ui.horizontal(|ui| {
+                        ui.label("Emitter:");
+                        for emitter in ParticleEmitter::ALL {
+                            ui.selectable_value(&mut style.emitter, emitter, emitter.label());
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Colour:");
+                        for color in ParticleColor::ALL {
+                            ui.selectable_value(&mut style.color, color, color.label());
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Blend:");
+                        ui.selectable_value(&mut style.blend, ParticleBlend::Alpha,    "Alpha");
+                        ui.selectable_value(&mut style.blend, ParticleBlend::Additive, "Additive");
+                    });
+                    ui.checkbox(&mut style.beat_bursts, "Burst on beats");
+                    if style.beat_bursts {
+                        ui.add(egui::Slider::new(&mut style.burst_fraction, 0.0..=1.0).text("Burst size"));
+                    }
+                });
+
+                ui.collapsing("🔤 Text Overlay", |ui| {
+                    let text = &mut settings_copy.text;
+                    ui.horizontal(|ui| {
+                        ui.label("Caption:");
+                        ui.text_edit_singleline(&mut text.caption);
+                    });
+                    text_style_controls(ui, "Caption", &mut text.caption_style);
+                    text_style_controls(ui, "Track title", &mut text.track_style);
+                    text_style_controls(ui, "Clock", &mut text.clock_style);
+                    if text.clock_style.enabled {
+                        ui.checkbox(&mut text.clock_seconds, "Clock shows seconds");
+                    }
+                    ui.separator();
+                    ui.add(egui::Slider::new(&mut text.fade_seconds, 0.0..=5.0).text("Fade (s)"));
+                    ui.horizontal(|ui| {
+                        ui.label("Colour:");
+                        ui.color_edit_button_rgb(&mut text.color);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Font:");
+                        let mut path = text.font.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
+                        let edit = egui::TextEdit::singleline(&mut path).hint_text("bundled DK Hand");
+                        if ui.add(edit).changed() {
+                            text.font = (!path.trim().is_empty()).then(|| path.trim().into());
+                        }
+                    });
+                    if let Some(err) = &self.state.text_font_error {
+                        ui.colored_label(egui::Color32::from_rgb(230, 120, 80), err);
+                    }
+                });
+
                 ui.collapsing("▶ Playback", |ui| {
                     ui.checkbox(&mut settings_copy.auto_switch_modes, "Auto-switch modes");
                     if settings_copy.auto_switch_modes {
                         ui.add(egui::Slider::new(&mut settings_copy.mode_switch_seconds, 5.0..=120.0).text("Switch interval (s)"));
+                        let auto_switch = &mut settings_copy.auto_switch;
+                        ui.horizontal(|ui| {
+                            ui.label("Order:");
+                            for order in SwitchOrder::ALL {
+                                ui.selectable_value(&mut auto_switch.order, order, order.label());
+                            }
+                        });
+                        if auto_switch.order == SwitchOrder::Shuffle {
+                            ui.add(egui::Slider::new(&mut auto_switch.avoid_recent, 0..=MAX_AVOID_RECENT).text("Avoid last N"));
+                            ui.label(egui::RichText::new("Higher ratings and favorites come up more often.").small().weak());
+                        }
+                        ui.label("Categories (none ticked = all):");
+                        ui.horizontal_wrapped(|ui| {
+                            for category in ShaderCategory::ALL {
+                                let mut on = auto_switch.categories.contains(&category);
+                                if ui.checkbox(&mut on, category.label()).changed() {
+                                    if on {
+                                        auto_switch.categories.insert(category);
+                                    } else {
+                                        auto_switch.categories.remove(&category);
+                                    }
+                                }
+                            }
+                        });
+                    }
+                    ui.separator();
+                    let now_playing = &mut settings_copy.now_playing;
+                    ui.checkbox(&mut now_playing.enabled, "Follow the media player (MPRIS)");
+                    if now_playing.enabled {
+                        ui.checkbox(&mut now_playing.switch_on_track_change, "Switch visualization on track change");
+                        if let Some(note) = &self.state.now_playing_note {
+                            ui.colored_label(egui::Color32::from_rgb(230, 120, 80), note);
+                        }
                     }
                 });
 
+                ui.collapsing("🌙 Attract Mode", |ui| {
+                    let attract = &mut settings_copy.attract;
+                    ui.checkbox(&mut attract.enabled, "Start attract mode when nothing plays and nobody uses MV");
+                    if !attract.enabled {
+                        return;
+                    }
+                    ui.add(egui::Slider::new(&mut attract.after_seconds, 10.0..=3600.0).logarithmic(true).text("After quiet (s)"));
+                    ui.add(egui::Slider::new(&mut attract.switch_seconds, 5.0..=120.0).text("Switch interval (s)"));
+                    ui.horizontal(|ui| {
+                        ui.label("Audio:");
+                        for signal in SyntheticSignal::ALL {
+                            ui.selectable_value(&mut attract.signal, signal, signal.label());
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Caption:");
+                        ui.text_edit_singleline(&mut attract.caption)
+                            .on_hover_text("{name} becomes the visualization name");
+                    });
+                    text_style_controls(ui, "Attract caption", &mut attract.caption_style);
+                    ui.collapsing("Visualizations (none ticked = all enabled)", |ui| {
+                        for (group_name, names) in &plugin_groups {
+                            ui.label(group_name.as_str());
+                            ui.horizontal_wrapped(|ui| {
+                                for name in names {
+                                    let mut on = attract.shaders.contains(name);
+                                    if ui.checkbox(&mut on, name.as_str()).changed() {
+                                        if on {
+                                            attract.shaders.insert(name.clone());
+                                        } else {
+                                            attract.shaders.remove(name);
+                                        }
+                                    }
+                                }
+                            });
+                        }
+                    });
+                    let status = if self.state.attract.active {
+                        "Running; sound or input ends it".to_string()
+                    } else {
+                        format!("Quiet for {:.0} s", self.state.power.quiet_seconds())
+                    };
+                    ui.label(egui::RichText::new(status).small().weak());
+                });
+
+                ui.collapsing("🖱 Interaction", |ui| {
+                    let interaction = &mut settings_copy.interaction;
+                    ui.checkbox(&mut interaction.click_particles, "Click to emit particles");
+                    ui.checkbox(&mut interaction.drag_to_orbit, "Drag to orbit the 3D camera");
+                    ui.label("Shaders always see the pointer, buttons, drag and scroll.");
+                });
+
+                ui.collapsing("📊 Performance", |ui| {
+                    let perf = &mut settings_copy.performance;
+                    ui.checkbox(&mut perf.show_hud, "Show performance HUD (F5)");
+                    ui.add(egui::Slider::new(&mut perf.frame_budget_ms, 4.0..=100.0).logarithmic(true).text("Frame budget (ms)"));
+                    ui.horizontal(|ui| {
+                        ui.label("When over budget:");
+                        for policy in QualityPolicy::ALL {
+                            ui.selectable_value(&mut perf.policy, policy, policy.label());
+                        }
+                    });
+                    ui.separator();
+                    ui.add(egui::Slider::new(&mut perf.render_scale, MIN_RENDER_SCALE..=1.0).text("Render scale"));
+                    if let Some(name) = &current_plugin {
+                        let mut custom = perf.scale_overrides.contains_key(name);
+                        if ui.checkbox(&mut custom, format!("Custom scale for {name}")).changed() {
+                            if custom {
+                                perf.scale_overrides.insert(name.clone(), perf.render_scale);
+                            } else {
+                                perf.scale_overrides.remove(name);
+                            }
+                        }
+                        if let Some(scale) = perf.scale_overrides.get_mut(name) {
+                            ui.add(egui::Slider::new(scale, MIN_RENDER_SCALE..=1.0).text(name.as_str()));
+                        }
+                    }
+                });
+
+                ui.collapsing("🔋 Power", |ui| {
+                    let power = &mut settings_copy.power;
+                    ui.add(egui::Slider::new(&mut power.max_fps, 0..=MAX_FPS_CAP).text("Frame-rate cap (0 = none)"));
+                    ui.horizontal(|ui| {
+                        ui.label("Present:");
+                        for mode in PresentMode::ALL {
+                            let supported = self.state.gpu.as_ref().is_none_or(|gpu| gpu.supports_present_mode(mode));
+                            ui.add_enabled_ui(supported, |ui| ui.selectable_value(&mut power.present_mode, mode, mode.label()))
+                                .response
+                                .on_disabled_hover_text("Not supported by this surface; VSync is used");
+                        }
+                    });
+                    ui.checkbox(&mut power.pause_when_hidden, "Pause while minimized or covered");
+                    ui.checkbox(&mut power.idle_when_silent, "Idle while silent");
+                    if power.idle_when_silent {
+                        ui.add(egui::Slider::new(&mut power.idle_after_seconds, 5.0..=600.0).logarithmic(true).text("Idle after (s)"));
+                        ui.add(egui::Slider::new(&mut power.idle_fps, 1..=MAX_IDLE_FPS).text("Idle frame rate"));
+                    }
+                    let state = &self.state.power;
+                    let quiet = state.quiet_seconds();
+                    let note = if quiet > 0.0 { format!("{} · quiet for {:.0} s", state.mode.label(), quiet) } else { state.mode.label().to_string() };
+                    ui.label(egui::RichText::new(note).small().weak());
+                });
+
                 ui.collapsing("✨ Effects", |ui| {
                     for (group_name, names) in &plugin_groups {
                         ui.collapsing(group_name.as_str(), |ui| {
@@ -67,3 +324,23 @@ impl App {
             });
     }
 }
+
+/// Enable switch and, while enabled, placement controls of one overlay line.
+fn text_style_controls(ui: &mut egui::Ui, name: &str, style: &mut TextStyle) {
+    ui.checkbox(&mut style.enabled, format!("Show {}", name.to_lowercase()));
+    if !style.enabled {
+        return;
+    }
+    ui.indent(name, |ui| {
+        egui::ComboBox::from_id_source(name)
+            .selected_text(style.anchor.label())
+            .show_ui(ui, |ui| {
+                for anchor in TextAnchor::ALL {
+                    ui.selectable_value(&mut style.anchor, anchor, anchor.label());
+                }
+            });
+        ui.add(egui::Slider::new(&mut style.size, 0.02..=0.25).text("Size (of height)"));
+        ui.add(egui::Slider::new(&mut style.opacity, 0.0..=1.0).text("Opacity"));
+        ui.add(egui::Slider::new(&mut style.beat_scale, 0.0..=0.5).text("Beat scale"));
+    });
+}
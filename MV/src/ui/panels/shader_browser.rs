@@ -1,20 +1,38 @@
 //! Shader browser panel (F4)
 
 use crate::app::App;
-use crate::visualization::ShaderInfo;
+use crate::config::constants::MAX_RATING;
+use crate::config::persistence::PlayStats;
+use crate::visualization::{PluginBindings, ShaderInfo};
+
+/// Plugin index, name, registry metadata and its group 1 bindings.
+type BrowserEntry = (usize, String, Option<&'static ShaderInfo>, PluginBindings);
 
 impl App {
     pub fn show_shader_browser(&mut self, ctx: &egui::Context) {
         // Collect shader browser entries before closure
-        let shader_browser_entries: Vec<(usize, String, Option<&'static ShaderInfo>)> = self.state.gpu.as_ref()
+        let shader_browser_entries: Vec<BrowserEntry> = self.state.gpu.as_ref()
             .map(|g| g.plugins.iter().enumerate()
-                .map(|(i, p)| (i, p.name.clone(), p.info))
+                .map(|(i, p)| (i, p.name.clone(), p.info, p.bindings))
                 .collect())
             .unwrap_or_default();
 
         let current_plugin_idx = self.state.current_plugin_index;
         let mut show_shader_browser = self.state.show_shader_browser;
         let mut new_plugin_index: Option<usize> = None;
+        let current_image = self.state.gpu.as_ref().and_then(|g| {
+            let plugin = g.plugins.get(current_plugin_idx).filter(|p| p.bindings == PluginBindings::Image)?;
+            let source = g.images.path(&plugin.name).map_or("MV logo".to_string(), |p| p.display().to_string());
+            Some((plugin.name.clone(), source))
+        });
+        let mut image_path_edit = std::mem::take(&mut self.state.image_path_edit);
+        let image_error = self.state.image_error.clone();
+        let mut image_action: Option<Option<std::path::PathBuf>> = None;
+        let favorites = &self.state.settings.favorites;
+        let ratings = &self.state.settings.ratings;
+        let history = &self.state.play_history;
+        let mut favorite_toggled: Option<String> = None;
+        let mut rating_set: Option<(String, u8)> = None;
 
         egui::Window::new("🎭 Shaders")
             .open(&mut show_shader_browser)
@@ -23,20 +41,43 @@ impl App {
             .default_width(400.0)
             .frame(egui::Frame::window(&ctx.style()).shadow(egui::epaint::Shadow::NONE))
             .show(ctx, |ui| {
+                if let Some((name, source)) = &current_image {
+                    ui.label(egui::RichText::new(format!("🖼 Image for {}", name)).strong());
+                    ui.label(egui::RichText::new(source.as_str()).small().color(egui::Color32::GRAY));
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut image_path_edit).hint_text("path to PNG / JPEG, or drop a file"));
+                        if ui.button("Load").clicked() && !image_path_edit.trim().is_empty() {
+                            image_action = Some(Some(image_path_edit.trim().into()));
+                        }
+                        if ui.button("Default").clicked() {
+                            image_action = Some(None);
+                        }
+                    });
+                    if let Some(err) = &image_error {
+                        ui.colored_label(egui::Color32::from_rgb(230, 120, 80), err);
+                    }
+                    ui.separator();
+                }
                 egui::ScrollArea::vertical().show(ui, |ui| {
-                    let mut grouped: std::collections::HashMap<&'static str, Vec<(usize, String, Option<&'static ShaderInfo>)>> = std::collections::HashMap::new();
-                    for (idx, name, info) in &shader_browser_entries {
+                    let mut grouped: std::collections::HashMap<&'static str, Vec<BrowserEntry>> = std::collections::HashMap::new();
+                    for (idx, name, info, bindings) in &shader_browser_entries {
                         let cat: &'static str = if let Some(si) = info { si.category.label() } else { "✨ Abstract" };
-                        grouped.entry(cat).or_default().push((*idx, name.clone(), *info));
+                        grouped.entry(cat).or_default().push((*idx, name.clone(), *info, *bindings));
                     }
 
                     const CAT_ORDER: &[&str] = &["🎵 Spectrum", "🌡 Heatmap", "🌊 Waveform", "🔮 3D Effects", "✨ Abstract"];
                     for cat in CAT_ORDER {
                         if let Some(entries) = grouped.get(*cat) {
                             ui.collapsing(*cat, |ui| {
-                                for (idx, name, info) in entries {
+                                for (idx, name, info, bindings) in entries {
                                     let is_active = *idx == current_plugin_idx;
                                     ui.horizontal(|ui| {
+                                        let favorite = favorites.contains(name);
+                                        let star = egui::RichText::new(if favorite { "★" } else { "☆" })
+                                            .color(if favorite { egui::Color32::GOLD } else { egui::Color32::GRAY });
+                                        if ui.add(egui::Label::new(star).sense(egui::Sense::click())).on_hover_text("Favorite").clicked() {
+                                            favorite_toggled = Some(name.clone());
+                                        }
                                         let label = if is_active {
                                             egui::RichText::new(name.as_str()).strong().color(egui::Color32::from_rgb(100, 200, 100))
                                         } else {
@@ -48,10 +89,32 @@ impl App {
                                         if let Some(info) = info {
                                             ui.label(egui::RichText::new(info.performance.label()).small().color(egui::Color32::GRAY));
                                         }
+                                        if *bindings == PluginBindings::Shadertoy {
+                                            ui.label(egui::RichText::new("GLSL").small().color(egui::Color32::GRAY));
+                                        }
+                                        if *bindings == PluginBindings::Image {
+                                            ui.label(egui::RichText::new("🖼").small())
+                                                .on_hover_text("Takes an image: select it, then load a file or drop one on the window");
+                                        }
+                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                            // Right to left, so the highest star comes first.
+                                            let rating = ratings.get(name).copied().unwrap_or(0);
+                                            for stars in (1..=MAX_RATING).rev() {
+                                                let text = egui::RichText::new(if stars <= rating { "★" } else { "☆" }).small()
+                                                    .color(if stars <= rating { egui::Color32::GOLD } else { egui::Color32::DARK_GRAY });
+                                                let hover = if stars == rating { "Click again to clear".to_string() } else { format!("Rate {} of {}", stars, MAX_RATING) };
+                                                if ui.add(egui::Label::new(text).sense(egui::Sense::click())).on_hover_text(hover).clicked() {
+                                                    rating_set = Some((name.clone(), if stars == rating { 0 } else { stars }));
+                                                }
+                                            }
+                                        });
                                     });
                                     if let Some(info) = info {
                                         ui.label(egui::RichText::new(info.description).small().italics().color(egui::Color32::GRAY));
                                     }
+                                    if let Some(stats) = play_summary(history.get(name)) {
+                                        ui.label(egui::RichText::new(stats).small().color(egui::Color32::GRAY));
+                                    }
                                 }
                             });
                         }
@@ -60,11 +123,36 @@ impl App {
             });
 
         self.state.show_shader_browser = show_shader_browser;
+        self.state.image_path_edit = image_path_edit;
+        if let (Some((name, _)), Some(path)) = (current_image, image_action) {
+            self.state.image_error = self.set_shader_image(&name, path).err().map(|e| e.to_string());
+        }
         if let Some(idx) = new_plugin_index {
-            self.state.current_plugin_index = idx;
-            self.state.transition_active = true;
-            self.state.transition_time = 0.0;
-            self.state.last_mode_switch = std::time::Instant::now();
+            self.select_visualization(idx);
+        }
+        if favorite_toggled.is_some() || rating_set.is_some() {
+            let settings = &mut self.state.settings;
+            if let Some(name) = favorite_toggled {
+                if !settings.favorites.remove(&name) {
+                    settings.favorites.insert(name);
+                }
+            }
+            match rating_set {
+                Some((name, 0))     => { settings.ratings.remove(&name); }
+                Some((name, stars)) => { settings.ratings.insert(name, stars); }
+                None => {}
+            }
+            self.save_settings();
         }
     }
 }
+
+/// "Played 3× · 12 min", or `None` for a visualization never shown.
+fn play_summary(stats: PlayStats) -> Option<String> {
+    if stats.plays == 0 {
+        return None;
+    }
+    let minutes = stats.seconds / 60.0;
+    let time = if minutes < 1.0 { format!("{:.0} s", stats.seconds) } else if minutes < 60.0 { format!("{:.0} min", minutes) } else { format!("{:.1} h", minutes / 60.0) };
+    Some(format!("Played {}× · {}", stats.plays, time))
+}
@@ -0,0 +1,225 @@
+//! Live WGSL editor (F7)
+
+use crate::app::shader_editor::ShaderEditor;
+use crate::app::App;
+use egui::text::LayoutJob;
+use egui::{Color32, FontId, TextFormat};
+
+const FONT_SIZE: f32 = 13.0;
+
+const PLAIN: Color32     = Color32::from_rgb(212, 212, 212);
+const COMMENT: Color32   = Color32::from_rgb(106, 153, 85);
+const KEYWORD: Color32   = Color32::from_rgb(197, 134, 192);
+const TYPE: Color32      = Color32::from_rgb(78, 201, 176);
+const FUNCTION: Color32  = Color32::from_rgb(220, 220, 170);
+const ATTRIBUTE: Color32 = Color32::from_rgb(86, 156, 214);
+const NUMBER: Color32    = Color32::from_rgb(181, 206, 168);
+const ERROR: Color32     = Color32::from_rgb(240, 100, 90);
+const ERROR_BG: Color32  = Color32::from_rgba_premultiplied(70, 18, 18, 70);
+
+const KEYWORDS: &[&str] = &[
+    "alias", "break", "case", "const", "const_assert", "continue", "continuing", "default", "diagnostic",
+    "discard", "else", "enable", "false", "fn", "for", "if", "let", "loop", "override", "return",
+    "struct", "switch", "true", "var", "while",
+];
+
+const TYPES: &[&str] = &["bool", "f16", "f32", "i32", "u32", "sampler", "sampler_comparison"];
+
+/// What the editor's buttons asked for, applied once the window is drawn.
+enum EditorAction {
+    Save,
+    Reload,
+    Revert,
+    Open(String),
+}
+
+impl App {
+    pub fn show_shader_editor(&mut self, ctx: &egui::Context) {
+        if !self.state.show_shader_editor {
+            return;
+        }
+        let current = self.state.gpu.as_ref()
+            .and_then(|g| g.plugins.get(self.state.current_plugin_index))
+            .map(|p| p.name.clone());
+        let note = self.state.shader_editor_note.clone();
+        let mut open = true;
+        let mut action: Option<EditorAction> = None;
+
+        egui::Window::new("📝 Shader Editor")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_size([640.0, 480.0])
+            .frame(egui::Frame::window(&ctx.style()).shadow(egui::epaint::Shadow::NONE))
+            .show(ctx, |ui| match self.state.shader_editor.as_mut() {
+                Some(editor) => editor_contents(ui, editor, current.as_deref(), &mut action),
+                None => {
+                    ui.label(note.as_deref().unwrap_or("No shader open."));
+                    if let Some(current) = &current {
+                        if ui.button(format!("Edit {}", current)).clicked() {
+                            action = Some(EditorAction::Open(current.clone()));
+                        }
+                    }
+                }
+            });
+
+        self.state.show_shader_editor = open;
+        match action {
+            Some(EditorAction::Save)       => self.save_shader_editor(),
+            Some(EditorAction::Reload)     => self.reload_shader_editor(),
+            Some(EditorAction::Revert)     => self.revert_shader_editor(),
+            Some(EditorAction::Open(name)) => self.edit_shader(&name),
+            None => {}
+        }
+    }
+}
+
+fn editor_contents(ui: &mut egui::Ui, editor: &mut ShaderEditor, current: Option<&str>, action: &mut Option<EditorAction>) {
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new(editor.plugin.as_str()).strong());
+        let source = editor.user_file.as_ref().map_or("built-in".to_string(), |p| p.display().to_string());
+        ui.label(egui::RichText::new(source).small().color(Color32::GRAY));
+        if editor.unsaved {
+            ui.label(egui::RichText::new("● unsaved").small().color(Color32::from_rgb(230, 160, 60)));
+        }
+    });
+    ui.horizontal(|ui| {
+        let save_key = ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::S));
+        if ui.button("💾 Save").on_hover_text("Ctrl+S — writes the user shader directory").clicked() || save_key {
+            *action = Some(EditorAction::Save);
+        }
+        if ui.button("Reload").on_hover_text("Discard changes since the last save").clicked() {
+            *action = Some(EditorAction::Reload);
+        }
+        if editor.user_file.is_some() && editor.has_builtin()
+            && ui.button("Revert to built-in").on_hover_text("Delete the saved copy").clicked()
+        {
+            *action = Some(EditorAction::Revert);
+        }
+        if let Some(current) = current.filter(|c| *c != editor.plugin) {
+            if ui.button(format!("Edit {}", current)).clicked() {
+                *action = Some(EditorAction::Open(current.to_string()));
+            }
+        }
+    });
+    match (&editor.error, editor.edited_at) {
+        (_, Some(_))      => { ui.label(egui::RichText::new("… compiling").small().color(Color32::GRAY)); }
+        (Some(error), _)  => { ui.colored_label(ERROR, error.to_string()); }
+        (None, None)      => { ui.label(egui::RichText::new("✔ Compiled").small().color(Color32::from_rgb(100, 200, 100))); }
+    }
+    if let Some(status) = &editor.status {
+        ui.label(egui::RichText::new(status.as_str()).small().color(Color32::GRAY));
+    }
+    ui.separator();
+
+    let error = editor.error.as_ref().and_then(|e| Some((e.line?, e.message.clone())));
+    let error_line = error.as_ref().map(|(line, _)| *line);
+    let mut layouter = |ui: &egui::Ui, text: &str, _wrap_width: f32| {
+        // Lines never wrap, so galley rows and source lines correspond.
+        let mut job = highlight(text, error_line);
+        job.wrap.max_width = f32::INFINITY;
+        ui.fonts(|f| f.layout_job(job))
+    };
+
+    egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+        ui.horizontal_top(|ui| {
+            let font = FontId::monospace(FONT_SIZE);
+            let digits = editor.code.split('\n').count().to_string().len().max(2);
+            let char_width = ui.fonts(|f| f.glyph_width(&font, '0'));
+            let (gutter, _) = ui.allocate_exact_size(egui::vec2(char_width * digits as f32 + 8.0, 0.0), egui::Sense::hover());
+
+            let output = egui::TextEdit::multiline(&mut editor.code)
+                .code_editor()
+                .desired_width(f32::INFINITY)
+                .desired_rows(24)
+                .layouter(&mut layouter)
+                .show(ui);
+            if output.response.changed() {
+                editor.touch();
+            }
+
+            let painter = ui.painter();
+            for (i, row) in output.galley.rows.iter().enumerate() {
+                let y = output.galley_pos.y + row.rect.min.y;
+                let is_error = error_line == Some(i + 1);
+                let color = if is_error { ERROR } else { Color32::DARK_GRAY };
+                painter.text(egui::pos2(gutter.right() - 6.0, y), egui::Align2::RIGHT_TOP, (i + 1).to_string(), font.clone(), color);
+                if let (true, Some((_, message))) = (is_error, &error) {
+                    let x = output.galley_pos.x + row.rect.max.x + 3.0 * char_width;
+                    painter.text(egui::pos2(x, y), egui::Align2::LEFT_TOP, format!("⚠ {}", message), font.clone(), ERROR);
+                }
+            }
+        });
+    });
+}
+
+/// WGSL syntax colouring; `error_line` (1-based) gets a red background.
+fn highlight(code: &str, error_line: Option<usize>) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    let mut in_block_comment = false;
+    for (i, line) in code.split_inclusive('\n').enumerate() {
+        let background = if error_line == Some(i + 1) { ERROR_BG } else { Color32::TRANSPARENT };
+        let mut rest = line;
+        while !rest.is_empty() {
+            let (len, color) = if in_block_comment {
+                match rest.find("*/") {
+                    Some(end) => {
+                        in_block_comment = false;
+                        (end + 2, COMMENT)
+                    }
+                    None => (rest.len(), COMMENT),
+                }
+            } else {
+                token(rest, &mut in_block_comment)
+            };
+            append(&mut job, &rest[..len], color, background);
+            rest = &rest[len..];
+        }
+    }
+    job
+}
+
+/// Length and colour of the token `s` starts with.
+fn token(s: &str, in_block_comment: &mut bool) -> (usize, Color32) {
+    let ident_len = |s: &str| s.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(s.len());
+    let first = s.chars().next().unwrap_or(' ');
+    if s.starts_with("//") {
+        (s.len(), COMMENT)
+    } else if s.starts_with("/*") {
+        *in_block_comment = true;
+        (2, COMMENT)
+    } else if first == '@' {
+        (1 + ident_len(&s[1..]), ATTRIBUTE)
+    } else if first.is_ascii_digit() {
+        (s.find(|c: char| !(c.is_ascii_alphanumeric() || c == '.')).unwrap_or(s.len()), NUMBER)
+    } else if first.is_alphabetic() || first == '_' {
+        let len = ident_len(s);
+        let word = &s[..len];
+        let is_type = TYPES.contains(&word)
+            || ["vec", "mat", "array", "atomic", "ptr", "texture_"].iter().any(|p| word.starts_with(p));
+        let color = if KEYWORDS.contains(&word) {
+            KEYWORD
+        } else if is_type {
+            TYPE
+        } else if s[len..].trim_start().starts_with('(') {
+            FUNCTION
+        } else {
+            PLAIN
+        };
+        (len, color)
+    } else {
+        (first.len_utf8(), PLAIN)
+    }
+}
+
+/// Append `text`, extending the last section when its format matches.
+fn append(job: &mut LayoutJob, text: &str, color: Color32, background: Color32) {
+    let format = TextFormat { font_id: FontId::monospace(FONT_SIZE), color, background, ..Default::default() };
+    match job.sections.last_mut() {
+        Some(last) if last.format == format && last.byte_range.end == job.text.len() => {
+            job.text.push_str(text);
+            last.byte_range.end = job.text.len();
+        }
+        _ => job.append(text, 0.0, format),
+    }
+}
@@ -0,0 +1,143 @@
+//! Compute-shader plugins: storage buffers, compute passes and the draw that shows them
+
+/// Declares the GPU work of a compute plugin.
+#[derive(Debug, Clone, Copy)]
+pub struct ComputeDesc {
+    /// Size in bytes of each storage buffer, bound in order at group 1.
+    /// Buffers start zeroed and persist for the lifetime of the app.
+    pub buffers: &'static [u64],
+    /// Compute entry points run every frame, in order, with their workgroup count.
+    pub passes: &'static [(&'static str, u32)],
+    /// Vertices and instances drawn by the render shader afterwards.
+    pub vertices: u32,
+    pub instances: u32,
+}
+
+/// GPU resources owned by a compute plugin.
+#[derive(Debug)]
+pub struct ComputeState {
+    /// Kept alive alongside the bind groups that reference them.
+    _buffers: Vec<wgpu::Buffer>,
+    compute_bind_group: wgpu::BindGroup,
+    /// The same buffers, read-only, for the render shader.
+    pub render_bind_group: wgpu::BindGroup,
+    passes: Vec<(wgpu::ComputePipeline, u32)>,
+    pub vertices: u32,
+    pub instances: u32,
+}
+
+impl ComputeState {
+    /// Create the buffers and compute pipelines described by `desc`.
+    ///
+    /// Returns the state together with the pipeline layout the render shader
+    /// must use (shared group 0 plus the read-only buffers in group 1).
+    pub fn new(
+        device: &wgpu::Device,
+        shared_layout: &wgpu::BindGroupLayout,
+        name: &str,
+        compute_source: &str,
+        desc: &ComputeDesc,
+    ) -> (Self, wgpu::PipelineLayout) {
+        let buffers: Vec<wgpu::Buffer> = desc.buffers.iter()
+            .enumerate()
+            .map(|(i, &size)| device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(&format!("{name} Storage Buffer {i}")),
+                size,
+                usage: wgpu::BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            }))
+            .collect();
+
+        let compute_layout = create_storage_layout(device, desc.buffers.len(), false);
+        let render_layout = create_storage_layout(device, desc.buffers.len(), true);
+        let compute_bind_group = create_storage_bind_group(device, &compute_layout, &buffers);
+        let render_bind_group = create_storage_bind_group(device, &render_layout, &buffers);
+
+        let compute_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(name),
+            bind_group_layouts: &[shared_layout, &compute_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(name),
+            bind_group_layouts: &[shared_layout, &render_layout],
+            push_constant_ranges: &[],
+        });
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(name),
+            source: wgpu::ShaderSource::Wgsl(compute_source.into()),
+        });
+        let passes = desc.passes.iter()
+            .map(|&(entry, workgroups)| {
+                let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: Some(entry),
+                    layout: Some(&compute_pipeline_layout),
+                    module: &module,
+                    entry_point: entry,
+                });
+                (pipeline, workgroups)
+            })
+            .collect();
+
+        let state = Self {
+            _buffers: buffers,
+            compute_bind_group,
+            render_bind_group,
+            passes,
+            vertices: desc.vertices,
+            instances: desc.instances,
+        };
+        (state, render_pipeline_layout)
+    }
+
+    /// Record this frame's compute passes.
+    pub fn dispatch(&self, encoder: &mut wgpu::CommandEncoder, shared_bind_group: &wgpu::BindGroup) {
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Plugin Compute Pass"),
+            timestamp_writes: None,
+        });
+        cpass.set_bind_group(0, shared_bind_group, &[]);
+        cpass.set_bind_group(1, &self.compute_bind_group, &[]);
+        for (pipeline, workgroups) in &self.passes {
+            cpass.set_pipeline(pipeline);
+            cpass.dispatch_workgroups(*workgroups, 1, 1);
+        }
+    }
+}
+
+fn create_storage_layout(device: &wgpu::Device, count: usize, read_only: bool) -> wgpu::BindGroupLayout {
+    let visibility = if read_only {
+        wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT
+    } else {
+        wgpu::ShaderStages::COMPUTE
+    };
+    let entries: Vec<wgpu::BindGroupLayoutEntry> = (0..count as u32)
+        .map(|binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        })
+        .collect();
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &entries,
+        label: Some("plugin_storage_bind_group_layout"),
+    })
+}
+
+fn create_storage_bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout, buffers: &[wgpu::Buffer]) -> wgpu::BindGroup {
+    let entries: Vec<wgpu::BindGroupEntry> = buffers.iter()
+        .enumerate()
+        .map(|(i, buffer)| wgpu::BindGroupEntry { binding: i as u32, resource: buffer.as_entire_binding() })
+        .collect();
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &entries,
+        label: Some("plugin_storage_bind_group"),
+    })
+}
@@ -1,51 +1,152 @@
 //! Plugin loading functionality
 
 use crate::common::error::{AppError, AppResult};
-use super::plugin::Plugin;
+use crate::config::constants::USER_SHADER_DIR;
+use std::path::{Path, PathBuf};
+use super::compute::ComputeDesc;
+use super::mesh::MeshShape;
+use super::plugin::{Plugin, PluginBindings, PluginLayouts};
 
-/// Load all visualization plugins from embedded shader sources.
+/// Boids simulated by the `boids` compute plugin (a multiple of its workgroup size).
+const BOIDS: u32 = 2048;
+
+/// The file `shaders/<path>` with `uniforms.wgsl` in front of it.
+macro_rules! with_uniforms {
+    ($path:literal) => {
+        concat!(include_str!("../../shaders/uniforms.wgsl"), include_str!(concat!("../../shaders/", $path)))
+    };
+}
+
+/// Common WGSL a fragment shader is built on, which also decides the
+/// resources it binds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preamble {
+    Common,
+    History,
+    Feedback,
+    Image,
+}
+
+impl Preamble {
+    pub fn source(self) -> &'static str {
+        match self {
+            Self::Common   => with_uniforms!("common.wgsl"),
+            Self::History  => with_uniforms!("common_history.wgsl"),
+            Self::Feedback => with_uniforms!("common_feedback.wgsl"),
+            Self::Image    => with_uniforms!("common_image.wgsl"),
+        }
+    }
+
+    pub fn bindings(self) -> PluginBindings {
+        match self {
+            Self::Common | Self::History => PluginBindings::Standard,
+            Self::Feedback               => PluginBindings::Feedback,
+            Self::Image                  => PluginBindings::Image,
+        }
+    }
+}
+
+/// Embedded fullscreen fragment shaders: (name, preamble, specific_shader_source).
+static FRAGMENT_SHADERS: &[(&str, Preamble, &str)] = &[
+    ("bars_3d",            Preamble::Common,   include_str!("../../shaders/spectrum/bars_3d.wgsl")),
+    ("circular_spectrum",  Preamble::Common,   include_str!("../../shaders/spectrum/circular_spectrum.wgsl")),
+    ("energy_field",       Preamble::Common,   include_str!("../../shaders/abstract/energy_field.wgsl")),
+    ("gradient_bars",      Preamble::Common,   include_str!("../../shaders/spectrum/gradient_bars.wgsl")),
+    ("heatmap",            Preamble::Common,   include_str!("../../shaders/spectrum/heatmap.wgsl")),
+    ("kaleidoscope",       Preamble::Common,   include_str!("../../shaders/spectrum/kaleidoscope.wgsl")),
+    ("mandala",            Preamble::Common,   include_str!("../../shaders/abstract/mandala.wgsl")),
+    ("neon_pulse",         Preamble::Common,   include_str!("../../shaders/waveform/neon_pulse.wgsl")),
+    ("oscilloscope",       Preamble::Common,   include_str!("../../shaders/waveform/oscilloscope.wgsl")),
+    ("ripple",             Preamble::Common,   include_str!("../../shaders/abstract/ripple.wgsl")),
+    ("simple_bars",        Preamble::Common,   include_str!("../../shaders/spectrum/simple_bars.wgsl")),
+    ("spectrum",           Preamble::Common,   include_str!("../../shaders/spectrum/spectrum.wgsl")),
+    ("wave_3d",            Preamble::Common,   include_str!("../../shaders/abstract/wave_3d.wgsl")),
+    ("waveform",           Preamble::Common,   include_str!("../../shaders/waveform/waveform.wgsl")),
+    ("cubes_3d",           Preamble::Common,   include_str!("../../shaders/geometry_3d/cubes_3d.wgsl")),
+    ("depth_wave_3d",      Preamble::Common,   include_str!("../../shaders/abstract/depth_wave_3d.wgsl")),
+    ("echo_zoom",          Preamble::Feedback, include_str!("../../shaders/abstract/echo_zoom.wgsl")),
+    ("plasma_sphere_3d",   Preamble::Common,   include_str!("../../shaders/abstract/plasma_sphere_3d.wgsl")),
+    ("sphere_3d",          Preamble::Common,   include_str!("../../shaders/geometry_3d/sphere_3d.wgsl")),
+    ("terrain_3d",         Preamble::Common,   include_str!("../../shaders/geometry_3d/terrain_3d.wgsl")),
+    ("tunnel_3d",          Preamble::Common,   include_str!("../../shaders/abstract/tunnel_3d.wgsl")),
+    ("water_droplets_3d",  Preamble::Common,   include_str!("../../shaders/geometry_3d/water_droplets_3d.wgsl")),
+    ("waveform_glow",      Preamble::History,  include_str!("../../shaders/waveform/waveform_glow.wgsl")),
+    ("waveform_history",   Preamble::History,  include_str!("../../shaders/waveform/waveform_history.wgsl")),
+    ("logo_pulse",         Preamble::Image,    include_str!("../../shaders/image/logo_pulse.wgsl")),
+    ("cover_warp",         Preamble::Image,    include_str!("../../shaders/image/cover_warp.wgsl")),
+];
+
+/// Load all visualization plugins: the embedded WGSL and Shadertoy GLSL
+/// shaders, then the `*.glsl` and `*.wgsl` files found in
+/// [`USER_SHADER_DIR`].
 pub fn load_plugins(
     device: &wgpu::Device,
-    pipeline_layout: &wgpu::PipelineLayout,
+    layouts: &PluginLayouts,
     format: wgpu::TextureFormat,
 ) -> AppResult<Vec<Plugin>> {
-    let common      = include_str!("../../shaders/common.wgsl");
-    let common_hist = include_str!("../../shaders/common_history.wgsl");
-
-    // (name, preamble, specific_shader_source)
-    let raw: &[(&str, &str, &str)] = &[
-        ("bars_3d",           common,      include_str!("../../shaders/spectrum/bars_3d.wgsl")),
-        ("circular_spectrum", common,      include_str!("../../shaders/spectrum/circular_spectrum.wgsl")),
-        ("energy_field",      common,      include_str!("../../shaders/abstract/energy_field.wgsl")),
-        ("gradient_bars",     common,      include_str!("../../shaders/spectrum/gradient_bars.wgsl")),
-        ("heatmap",           common,      include_str!("../../shaders/spectrum/heatmap.wgsl")),
-        ("kaleidoscope",      common,      include_str!("../../shaders/spectrum/kaleidoscope.wgsl")),
-        ("mandala",           common,      include_str!("../../shaders/abstract/mandala.wgsl")),
-        ("neon_pulse",        common,      include_str!("../../shaders/waveform/neon_pulse.wgsl")),
-        ("oscilloscope",      common,      include_str!("../../shaders/waveform/oscilloscope.wgsl")),
-        ("ripple",            common,      include_str!("../../shaders/abstract/ripple.wgsl")),
-        ("simple_bars",       common,      include_str!("../../shaders/spectrum/simple_bars.wgsl")),
-        ("spectrum",          common,      include_str!("../../shaders/spectrum/spectrum.wgsl")),
-        ("wave_3d",           common,      include_str!("../../shaders/abstract/wave_3d.wgsl")),
-        ("waveform",          common,      include_str!("../../shaders/waveform/waveform.wgsl")),
-        ("cubes_3d",          common,      include_str!("../../shaders/geometry_3d/cubes_3d.wgsl")),
-        ("depth_wave_3d",     common,      include_str!("../../shaders/abstract/depth_wave_3d.wgsl")),
-        ("plasma_sphere_3d",  common,      include_str!("../../shaders/abstract/plasma_sphere_3d.wgsl")),
-        ("sphere_3d",         common,      include_str!("../../shaders/geometry_3d/sphere_3d.wgsl")),
-        ("terrain_3d",        common,      include_str!("../../shaders/geometry_3d/terrain_3d.wgsl")),
-        ("tunnel_3d",         common,      include_str!("../../shaders/abstract/tunnel_3d.wgsl")),
-        ("water_droplets_3d", common,      include_str!("../../shaders/geometry_3d/water_droplets_3d.wgsl")),
-        ("waveform_glow",     common_hist, include_str!("../../shaders/waveform/waveform_glow.wgsl")),
-        ("waveform_history",  common_hist, include_str!("../../shaders/waveform/waveform_history.wgsl")),
+    let common_mb   = with_uniforms!("common_multibuffer.wgsl");
+    let common_cs   = with_uniforms!("common_compute.wgsl");
+    let common_mesh = with_uniforms!("common_mesh.wgsl");
+
+    // (name, buffer_passes, specific_shader_source) — built on common_multibuffer.wgsl
+    let multibuffer: &[(&str, usize, &str)] = &[
+        ("reaction_diffusion", 2, include_str!("../../shaders/abstract/reaction_diffusion.wgsl")),
+    ];
+
+    // (name, compute_source, render_source, gpu work) — both built on common_compute.wgsl
+    let compute: &[(&str, &str, &str, ComputeDesc)] = &[
+        ("boids", include_str!("../../shaders/compute/boids.wgsl"), include_str!("../../shaders/compute/boids_render.wgsl"), ComputeDesc {
+            buffers:   &[BOIDS as u64 * 16, BOIDS as u64 * 16],
+            passes:    &[("cs_step", BOIDS / 64), ("cs_copy", BOIDS / 64)],
+            vertices:  3,
+            instances: BOIDS,
+        }),
     ];
 
-    let mut plugins = Vec::with_capacity(raw.len());
-    for &(name, preamble, specific) in raw {
-        let src = format!("{}\n{}", preamble, specific);
-        let plugin = Plugin::load_from_source(device, pipeline_layout, name, &src, format)
+    // (name, base shape, instances, specific_shader_source) — built on common_mesh.wgsl
+    let meshes: &[(&str, MeshShape, u32, &str)] = &[
+        ("pulse_sphere",  MeshShape::Sphere { rings: 48, segments: 96 }, 1,       include_str!("../../shaders/mesh/pulse_sphere.wgsl")),
+        ("spectrum_city", MeshShape::Cube,                               32 * 32, include_str!("../../shaders/mesh/spectrum_city.wgsl")),
+    ];
+
+    // (name, shadertoy_source)
+    let glsl: &[(&str, &str)] = &[
+        ("audio_plasma",      include_str!("../../shaders/shadertoy/audio_plasma.glsl")),
+    ];
+
+    let mut plugins = Vec::with_capacity(FRAGMENT_SHADERS.len() + multibuffer.len() + compute.len() + meshes.len() + glsl.len());
+    for &(name, preamble, specific) in FRAGMENT_SHADERS {
+        let src = format!("{}\n{}", preamble.source(), specific);
+        let plugin = Plugin::load_from_source(device, layouts, name, &src, format, preamble.bindings())
+            .map_err(|e| AppError::Plugin(format!("Failed to load shader '{}': {}", name, e)))?;
+        plugins.push(plugin);
+    }
+    for &(name, buffers, specific) in multibuffer {
+        let src = format!("{}\n{}", common_mb, specific);
+        let plugin = Plugin::load_multibuffer(device, layouts, name, &src, format, buffers)
+            .map_err(|e| AppError::Plugin(format!("Failed to load shader '{}': {}", name, e)))?;
+        plugins.push(plugin);
+    }
+    for (name, compute_specific, render_specific, desc) in compute {
+        let compute_src = format!("{}\n{}", common_cs, compute_specific);
+        let render_src = format!("{}\n{}", common_cs, render_specific);
+        let plugin = Plugin::load_compute(device, layouts, name, &compute_src, &render_src, format, desc)
+            .map_err(|e| AppError::Plugin(format!("Failed to load shader '{}': {}", name, e)))?;
+        plugins.push(plugin);
+    }
+    for &(name, shape, instances, specific) in meshes {
+        let src = format!("{}\n{}", common_mesh, specific);
+        let plugin = Plugin::load_mesh(device, layouts, name, &src, format, shape, instances)
             .map_err(|e| AppError::Plugin(format!("Failed to load shader '{}': {}", name, e)))?;
         plugins.push(plugin);
     }
+    for &(name, src) in glsl {
+        let plugin = Plugin::load_from_glsl(device, layouts, name, src, format)
+            .map_err(|e| AppError::Plugin(format!("Failed to load shader '{}': {}", name, e)))?;
+        plugins.push(plugin);
+    }
+
+    load_user_shaders(device, layouts, format, &mut plugins);
 
     if plugins.is_empty() {
         return Err(AppError::Plugin("No visualization plugins loaded".to_string()));
@@ -53,3 +154,113 @@ pub fn load_plugins(
 
     Ok(plugins)
 }
+
+/// A fullscreen WGSL fragment shader as the live editor sees it.
+#[derive(Debug, Clone)]
+pub struct FragmentSource {
+    pub preamble: Preamble,
+    /// The shader's own source, without the preamble.
+    pub body: String,
+    /// The user file it was read from, if any.
+    pub user_file: Option<PathBuf>,
+}
+
+/// Where a user copy of shader `name` is kept.
+pub fn user_wgsl_path(name: &str) -> PathBuf {
+    Path::new(USER_SHADER_DIR).join(format!("{}.wgsl", name))
+}
+
+/// The embedded fragment shader `name`, ignoring any user copy.
+pub fn embedded_fragment(name: &str) -> Option<(Preamble, &'static str)> {
+    FRAGMENT_SHADERS.iter().find(|(n, ..)| *n == name).map(|&(_, preamble, body)| (preamble, body))
+}
+
+/// Source of the WGSL fragment shader `name`: the user copy when there is
+/// one, else the embedded shader. `None` for shaders of other kinds.
+pub fn fragment_source(name: &str) -> AppResult<Option<FragmentSource>> {
+    let embedded = embedded_fragment(name);
+    let path = user_wgsl_path(name);
+    if path.is_file() {
+        let body = std::fs::read_to_string(&path)?;
+        let preamble = embedded.map_or(Preamble::Common, |(preamble, _)| preamble);
+        return Ok(Some(FragmentSource { preamble, body, user_file: Some(path) }));
+    }
+    Ok(embedded.map(|(preamble, body)| FragmentSource { preamble, body: body.to_string(), user_file: None }))
+}
+
+/// Build a fragment plugin from WGSL that has not been checked yet, e.g. a
+/// user file or a live edit. Errors come back instead of taking the device
+/// down.
+pub fn load_fragment(
+    device: &wgpu::Device,
+    layouts: &PluginLayouts,
+    name: &str,
+    preamble: Preamble,
+    body: &str,
+    format: wgpu::TextureFormat,
+) -> AppResult<Plugin> {
+    super::wgsl::check(preamble.source(), body, &["vs_main", "fs_main"])
+        .map_err(|e| AppError::Plugin(e.to_string()))?;
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let plugin = Plugin::load_from_source(device, layouts, name, &format!("{}\n{}", preamble.source(), body), format, preamble.bindings());
+    match pollster::block_on(device.pop_error_scope()) {
+        Some(error) => Err(AppError::Plugin(error.to_string())),
+        None => plugin,
+    }
+}
+
+/// Append Shadertoy GLSL shaders and WGSL fragment shaders from
+/// [`USER_SHADER_DIR`]. A WGSL file named after an embedded fragment shader
+/// replaces it; other WGSL files are built on `common.wgsl`.
+///
+/// User shaders are optional, so a missing directory is silently ignored and
+/// a shader that fails to translate is reported and skipped rather than
+/// aborting startup.
+fn load_user_shaders(device: &wgpu::Device, layouts: &PluginLayouts, format: wgpu::TextureFormat, plugins: &mut Vec<Plugin>) {
+    let Ok(entries) = std::fs::read_dir(USER_SHADER_DIR) else { return };
+
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "glsl" || ext == "wgsl"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+        if path.extension().is_some_and(|ext| ext == "wgsl") {
+            load_user_wgsl(device, layouts, format, plugins, name, &path);
+            continue;
+        }
+        if plugins.iter().any(|p| p.name == name) {
+            eprintln!("Skipping user shader {}: a shader named '{}' already exists", path.display(), name);
+            continue;
+        }
+        let result = std::fs::read_to_string(&path)
+            .map_err(AppError::from)
+            .and_then(|src| Plugin::load_from_glsl(device, layouts, name, &src, format));
+        match result {
+            Ok(plugin) => plugins.push(plugin),
+            Err(e) => eprintln!("Skipping user shader {}: {}", path.display(), e),
+        }
+    }
+}
+
+fn load_user_wgsl(device: &wgpu::Device, layouts: &PluginLayouts, format: wgpu::TextureFormat, plugins: &mut Vec<Plugin>, name: &str, path: &Path) {
+    let existing = plugins.iter().position(|p| p.name == name);
+    let preamble = match (existing, embedded_fragment(name)) {
+        (None, _)               => Preamble::Common,
+        (Some(_), Some((p, _))) => p,
+        (Some(_), None) => {
+            eprintln!("Skipping user shader {}: '{}' is not a WGSL fragment shader", path.display(), name);
+            return;
+        }
+    };
+    let result = std::fs::read_to_string(path)
+        .map_err(AppError::from)
+        .and_then(|body| load_fragment(device, layouts, name, preamble, &body, format));
+    match (result, existing) {
+        (Ok(plugin), Some(index)) => plugins[index] = plugin,
+        (Ok(plugin), None)        => plugins.push(plugin),
+        (Err(e), _)               => eprintln!("Skipping user shader {}: {}", path.display(), e),
+    }
+}
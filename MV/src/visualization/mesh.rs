@@ -0,0 +1,103 @@
+//! Procedural geometry for mesh plugins
+
+use crate::common::types::MeshVertex;
+use std::f32::consts::{PI, TAU};
+use wgpu::util::DeviceExt;
+
+/// Base geometry a mesh plugin instances; shaders place and deform each copy.
+#[derive(Debug, Clone, Copy)]
+pub enum MeshShape {
+    /// Unit cube centred on the origin (-0.5..0.5), flat-shaded.
+    Cube,
+    /// Unit-radius UV sphere.
+    Sphere { rings: u32, segments: u32 },
+}
+
+/// Vertex and index buffers of a mesh plugin, drawn `instances` times.
+#[derive(Debug)]
+pub struct Mesh {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub index_count: u32,
+    pub instances: u32,
+}
+
+impl Mesh {
+    pub fn new(device: &wgpu::Device, name: &str, shape: MeshShape, instances: u32) -> Self {
+        let (vertices, indices) = match shape {
+            MeshShape::Cube => cube(),
+            MeshShape::Sphere { rings, segments } => sphere(rings, segments),
+        };
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{name} Vertex Buffer")),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{name} Index Buffer")),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        Self { vertex_buffer, index_buffer, index_count: indices.len() as u32, instances }
+    }
+
+    pub fn vertex_layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<MeshVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute { offset: 0,  shader_location: 0, format: wgpu::VertexFormat::Float32x3 },
+                wgpu::VertexAttribute { offset: 12, shader_location: 1, format: wgpu::VertexFormat::Float32x3 },
+            ],
+        }
+    }
+}
+
+fn cube() -> (Vec<MeshVertex>, Vec<u32>) {
+    // (normal, tangent u, tangent v) per face, with u × v = normal so the
+    // winding below is counter-clockwise seen from outside.
+    let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+        ([ 1.0,  0.0,  0.0], [ 0.0,  0.0, -1.0], [0.0, 1.0,  0.0]),
+        ([-1.0,  0.0,  0.0], [ 0.0,  0.0,  1.0], [0.0, 1.0,  0.0]),
+        ([ 0.0,  1.0,  0.0], [ 1.0,  0.0,  0.0], [0.0, 0.0, -1.0]),
+        ([ 0.0, -1.0,  0.0], [ 1.0,  0.0,  0.0], [0.0, 0.0,  1.0]),
+        ([ 0.0,  0.0,  1.0], [ 1.0,  0.0,  0.0], [0.0, 1.0,  0.0]),
+        ([ 0.0,  0.0, -1.0], [-1.0,  0.0,  0.0], [0.0, 1.0,  0.0]),
+    ];
+
+    let mut vertices = Vec::with_capacity(24);
+    let mut indices = Vec::with_capacity(36);
+    for (n, u, v) in faces {
+        let base = vertices.len() as u32;
+        for (su, sv) in [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)] {
+            let position = std::array::from_fn(|i| n[i] * 0.5 + u[i] * su + v[i] * sv);
+            vertices.push(MeshVertex { position, normal: n });
+        }
+        indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+    (vertices, indices)
+}
+
+fn sphere(rings: u32, segments: u32) -> (Vec<MeshVertex>, Vec<u32>) {
+    let (rings, segments) = (rings.max(2), segments.max(3));
+    let mut vertices = Vec::with_capacity(((rings + 1) * (segments + 1)) as usize);
+    for r in 0..=rings {
+        let theta = r as f32 / rings as f32 * PI;
+        for s in 0..=segments {
+            let phi = s as f32 / segments as f32 * TAU;
+            let n = [theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin()];
+            vertices.push(MeshVertex { position: n, normal: n });
+        }
+    }
+
+    let mut indices = Vec::with_capacity((rings * segments * 6) as usize);
+    let stride = segments + 1;
+    for r in 0..rings {
+        for s in 0..segments {
+            let a = r * stride + s;
+            let b = a + stride;
+            indices.extend([a, a + 1, b, a + 1, b + 1, b]);
+        }
+    }
+    (vertices, indices)
+}
@@ -1,10 +1,14 @@
 //! Visualization plugins and shaders
 
 pub mod plugin;
+pub mod compute;
+pub mod mesh;
 pub mod loader;
 pub mod shader_info;
+pub mod shadertoy;
+pub mod wgsl;
 
 // Re-export types and functions
-pub use plugin::Plugin;
+pub use plugin::{Plugin, PluginBindings, PluginLayouts};
 pub use loader::load_plugins;
-pub use shader_info::{ShaderCategory, PerformanceTier, ShaderInfo, SHADER_REGISTRY, shader_info};
+pub use shader_info::{ShaderCategory, ShaderInfo, shader_category};
@@ -1,14 +1,71 @@
 //! Plugin structure and loading
 
 use crate::common::error::{AppError, AppResult};
+use crate::config::constants::{DEPTH_FORMAT, HDR_FORMAT, MAX_SHADER_BUFFERS};
+use super::compute::{ComputeDesc, ComputeState};
+use super::mesh::{Mesh, MeshShape};
 use super::shader_info::{ShaderInfo, shader_info};
 
+/// Resources a plugin binds in group 1, on top of the shared group 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginBindings {
+    /// Only the shared uniforms / data / history group.
+    Standard,
+    /// Its own previous frame (built on `common_feedback.wgsl`).
+    Feedback,
+    /// Shadertoy uniforms and the `iChannel0` audio texture (imported GLSL).
+    Shadertoy,
+    /// Persistent Buffer A/B/C textures (built on `common_multibuffer.wgsl`).
+    MultiBuffer,
+    /// The plugin's own storage buffers, written by its compute passes.
+    Compute,
+    /// The shared orbit camera (built on `common_mesh.wgsl`).
+    Mesh,
+    /// A user-assigned image (built on `common_image.wgsl`).
+    Image,
+}
+
+/// Pipeline layouts for each kind of plugin binding set.
+pub struct PluginLayouts {
+    /// Group 0 (uniforms, data, history), for layouts built per plugin.
+    pub shared: wgpu::BindGroupLayout,
+    pub standard: wgpu::PipelineLayout,
+    pub feedback: wgpu::PipelineLayout,
+    pub shadertoy: wgpu::PipelineLayout,
+    pub multibuffer: wgpu::PipelineLayout,
+    pub mesh: wgpu::PipelineLayout,
+    pub image: wgpu::PipelineLayout,
+}
+
+impl PluginLayouts {
+    /// The shared layout for `bindings`; `None` for compute plugins, which
+    /// build their own.
+    pub fn get(&self, bindings: PluginBindings) -> Option<&wgpu::PipelineLayout> {
+        match bindings {
+            PluginBindings::Standard    => Some(&self.standard),
+            PluginBindings::Feedback    => Some(&self.feedback),
+            PluginBindings::Shadertoy   => Some(&self.shadertoy),
+            PluginBindings::MultiBuffer => Some(&self.multibuffer),
+            PluginBindings::Mesh        => Some(&self.mesh),
+            PluginBindings::Image       => Some(&self.image),
+            PluginBindings::Compute     => None,
+        }
+    }
+}
+
 /// Represents a loaded visualization plugin
 #[derive(Debug)]
 pub struct Plugin {
     pub name: String,
     pub is_spectrum: bool,
+    pub bindings: PluginBindings,
     pub render_pipeline: wgpu::RenderPipeline,
+    /// Passes drawn into Buffer A, B, C… before `render_pipeline` (multi-buffer only).
+    pub buffer_pipelines: Vec<wgpu::RenderPipeline>,
+    /// Storage buffers and compute passes (compute plugins only).
+    pub compute: Option<ComputeState>,
+    /// Geometry drawn with depth testing (mesh plugins only).
+    pub mesh: Option<Mesh>,
     pub info: Option<&'static ShaderInfo>,
 }
 
@@ -16,19 +73,133 @@ impl Plugin {
     /// Create a plugin from an embedded WGSL shader source string
     pub fn load_from_source(
         device: &wgpu::Device,
-        pipeline_layout: &wgpu::PipelineLayout,
+        layouts: &PluginLayouts,
         name: &str,
         source: &str,
         format: wgpu::TextureFormat,
+        bindings: PluginBindings,
     ) -> AppResult<Self> {
         let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some(name),
             source: wgpu::ShaderSource::Wgsl(source.into()),
         });
 
+        let layout = layouts.get(bindings)
+            .ok_or_else(|| AppError::Plugin(format!("'{}' has no shared pipeline layout for {:?} bindings", name, bindings)))?;
+        let render_pipeline = create_fullscreen_pipeline(
+            device,
+            layout,
+            name,
+            (&shader_module, "vs_main"),
+            (&shader_module, "fs_main"),
+            format,
+        );
+
+        Ok(Self::new(name, bindings, render_pipeline))
+    }
+
+    /// Create a plugin from a Shadertoy-style GLSL fragment shader that
+    /// defines `mainImage(out vec4, in vec2)`.
+    pub fn load_from_glsl(
+        device: &wgpu::Device,
+        layouts: &PluginLayouts,
+        name: &str,
+        source: &str,
+        format: wgpu::TextureFormat,
+    ) -> AppResult<Self> {
+        let module = super::shadertoy::translate(source)?;
+
+        let vertex_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Fullscreen Vertex Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/fullscreen.wgsl").into()),
+        });
+        let fragment_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(name),
+            source: wgpu::ShaderSource::Naga(std::borrow::Cow::Owned(module)),
+        });
+
+        let render_pipeline = create_fullscreen_pipeline(
+            device,
+            &layouts.shadertoy,
+            name,
+            (&vertex_module, "vs_main"),
+            (&fragment_module, "main"),
+            format,
+        );
+
+        Ok(Self::new(name, PluginBindings::Shadertoy, render_pipeline))
+    }
+
+    /// Create a multi-buffer plugin whose source defines `fs_buffer_a`, up to
+    /// `buffer_count` buffer passes in total, and the final `fs_main`.
+    pub fn load_multibuffer(
+        device: &wgpu::Device,
+        layouts: &PluginLayouts,
+        name: &str,
+        source: &str,
+        format: wgpu::TextureFormat,
+        buffer_count: usize,
+    ) -> AppResult<Self> {
+        if !(1..=MAX_SHADER_BUFFERS).contains(&buffer_count) {
+            return Err(AppError::Plugin(format!(
+                "'{}' declares {} buffers; 1 to {} are supported", name, buffer_count, MAX_SHADER_BUFFERS
+            )));
+        }
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(name),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let layout = &layouts.multibuffer;
+
+        // Buffers are always HDR, whatever the final target format is.
+        let buffer_pipelines = ["fs_buffer_a", "fs_buffer_b", "fs_buffer_c"][..buffer_count]
+            .iter()
+            .map(|&entry| create_fullscreen_pipeline(
+                device,
+                layout,
+                name,
+                (&shader_module, "vs_main"),
+                (&shader_module, entry),
+                HDR_FORMAT,
+            ))
+            .collect();
+        let render_pipeline = create_fullscreen_pipeline(
+            device,
+            layout,
+            name,
+            (&shader_module, "vs_main"),
+            (&shader_module, "fs_main"),
+            format,
+        );
+
+        Ok(Self { buffer_pipelines, ..Self::new(name, PluginBindings::MultiBuffer, render_pipeline) })
+    }
+
+    /// Create a compute plugin: `compute_source` holds the compute entry points
+    /// named in `desc`, `render_source` the `vs_main`/`fs_main` that draw the
+    /// results additively over a cleared scene.
+    pub fn load_compute(
+        device: &wgpu::Device,
+        layouts: &PluginLayouts,
+        name: &str,
+        compute_source: &str,
+        render_source: &str,
+        format: wgpu::TextureFormat,
+        desc: &ComputeDesc,
+    ) -> AppResult<Self> {
+        if desc.buffers.is_empty() || desc.passes.is_empty() {
+            return Err(AppError::Plugin(format!("'{}' declares no storage buffers or compute passes", name)));
+        }
+
+        let (compute, layout) = ComputeState::new(device, &layouts.shared, name, compute_source, desc);
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(name),
+            source: wgpu::ShaderSource::Wgsl(render_source.into()),
+        });
         let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some(name),
-            layout: Some(pipeline_layout),
+            layout: Some(&layout),
             vertex: wgpu::VertexState {
                 module: &shader_module,
                 entry_point: "vs_main",
@@ -39,7 +210,14 @@ impl Plugin {
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
                     format,
-                    blend: Some(wgpu::BlendState::REPLACE),
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent::OVER,
+                    }),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
             }),
@@ -49,14 +227,105 @@ impl Plugin {
             multiview: None,
         });
 
+        Ok(Self { compute: Some(compute), ..Self::new(name, PluginBindings::Compute, render_pipeline) })
+    }
+
+    /// Create a mesh plugin that draws `instances` copies of `shape` with a
+    /// depth buffer; its `vs_main` places them and applies the shared camera.
+    pub fn load_mesh(
+        device: &wgpu::Device,
+        layouts: &PluginLayouts,
+        name: &str,
+        source: &str,
+        format: wgpu::TextureFormat,
+        shape: MeshShape,
+        instances: u32,
+    ) -> AppResult<Self> {
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(name),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(name),
+            layout: Some(&layouts.mesh),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vs_main",
+                buffers: &[Mesh::vertex_layout()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let mesh = Mesh::new(device, name, shape, instances);
+        Ok(Self { mesh: Some(mesh), ..Self::new(name, PluginBindings::Mesh, render_pipeline) })
+    }
+
+    fn new(name: &str, bindings: PluginBindings, render_pipeline: wgpu::RenderPipeline) -> Self {
         let info = shader_info(name);
-        let is_waveform = info.map_or(false, |i| i.is_waveform);
+        let is_waveform = info.is_some_and(|i| i.is_waveform);
 
-        Ok(Self {
+        Self {
             name: name.to_string(),
             is_spectrum: !is_waveform,
+            bindings,
             render_pipeline,
+            buffer_pipelines: Vec::new(),
+            compute: None,
+            mesh: None,
             info,
-        })
+        }
     }
 }
+
+fn create_fullscreen_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    name: &str,
+    (vertex_module, vertex_entry): (&wgpu::ShaderModule, &str),
+    (fragment_module, fragment_entry): (&wgpu::ShaderModule, &str),
+    format: wgpu::TextureFormat,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(name),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: vertex_module,
+            entry_point: vertex_entry,
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: fragment_module,
+            entry_point: fragment_entry,
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    })
+}
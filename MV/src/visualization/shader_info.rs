@@ -1,7 +1,9 @@
 //! Shader information and registry
 
+use serde::{Deserialize, Serialize};
+
 /// Visual category of a shader.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ShaderCategory {
     Spectrum,
     Waveform,
@@ -11,6 +13,8 @@ pub enum ShaderCategory {
 }
 
 impl ShaderCategory {
+    pub const ALL: [Self; 5] = [Self::Spectrum, Self::Heatmap, Self::Waveform, Self::Geometry3D, Self::Abstract];
+
     pub fn label(self) -> &'static str {
         match self {
             Self::Spectrum   => "🎵 Spectrum",
@@ -49,21 +53,29 @@ pub struct ShaderInfo {
 }
 
 pub const SHADER_REGISTRY: &[ShaderInfo] = &[
+    ShaderInfo { id: "audio_plasma",      description: "Shadertoy-style plasma that swells with the bass",      category: ShaderCategory::Abstract,   performance: PerformanceTier::Light,  is_waveform: false },
     ShaderInfo { id: "bars_3d",           description: "3-D spectrum bars rising from the bottom",                category: ShaderCategory::Spectrum,   performance: PerformanceTier::Medium, is_waveform: false },
+    ShaderInfo { id: "boids",             description: "Compute-simulated flock that scatters on the beat",      category: ShaderCategory::Abstract,   performance: PerformanceTier::Medium, is_waveform: false },
     ShaderInfo { id: "circular_spectrum", description: "Radial bars arranged in a circle, mirrored",             category: ShaderCategory::Spectrum,   performance: PerformanceTier::Medium, is_waveform: false },
+    ShaderInfo { id: "cover_warp",        description: "Assigned image filling the screen, rippled by the spectrum", category: ShaderCategory::Abstract, performance: PerformanceTier::Light, is_waveform: false },
     ShaderInfo { id: "cubes_3d",          description: "Perspective grid of cubes that grow with the beat",      category: ShaderCategory::Geometry3D, performance: PerformanceTier::Heavy,  is_waveform: false },
     ShaderInfo { id: "depth_wave_3d",     description: "Rippling wave surface in perspective space",             category: ShaderCategory::Abstract,   performance: PerformanceTier::Heavy,  is_waveform: false },
+    ShaderInfo { id: "echo_zoom",         description: "Spectrum ring echoed outwards through its past frames",  category: ShaderCategory::Abstract,   performance: PerformanceTier::Medium, is_waveform: false },
     ShaderInfo { id: "energy_field",      description: "Electric plasma field that pulses with the beat",        category: ShaderCategory::Abstract,   performance: PerformanceTier::Medium, is_waveform: false },
     ShaderInfo { id: "gradient_bars",     description: "Spectrum bars with a smooth colour gradient",            category: ShaderCategory::Spectrum,   performance: PerformanceTier::Light,  is_waveform: false },
     ShaderInfo { id: "heatmap",           description: "Cold-to-hot thermal colour gradient per frequency",      category: ShaderCategory::Heatmap,    performance: PerformanceTier::Light,  is_waveform: false },
     ShaderInfo { id: "kaleidoscope",      description: "Mirrored kaleidoscope pattern driven by bass energy",    category: ShaderCategory::Spectrum,   performance: PerformanceTier::Medium, is_waveform: false },
+    ShaderInfo { id: "logo_pulse",        description: "Assigned image pulsing with the bass inside a spectrum ring", category: ShaderCategory::Abstract, performance: PerformanceTier::Light, is_waveform: false },
     ShaderInfo { id: "mandala",           description: "Rotating mandala pattern with beat highlights",          category: ShaderCategory::Abstract,   performance: PerformanceTier::Medium, is_waveform: false },
     ShaderInfo { id: "neon_pulse",        description: "Neon-glow waveform that pulses bright on every beat",    category: ShaderCategory::Waveform,   performance: PerformanceTier::Light,  is_waveform: true  },
     ShaderInfo { id: "oscilloscope",      description: "Classic X-Y oscilloscope waveform trace",               category: ShaderCategory::Waveform,   performance: PerformanceTier::Light,  is_waveform: true  },
     ShaderInfo { id: "plasma_sphere_3d",  description: "Animated plasma sphere with frequency-driven colours",  category: ShaderCategory::Geometry3D, performance: PerformanceTier::Heavy,  is_waveform: false },
+    ShaderInfo { id: "pulse_sphere",      description: "Lit sphere mesh pushed outwards by the spectrum",       category: ShaderCategory::Geometry3D, performance: PerformanceTier::Light,  is_waveform: false },
+    ShaderInfo { id: "reaction_diffusion", description: "Gray-Scott reaction-diffusion seeded by the spectrum", category: ShaderCategory::Abstract,   performance: PerformanceTier::Medium, is_waveform: false },
     ShaderInfo { id: "ripple",            description: "Concentric ripples that expand on each beat",           category: ShaderCategory::Abstract,   performance: PerformanceTier::Light,  is_waveform: false },
     ShaderInfo { id: "simple_bars",       description: "Clean minimal spectrum bars, fast and clear",           category: ShaderCategory::Spectrum,   performance: PerformanceTier::Light,  is_waveform: false },
     ShaderInfo { id: "spectrum",          description: "Simple full-width frequency spectrum bars",             category: ShaderCategory::Spectrum,   performance: PerformanceTier::Light,  is_waveform: false },
+    ShaderInfo { id: "spectrum_city",     description: "Rasterised towers rising with each frequency band",     category: ShaderCategory::Geometry3D, performance: PerformanceTier::Light,  is_waveform: false },
     ShaderInfo { id: "sphere_3d",         description: "Rotating sphere with surface deformed by audio",        category: ShaderCategory::Geometry3D, performance: PerformanceTier::Heavy,  is_waveform: false },
     ShaderInfo { id: "terrain_3d",        description: "Procedural terrain that rises with the bass",           category: ShaderCategory::Geometry3D, performance: PerformanceTier::Heavy,  is_waveform: false },
     ShaderInfo { id: "tunnel_3d",         description: "Infinite tunnel with walls pulsing to the beat",        category: ShaderCategory::Abstract,   performance: PerformanceTier::Medium, is_waveform: false },
@@ -77,3 +89,8 @@ pub const SHADER_REGISTRY: &[ShaderInfo] = &[
 pub fn shader_info(id: &str) -> Option<&'static ShaderInfo> {
     SHADER_REGISTRY.iter().find(|s| s.id == id)
 }
+
+/// Category of `id`; shaders missing from the registry count as abstract.
+pub fn shader_category(id: &str) -> ShaderCategory {
+    shader_info(id).map_or(ShaderCategory::Abstract, |info| info.category)
+}
@@ -0,0 +1,50 @@
+//! Shadertoy-style GLSL import
+//!
+//! Imported shaders only define `mainImage`; they are wrapped with the
+//! Shadertoy inputs and a `main()` that flips `fragCoord` to Shadertoy's
+//! bottom-left origin, then translated to naga IR through the GLSL frontend.
+
+use crate::common::error::{AppError, AppResult};
+
+const HEADER: &str = include_str!("../../shaders/shadertoy/common_shadertoy.glsl");
+
+const FOOTER: &str = "
+void main() {
+    vec4 st_color = vec4(0.0);
+    mainImage(st_color, vec2(gl_FragCoord.x, iResolution.y - gl_FragCoord.y));
+    st_frag_color = vec4(st_color.rgb, 1.0);
+}
+";
+
+/// Wrap a Shadertoy fragment shader and translate it to a validated naga module.
+///
+/// Errors report line numbers relative to the user's source, not the wrapper.
+pub fn translate(source: &str) -> AppResult<naga::Module> {
+    let wrapped = format!("{HEADER}\n{source}\n{FOOTER}");
+    let header_lines = HEADER.lines().count() as u32 + 1;
+    let user_line = |span: naga::Span| {
+        let line = span.location(&wrapped).line_number;
+        line.saturating_sub(header_lines).max(1)
+    };
+
+    let mut frontend = naga::front::glsl::Frontend::default();
+    let options = naga::front::glsl::Options::from(naga::ShaderStage::Fragment);
+    let module = frontend.parse(&options, &wrapped).map_err(|errors| {
+        let messages: Vec<String> = errors.iter()
+            .map(|e| format!("line {}: {}", user_line(e.meta), e.kind))
+            .collect();
+        AppError::Plugin(messages.join("; "))
+    })?;
+
+    naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+        .validate(&module)
+        .map_err(|e| {
+            let line = e.spans().next().map(|(span, _)| user_line(*span));
+            match line {
+                Some(line) => AppError::Plugin(format!("line {}: {}", line, e.as_inner())),
+                None => AppError::Plugin(e.as_inner().to_string()),
+            }
+        })?;
+
+    Ok(module)
+}
@@ -0,0 +1,98 @@
+//! WGSL checks for shaders loaded or edited at runtime
+//!
+//! wgpu treats an invalid shader as a fatal device error, so user files and
+//! live edits are parsed and validated with naga first. Errors carry line
+//! numbers in the shader's own source, after the common preamble.
+
+/// One problem found in a shader.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceError {
+    /// 1-based line in the shader source; `None` when the error lies in the
+    /// preamble or has no location.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for SourceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None       => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Parse and validate `body` joined to `preamble` the way the loader joins
+/// them, and require the given entry points.
+pub fn check(preamble: &str, body: &str, entry_points: &[&str]) -> Result<(), SourceError> {
+    let source = format!("{}\n{}", preamble, body);
+    // The body starts on the line after the preamble's last one.
+    let body_start = preamble.matches('\n').count() + 2;
+    let body_line = |line: u32| (line as usize).checked_sub(body_start).map(|l| l + 1);
+
+    let module = naga::front::wgsl::parse_str(&source).map_err(|e| {
+        let labels: Vec<&str> = e.labels().map(|(_, label)| label).filter(|l| !l.is_empty()).collect();
+        let message = if labels.is_empty() { e.message().to_string() } else { format!("{} ({})", e.message(), labels.join(", ")) };
+        SourceError { line: e.location(&source).and_then(|l| body_line(l.line_number)), message }
+    })?;
+
+    naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+        .validate(&module)
+        .map_err(|e| {
+            // The innermost cause names the actual problem.
+            let mut message = e.as_inner().to_string();
+            let mut cause = std::error::Error::source(e.as_inner());
+            while let Some(inner) = cause {
+                message = format!("{}: {}", message, inner);
+                cause = inner.source();
+            }
+            SourceError { line: e.location(&source).and_then(|l| body_line(l.line_number)), message }
+        })?;
+
+    match entry_points.iter().find(|name| !module.entry_points.iter().any(|ep| ep.name == **name)) {
+        Some(missing) => Err(SourceError { line: None, message: format!("missing entry point `{}`", missing) }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::types::{AudioFeatures, VisUniforms, AUDIO_FEATURES_VERSION};
+
+    const UNIFORMS: &str = include_str!("../../shaders/uniforms.wgsl");
+
+    /// Compare the members naga lays out for struct `name` in `module` with
+    /// a Rust struct's `FIELDS` and size.
+    fn check_layout(module: &naga::Module, name: &str, fields: &[(&str, usize)], size: usize) -> Result<(), String> {
+        let inner = module.types.iter()
+            .find(|(_, ty)| ty.name.as_deref() == Some(name))
+            .map(|(_, ty)| &ty.inner)
+            .ok_or_else(|| format!("no struct {} in uniforms.wgsl", name))?;
+        let naga::TypeInner::Struct { members, span } = inner else {
+            return Err(format!("{} is not a struct", name));
+        };
+        let wgsl: Vec<(&str, usize)> = members.iter()
+            .map(|m| (m.name.as_deref().unwrap_or_default(), m.offset as usize))
+            .collect();
+        if wgsl != fields {
+            return Err(format!("{} fields differ:\n  WGSL {:?}\n  Rust {:?}", name, wgsl, fields));
+        }
+        if *span as usize != size {
+            return Err(format!("{} is {} bytes in WGSL but {} in Rust", name, span, size));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn uniforms_match_wgsl() {
+        let module = naga::front::wgsl::parse_str(UNIFORMS).expect("uniforms.wgsl parses");
+        check_layout(&module, "Uniforms", VisUniforms::FIELDS, std::mem::size_of::<VisUniforms>()).unwrap();
+        check_layout(&module, "AudioFeatures", AudioFeatures::FIELDS, std::mem::size_of::<AudioFeatures>()).unwrap();
+
+        let version = module.constants.iter()
+            .find(|(_, c)| c.name.as_deref() == Some("AUDIO_FEATURES_VERSION"))
+            .map(|(_, c)| &module.const_expressions[c.init])
+            .expect("AUDIO_FEATURES_VERSION is declared");
+        assert!(matches!(version, naga::Expression::Literal(naga::Literal::U32(v)) if *v == AUDIO_FEATURES_VERSION), "{:?}", version);
+    }
+}